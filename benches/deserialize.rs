@@ -0,0 +1,106 @@
+#[macro_use]
+extern crate bencher;
+extern crate gob;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_gob;
+#[macro_use]
+extern crate serde_gob_derive;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bencher::Bencher;
+use gob::{StreamDeserializer, StreamSerializer};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const RECORDS: usize = 1000;
+
+#[derive(Serialize, Deserialize, GobSerialize, Default)]
+struct Record {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Seq")]
+    seq: u64,
+    #[serde(rename = "Tags")]
+    tags: Vec<String>,
+}
+
+fn records() -> Vec<u8> {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for seq in 1..=RECORDS as u64 {
+            let record = Record {
+                name: format!("record-{}", seq),
+                seq,
+                tags: vec!["alpha".to_owned(), "beta".to_owned(), "gamma".to_owned()],
+            };
+            stream.serialize(&record).unwrap();
+        }
+    }
+    buffer
+}
+
+fn decode_fresh(buffer: &[u8]) {
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    let mut count = 0;
+    while let Some(record) = stream.deserialize::<Record>().unwrap() {
+        count += record.tags.len();
+    }
+    assert_eq!(count, RECORDS * 3);
+}
+
+fn decode_in_place(buffer: &[u8]) {
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    let mut record = Record::default();
+    let mut count = 0;
+    while stream.decode_into_existing(&mut record).unwrap() {
+        count += record.tags.len();
+    }
+    assert_eq!(count, RECORDS * 3);
+}
+
+fn allocations(f: fn(&[u8]), buffer: &[u8]) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f(buffer);
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn fresh_values(bench: &mut Bencher) {
+    let buffer = records();
+    let allocs = allocations(decode_fresh, &buffer);
+    eprintln!("fresh values: {} allocations for {} records", allocs, RECORDS);
+    bench.iter(|| decode_fresh(&buffer));
+}
+
+fn in_place(bench: &mut Bencher) {
+    let buffer = records();
+    let allocs = allocations(decode_in_place, &buffer);
+    eprintln!("in place: {} allocations for {} records", allocs, RECORDS);
+    // only the first record allocates its strings
+    assert!(allocs < allocations(decode_fresh, &buffer) / 10);
+    bench.iter(|| decode_in_place(&buffer));
+}
+
+benchmark_group!(benches, fresh_values, in_place);
+benchmark_main!(benches);
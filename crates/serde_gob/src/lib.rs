@@ -5,6 +5,9 @@ extern crate serde;
 #[cfg(feature = "bytes")]
 extern crate serde_bytes;
 
+#[cfg(feature = "indexmap")]
+extern crate indexmap;
+
 pub mod types;
 
 mod schema;
@@ -2,15 +2,24 @@ use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
 use std::hash::{BuildHasher, Hash};
 use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::num::{Saturating, Wrapping};
 
 use serde::ser::Serialize;
 
 #[cfg(feature = "bytes")]
 use serde_bytes::{ByteBuf, Bytes};
 
+#[cfg(feature = "indexmap")]
+use indexmap::IndexMap;
+
 use types::*;
 use Schema;
 
+#[diagnostic::on_unimplemented(
+    note = "derive `GobSerialize` for structs and enums with `#[derive(GobSerialize)]`",
+    note = "tuples implement `GobSerialize` with up to 16 elements, like they implement `Serialize`; use a struct for more"
+)]
 pub trait GobSerialize: Serialize {
     fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error>;
 }
@@ -89,6 +98,38 @@ impl<T: GobSerialize> GobSerialize for Option<T> {
     }
 }
 
+// ## Result
+
+/// A `Result` is sent as a struct tagged with whether it is `Ok`, which a
+/// Go program can read as
+///
+/// ```go
+/// type Result struct {
+///     Ok    bool
+///     Value T
+///     Error E
+/// }
+/// ```
+///
+/// where `Value` is set for `Ok` and `Error` for `Err`. Like any struct
+/// field, each is left out if it holds its zero value, so `Err` with a zero
+/// error is sent as an empty struct.
+impl<T: GobSerialize, E: GobSerialize> GobSerialize for Result<T, E> {
+    #[inline]
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let value = T::schema_register(schema)?;
+        let error = E::schema_register(schema)?;
+        schema.register_type(
+            Type::build()
+                .struct_type("Result", 3)
+                .field("Ok", S::TypeId::BOOL)
+                .field("Value", value)
+                .field("Error", error)
+                .end(),
+        )
+    }
+}
+
 // ## PhantomData
 
 impl<T> GobSerialize for PhantomData<T> {
@@ -103,27 +144,20 @@ impl<T> GobSerialize for PhantomData<T> {
 
 // ## Arrays
 
-macro_rules! array_impls {
-    {$($len:tt)+} => {
-        $(
-            impl<T: GobSerialize> GobSerialize for [T; $len] {
-                #[inline]
-                fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
-                    let id = T::schema_register(schema)?;
-                    schema.register_type(Type::Seq(SeqType { len: Some($len), element: id }))
-                }
-            }
-        )+
+/// Arrays of any length `N` are sent as a Go `[N]T`, as long as they
+/// implement `Serialize`, which serde does for lengths up to 32. Longer
+/// arrays need a wrapper implementing it, like `gob::go::Array`.
+impl<T: GobSerialize, const N: usize> GobSerialize for [T; N]
+where
+    [T; N]: Serialize,
+{
+    #[inline]
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let id = T::schema_register(schema)?;
+        schema.register_type(Type::Seq(SeqType { len: Some(N), element: id }))
     }
 }
 
-array_impls! {
-    00 01 02 03 04 05 06 07 08 09
-    10 11 12 13 14 15 16 17 18 19
-    20 21 22 23 24 25 26 27 28 29
-    30 31 32
-}
-
 // ## Slices
 
 impl<T: GobSerialize> GobSerialize for [T] {
@@ -185,6 +219,25 @@ impl<Idx: GobSerialize> GobSerialize for ::std::ops::Range<Idx> {
     }
 }
 
+// ## IP Addresses
+
+// Go's `net.IP` marshals itself as text, the same text `Serialize` writes
+// to a human-readable format such as gob.
+macro_rules! ip_addr_impl {
+    ($ty:ty) => {
+        impl GobSerialize for $ty {
+            #[inline]
+            fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                schema.register_type(Type::build().encoded_type("IP", Marshaler::TextMarshaler))
+            }
+        }
+    };
+}
+
+ip_addr_impl!(IpAddr);
+ip_addr_impl!(Ipv4Addr);
+ip_addr_impl!(Ipv6Addr);
+
 // ## Unit
 
 impl GobSerialize for () {
@@ -217,6 +270,8 @@ macro_rules! tuple_impls {
     }
 }
 
+// Serde implements `Serialize` for tuples of up to 16 elements, which is
+// required here as well, so there is no point in going beyond that.
 tuple_impls! {
     1 => (0 T0)
     2 => (0 T0 1 T1)
@@ -262,6 +317,8 @@ macro_rules! map_impl {
 
 map_impl!(BTreeMap<K: Ord, V>);
 map_impl!(HashMap<K: Eq + Hash, V, H: BuildHasher>);
+#[cfg(feature = "indexmap")]
+map_impl!(IndexMap<K: Eq + Hash, V, H: BuildHasher>);
 
 // ## References
 
@@ -291,4 +348,20 @@ impl<'a, T: GobSerialize + ToOwned + ?Sized> GobSerialize for Cow<'a, T> {
     fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
         T::schema_register(schema)
     }
-}
\ No newline at end of file
+}
+
+// ## Wrappers
+
+impl<T: GobSerialize> GobSerialize for Wrapping<T> {
+    #[inline]
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        T::schema_register(schema)
+    }
+}
+
+impl<T: GobSerialize> GobSerialize for Saturating<T> {
+    #[inline]
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        T::schema_register(schema)
+    }
+}
@@ -262,4 +262,13 @@ impl<T: TypeId> TypeBuilder<T> {
     pub fn enum_type(self, name: &'static str, len: usize) -> EnumBuilder<T> {
         EnumBuilder::new(name, len)
     }
+
+    #[inline]
+    pub fn encoded_type(self, name: &'static str, marshaler: Marshaler) -> Type<T> {
+        Type::Encoded(EncodedType {
+            _phan: PhantomData,
+            name: Cow::Borrowed(name),
+            marshaler,
+        })
+    }
 }
\ No newline at end of file
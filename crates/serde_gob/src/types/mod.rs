@@ -284,6 +284,33 @@ impl<T: TypeId> EnumType<T> {
     }
 }
 
+/// The Go interface through which a type encodes its own values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Marshaler {
+    GobEncoder,
+    BinaryMarshaler,
+    TextMarshaler,
+}
+
+/// A type that encodes itself, whose values are sent as the bytes its
+/// encoding method returns.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EncodedType<T: TypeId> {
+    pub(crate) _phan: PhantomData<T>,
+    pub(crate) name: Cow<'static, str>,
+    pub(crate) marshaler: Marshaler,
+}
+
+impl<T: TypeId> EncodedType<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn marshaler(&self) -> Marshaler {
+        self.marshaler
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Type<T: TypeId> {
     Option(OptionType<T>),
@@ -295,6 +322,7 @@ pub enum Type<T: TypeId> {
     Map(MapType<T>),
     Struct(StructType<T>),
     Enum(EnumType<T>),
+    Encoded(EncodedType<T>),
 }
 
 impl<T: TypeId> Type<T> {
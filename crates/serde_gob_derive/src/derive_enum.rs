@@ -1,12 +1,21 @@
-use serde_derive_internals::{ast, attr};
+use serde_derive_internals::ast;
 
+use super::derive_struct::derive_struct_underlying;
 use super::{derive_element, derive_field, derive_register_field_types, variant_field_type_variable};
 
 pub fn derive_enum<'a>(
     variants: Vec<ast::Variant<'a>>,
-    attr_container: &attr::Container,
+    name: &str,
 ) -> proc_macro2::TokenStream {
-    let name = attr_container.name().serialize_name();
+    // a C-like enum is sent as the name of its variant, like a Go string
+    // type with a constant for each variant
+    if is_c_like(&variants) {
+        return quote!{
+            let _ = schema;
+            Ok(<S::TypeId as ::gob::types::TypeId>::STR)
+        };
+    }
+
     let len = variants.len();
 
     let mut expanded_type_ids = quote!{};
@@ -45,6 +54,23 @@ pub fn derive_enum<'a>(
     }
 }
 
+/// Whether the enum has variants, all of them without fields.
+pub fn is_c_like(variants: &[ast::Variant]) -> bool {
+    !variants.is_empty() && variants.iter().all(|variant| matches!(variant.style, ast::Style::Unit))
+}
+
+/// Registers a C-like enum as a Go integer type like `type Color int`, to
+/// send the index of its variant, or as a string type to send its name.
+///
+/// The index counts from 0 in declaration order, like a Go `iota`, and is
+/// not the variant's discriminant, which serde does not pass on.
+pub fn derive_enum_underlying(underlying: &str) -> Option<proc_macro2::TokenStream> {
+    match underlying {
+        "bool" | "float32" | "float64" | "[]byte" => None,
+        _ => derive_struct_underlying(underlying),
+    }
+}
+
 fn derive_unit_variant<'a>(variant_name: &str) -> proc_macro2::TokenStream {
     quote!{
         .unit_variant(#variant_name)
@@ -1,25 +1,24 @@
-use serde_derive_internals::{ast, attr};
+use serde_derive_internals::ast;
 
 use super::{derive_element, derive_field, derive_register_field_types, variant_field_type_variable};
 
 pub fn derive_struct<'a>(
     style: ast::Style,
     fields: Vec<ast::Field<'a>>,
-    attr_container: &attr::Container,
+    name: &str,
 ) -> proc_macro2::TokenStream {
     match style {
-        ast::Style::Struct => derive_struct_named_fields(fields, attr_container),
-        ast::Style::Newtype => derive_struct_newtype(fields, attr_container),
-        ast::Style::Tuple => derive_struct_tuple(fields, attr_container),
-        ast::Style::Unit => derive_struct_unit(attr_container),
+        ast::Style::Struct => derive_struct_named_fields(fields, name),
+        ast::Style::Newtype => derive_struct_newtype(fields, name),
+        ast::Style::Tuple => derive_struct_tuple(fields, name),
+        ast::Style::Unit => derive_struct_unit(name),
     }
 }
 
 fn derive_struct_newtype<'a>(
     fields: Vec<ast::Field<'a>>,
-    attr_container: &attr::Container,
+    name: &str,
 ) -> proc_macro2::TokenStream {
-    let name = attr_container.name().serialize_name();
     let expanded_type_ids = derive_register_field_types(0, fields.iter());
     let type_id_ident = variant_field_type_variable(0, 0);
     quote!{
@@ -30,8 +29,32 @@ fn derive_struct_newtype<'a>(
     }
 }
 
-fn derive_struct_unit(attr_container: &attr::Container) -> proc_macro2::TokenStream {
-    let name = attr_container.name().serialize_name();
+/// Registers a newtype as the Go basic type named `underlying`, returning
+/// `None` for names that are not a basic type.
+pub fn derive_struct_underlying(underlying: &str) -> Option<proc_macro2::TokenStream> {
+    let id = match underlying {
+        "bool" => quote!(BOOL),
+        "int8" => quote!(I8),
+        "int16" => quote!(I16),
+        "int32" | "rune" => quote!(I32),
+        "int" | "int64" => quote!(I64),
+        "uint8" | "byte" => quote!(U8),
+        "uint16" => quote!(U16),
+        "uint32" => quote!(U32),
+        "uint" | "uint64" | "uintptr" => quote!(U64),
+        "float32" => quote!(F32),
+        "float64" => quote!(F64),
+        "string" => quote!(STR),
+        "[]byte" => quote!(BYTES),
+        _ => return None,
+    };
+    Some(quote!{
+        let _ = schema;
+        Ok(<S::TypeId as ::gob::types::TypeId>::#id)
+    })
+}
+
+fn derive_struct_unit(name: &str) -> proc_macro2::TokenStream {
     quote!{
         ::gob::Schema::register_type(schema,
             ::gob::types::Type::build().unit_struct_type(#name))
@@ -40,10 +63,9 @@ fn derive_struct_unit(attr_container: &attr::Container) -> proc_macro2::TokenStr
 
 fn derive_struct_named_fields<'a>(
     fields: Vec<ast::Field<'a>>,
-    attr_container: &attr::Container,
+    name: &str,
 ) -> proc_macro2::TokenStream {
     let len = fields.len();
-    let name = attr_container.name().serialize_name();
 
     let expanded_type_ids = derive_register_field_types(0, fields.iter());
 
@@ -66,10 +88,9 @@ fn derive_struct_named_fields<'a>(
 
 fn derive_struct_tuple<'a>(
     fields: Vec<ast::Field<'a>>,
-    attr_container: &attr::Container,
+    name: &str,
 ) -> proc_macro2::TokenStream {
     let len = fields.len();
-    let name = attr_container.name().serialize_name();
 
     let expanded_type_ids = derive_register_field_types(0, fields.iter());
 
@@ -19,9 +19,34 @@ pub fn derive_gob_serialize(input: proc_macro::TokenStream) -> proc_macro::Token
     let cx = Ctxt::new();
     let container = ast::Container::from_ast(&cx, &input, serde_derive_internals::Derive::Serialize).unwrap();
 
-    let interpret_as = get_interpret_as(&input.attrs);
+    // `Instant` does not implement `Serialize` either, but the error about
+    // the missing impls does not say why or what to send instead
+    if let Some(instant) = find_instant_field(&container.data) {
+        cx.check().unwrap();
+        return syn::Error::new_spanned(
+            instant,
+            "`Instant` cannot be serialized, as it only has a meaning within the running \
+             process; send a `SystemTime`, like the duration since `UNIX_EPOCH`, instead",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let interpret_as = get_gob_attr(&input.attrs, "interpret_as");
+    let underlying = get_gob_attr(&input.attrs, "underlying");
+    let go_name = get_gob_attr(&input.attrs, "go_name");
 
     let inner_impl = if let Some(interpret_as_str) = interpret_as {
+        // the fields become the map's entries, which needs them to be named
+        if !matches!(container.data, ast::Data::Struct(ast::Style::Struct, _)) {
+            cx.check().unwrap();
+            return syn::Error::new_spanned(
+                &input.ident,
+                "`interpret_as` is only supported on structs with named fields",
+            )
+            .to_compile_error()
+            .into();
+        }
         if interpret_as_str == "map[interface{}]interface{}" {
             quote!{
                 ::gob::Schema::register_type(schema,
@@ -38,12 +63,69 @@ pub fn derive_gob_serialize(input: proc_macro::TokenStream) -> proc_macro::Token
              // Let's error to be safe.
              panic!("Unsupported interpret_as value: {}", interpret_as_str);
         }
+    } else if let Some(underlying) = underlying {
+        // a named basic type like `type Timeout int64`, which Go sends as
+        // its underlying type, without a definition or its name
+        if go_name.is_some() {
+            cx.check().unwrap();
+            return syn::Error::new_spanned(
+                &input.ident,
+                "`go_name` is not supported with `underlying`, as Go sends such a type without its name",
+            )
+            .to_compile_error()
+            .into();
+        }
+        let expanded = match container.data {
+            ast::Data::Struct(ast::Style::Newtype, _) => {
+                derive_struct::derive_struct_underlying(&underlying)
+            }
+            ast::Data::Enum(ref variants) if derive_enum::is_c_like(variants) => {
+                // serde only hands the serializer the variant's index, so an
+                // explicit discriminant would silently not be what Go gets
+                let discriminant = variants
+                    .iter()
+                    .find_map(|variant| variant.original.discriminant.as_ref())
+                    .filter(|_| underlying != "string");
+                if let Some((_, discriminant)) = discriminant {
+                    cx.check().unwrap();
+                    return syn::Error::new_spanned(
+                        discriminant,
+                        "explicit discriminants are not sent with an integer `underlying` type, \
+                         as the index of the variant is sent instead",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+                derive_enum::derive_enum_underlying(&underlying)
+            }
+            _ => {
+                cx.check().unwrap();
+                return syn::Error::new_spanned(
+                    &input.ident,
+                    "`underlying` is only supported on newtype structs and enums of unit variants",
+                )
+                .to_compile_error()
+                .into();
+            }
+        };
+        match expanded {
+            Some(expanded) => expanded,
+            None => {
+                cx.check().unwrap();
+                return syn::Error::new_spanned(
+                    &input.ident,
+                    format!("unsupported underlying type `{}`", underlying),
+                )
+                .to_compile_error()
+                .into();
+            }
+        }
     } else {
+        // the name Go knows the type by, which may be package-qualified
+        let name = go_name.unwrap_or_else(|| container.attrs.name().serialize_name().to_string());
         match container.data {
-            ast::Data::Enum(variants) => derive_enum::derive_enum(variants, &container.attrs),
-            ast::Data::Struct(style, fields) => {
-                derive_struct::derive_struct(style, fields, &container.attrs)
-            }
+            ast::Data::Enum(variants) => derive_enum::derive_enum(variants, &name),
+            ast::Data::Struct(style, fields) => derive_struct::derive_struct(style, fields, &name),
         }
     };
 
@@ -65,19 +147,21 @@ pub fn derive_gob_serialize(input: proc_macro::TokenStream) -> proc_macro::Token
     expanded.into()
 }
 
-fn get_interpret_as(attrs: &[syn::Attribute]) -> Option<String> {
+/// Returns the value of `#[gob(key = "...")]`, if present.
+fn get_gob_attr(attrs: &[syn::Attribute], key: &str) -> Option<String> {
     for attr in attrs {
         if attr.path().is_ident("gob") {
             let mut res = None;
             let _ = attr.parse_nested_meta(|meta| {
-                if meta.path.is_ident("interpret_as") {
+                if meta.path.is_ident(key) {
                     let value = meta.value()?;
                     let s: syn::LitStr = value.parse()?;
                     res = Some(s.value());
-                    Ok(())
-                } else {
-                    Ok(())
+                } else if meta.input.peek(syn::Token![=]) {
+                    // skip the values of other keys
+                    let _: syn::Expr = meta.value()?.parse()?;
                 }
+                Ok(())
             });
             if res.is_some() {
                 return res;
@@ -87,6 +171,44 @@ fn get_interpret_as(attrs: &[syn::Attribute]) -> Option<String> {
     None
 }
 
+/// Returns the first `Instant` in the types of the fields, including
+/// their type arguments, like the one in `Option<Instant>`.
+fn find_instant_field<'a>(data: &ast::Data<'a>) -> Option<&'a syn::Type> {
+    let fields: Box<dyn Iterator<Item = &ast::Field<'a>>> = match *data {
+        ast::Data::Enum(ref variants) => Box::new(variants.iter().flat_map(|variant| &variant.fields)),
+        ast::Data::Struct(_, ref fields) => Box::new(fields.iter()),
+    };
+    fields.map(|field| field.ty).find_map(find_instant)
+}
+
+fn find_instant(ty: &syn::Type) -> Option<&syn::Type> {
+    match *ty {
+        syn::Type::Path(ref path) => {
+            let last = path.path.segments.last()?;
+            // only the std type, so that other types named `Instant` with
+            // their own impls can still be sent
+            let segments = path.path.segments.iter().map(|segment| segment.ident.to_string()).collect::<Vec<_>>();
+            if ["Instant", "time::Instant", "std::time::Instant"].contains(&&*segments.join("::")) {
+                return Some(ty);
+            }
+            match last.arguments {
+                syn::PathArguments::AngleBracketed(ref args) => args.args.iter().find_map(|arg| match *arg {
+                    syn::GenericArgument::Type(ref ty) => find_instant(ty),
+                    _ => None,
+                }),
+                _ => None,
+            }
+        }
+        syn::Type::Array(ref array) => find_instant(&array.elem),
+        syn::Type::Slice(ref slice) => find_instant(&slice.elem),
+        syn::Type::Reference(ref reference) => find_instant(&reference.elem),
+        syn::Type::Paren(ref paren) => find_instant(&paren.elem),
+        syn::Type::Group(ref group) => find_instant(&group.elem),
+        syn::Type::Tuple(ref tuple) => tuple.elems.iter().find_map(find_instant),
+        _ => None,
+    }
+}
+
 fn variant_field_type_variable(variant_idx: usize, field_idx: usize) -> syn::Ident {
     syn::Ident::new(&format!("type_id_{}_{}", variant_idx, field_idx), proc_macro2::Span::call_site())
 }
@@ -0,0 +1,20 @@
+//! Reads the first message header of a gitea session blob by hand.
+
+extern crate gob;
+
+use gob::Reader;
+
+fn main() -> Result<(), gob::Error> {
+    let blob = include_bytes!("../tests/normal-session-2.bin");
+    let mut reader = Reader::new(blob);
+
+    let len = reader.read_uint()?;
+    let type_id = reader.read_int()?;
+    if type_id < 0 {
+        // the session map's type definition
+        println!("{} byte definition of type {}", len, -type_id);
+    } else {
+        println!("{} byte value of type {}", len, type_id);
+    }
+    Ok(())
+}
@@ -0,0 +1,339 @@
+//! Schema compatibility checks
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Cursor;
+
+use bytes::Buf;
+use serde_gob::GobSerialize;
+
+use crate::de::{Types, read_schema};
+use crate::internal::types::{TypeId, WireType};
+use crate::schema::Schema;
+use crate::ser::OutputBuffer;
+
+/// A difference between the schema of a Rust type and the type definitions
+/// received from a gob stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The received definitions contain no type matching the Rust type.
+    MissingType { name: String },
+    /// The received struct at `path` lacks a field the Rust type expects.
+    MissingField { path: String },
+    /// The received type at `path` is not the one the Rust type expects.
+    TypeMismatch {
+        path: String,
+        expected: String,
+        found: String,
+    },
+    /// The schema of the Rust type could not be determined.
+    Unsupported { reason: String },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::MissingType { name } => write!(f, "no received type matches {}", name),
+            Mismatch::MissingField { path } => write!(f, "missing field {}", path),
+            Mismatch::TypeMismatch {
+                path,
+                expected,
+                found,
+            } => write!(f, "{}: expected {}, found {}", path, expected, found),
+            Mismatch::Unsupported { reason } => write!(f, "unsupported type: {}", reason),
+        }
+    }
+}
+
+/// How the received definitions of a struct differ from the Rust type,
+/// field by field. Fields are named by their path from the struct, like
+/// `User.Address.City`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    /// Received fields the Rust type does not have, which are skipped when
+    /// decoding.
+    pub added: Vec<String>,
+    /// Fields of the Rust type that were not received.
+    pub removed: Vec<String>,
+    /// Fields received with a type other than the one the Rust type
+    /// expects.
+    pub changed: Vec<FieldChange>,
+}
+
+/// A field whose received type differs from the expected one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    pub path: String,
+    pub expected: String,
+    pub found: String,
+}
+
+impl SchemaDiff {
+    /// Whether the received struct has exactly the fields of the Rust type.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for path in &self.added {
+            writeln!(f, "+ {}", path)?;
+        }
+        for path in &self.removed {
+            writeln!(f, "- {}", path)?;
+        }
+        for change in &self.changed {
+            writeln!(
+                f,
+                "~ {}: expected {}, found {}",
+                change.path, change.expected, change.found
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks whether values of the received types can be decoded into `T`.
+///
+/// A struct is matched by name, any other composite type by its structure.
+/// Every field of the Rust type has to be present in the received struct
+/// with a compatible type; additional received fields are fine, since they
+/// are skipped when decoding. Slices and arrays are interchangeable, except
+/// for arrays of different lengths. A primitive `T` is always compatible,
+/// as no definition is sent for it.
+pub fn check_compatibility<T: GobSerialize>(received: &Types) -> Result<(), Vec<Mismatch>> {
+    let (expected, root) = expected_types::<T>().map_err(|reason| {
+        vec![Mismatch::Unsupported {
+            reason: reason.to_string(),
+        }]
+    })?;
+
+    let root_def = match expected.lookup(root) {
+        Some(def) => def,
+        None => return Ok(()),
+    };
+
+    if let WireType::Struct(struct_type) = root_def {
+        let name = &*struct_type.common.name;
+        return match received.ids().find(|&id| received.name(id) == Some(name)) {
+            Some(id) => Checker::new(&expected, received).run(name, root, id),
+            None => Err(vec![Mismatch::MissingType {
+                name: name.to_owned(),
+            }]),
+        };
+    }
+
+    for id in received.ids() {
+        if Checker::new(&expected, received).run("", root, id).is_ok() {
+            return Ok(());
+        }
+    }
+    Err(vec![Mismatch::MissingType {
+        name: describe(&expected, root),
+    }])
+}
+
+/// Compares the fields of the struct `T` with those of the received struct
+/// of the same name, such as after a Go service renamed a field.
+///
+/// Unlike `check_compatibility`, this also lists the received fields `T`
+/// does not have. Types other than structs are matched as a whole and
+/// have no fields to differ in, so their diff is empty if they are
+/// compatible.
+pub fn schema_diff<T: GobSerialize>(received: &Types) -> Result<SchemaDiff, Mismatch> {
+    let (expected, root) = expected_types::<T>().map_err(|reason| Mismatch::Unsupported {
+        reason: reason.to_string(),
+    })?;
+
+    let name = match expected.lookup(root) {
+        Some(WireType::Struct(struct_type)) => &*struct_type.common.name,
+        _ => {
+            return match check_compatibility::<T>(received) {
+                Ok(()) => Ok(SchemaDiff::default()),
+                Err(mut mismatches) => Err(mismatches.remove(0)),
+            }
+        }
+    };
+    match received.ids().find(|&id| received.name(id) == Some(name)) {
+        Some(id) => Ok(Checker::new(&expected, received).diff(name, root, id)),
+        None => Err(Mismatch::MissingType {
+            name: name.to_owned(),
+        }),
+    }
+}
+
+/// Registers `T` with a fresh schema and reads back the definitions it
+/// would send, along with the id of `T` itself.
+fn expected_types<T: GobSerialize>() -> Result<(Types, TypeId), crate::Error> {
+    let mut schema = Schema::new();
+    let root = T::schema_register(&mut schema)?;
+    let mut buffer = OutputBuffer::new();
+    schema.write_pending(&mut buffer)?;
+    let bytes: Vec<u8> = buffer.collect();
+    let types = read_schema(&mut Cursor::new(bytes))?;
+    Ok((types, root))
+}
+
+struct Checker<'a> {
+    expected: &'a Types,
+    received: &'a Types,
+    visited: HashSet<(TypeId, TypeId)>,
+    mismatches: Vec<Mismatch>,
+    added: Vec<String>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(expected: &'a Types, received: &'a Types) -> Self {
+        Checker {
+            expected,
+            received,
+            visited: HashSet::new(),
+            mismatches: Vec::new(),
+            added: Vec::new(),
+        }
+    }
+
+    fn run(mut self, path: &str, expected: TypeId, received: TypeId) -> Result<(), Vec<Mismatch>> {
+        self.check(path, expected, received);
+        if self.mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(self.mismatches)
+        }
+    }
+
+    fn diff(mut self, path: &str, expected: TypeId, received: TypeId) -> SchemaDiff {
+        self.check(path, expected, received);
+        let mut diff = SchemaDiff {
+            added: self.added,
+            ..SchemaDiff::default()
+        };
+        for mismatch in self.mismatches {
+            match mismatch {
+                Mismatch::MissingField { path } => diff.removed.push(path),
+                Mismatch::TypeMismatch {
+                    path,
+                    expected,
+                    found,
+                } => diff.changed.push(FieldChange {
+                    path,
+                    expected,
+                    found,
+                }),
+                Mismatch::MissingType { .. } | Mismatch::Unsupported { .. } => {}
+            }
+        }
+        diff
+    }
+
+    fn check(&mut self, path: &str, expected: TypeId, received: TypeId) {
+        // recursive types would otherwise be followed forever
+        if !self.visited.insert((expected, received)) {
+            return;
+        }
+
+        match (
+            self.expected.lookup(expected),
+            self.received.lookup(received),
+        ) {
+            (None, None) if expected == received => {}
+            // a type that encodes itself arrives as the bytes it encodes to
+            (
+                None,
+                Some(WireType::GobEncoder(_))
+                | Some(WireType::BinaryMarshaler(_))
+                | Some(WireType::TextMarshaler(_)),
+            ) if expected == TypeId::BYTES => {}
+            // as long as both encode themselves the same way
+            (Some(WireType::GobEncoder(_)), Some(WireType::GobEncoder(_)))
+            | (Some(WireType::BinaryMarshaler(_)), Some(WireType::BinaryMarshaler(_)))
+            | (Some(WireType::TextMarshaler(_)), Some(WireType::TextMarshaler(_))) => {}
+            (Some(WireType::Struct(e)), Some(WireType::Struct(r))) => {
+                for field in e.fields.iter() {
+                    let field_path = field_path(path, &field.name);
+                    match r.fields.iter().find(|f| f.name == field.name) {
+                        Some(received_field) => {
+                            self.check(&field_path, field.id, received_field.id)
+                        }
+                        None => self
+                            .mismatches
+                            .push(Mismatch::MissingField { path: field_path }),
+                    }
+                }
+                for field in r.fields.iter() {
+                    if !e.fields.iter().any(|f| f.name == field.name) {
+                        self.added.push(field_path(path, &field.name));
+                    }
+                }
+            }
+            (Some(WireType::Array(e)), Some(WireType::Array(r))) if e.len != r.len => {
+                self.mismatch(path, expected, received)
+            }
+            (Some(WireType::Map(e)), Some(WireType::Map(r))) => {
+                self.check(&format!("{}[key]", path), e.key, r.key);
+                self.check(&format!("{}[value]", path), e.elem, r.elem);
+            }
+            (Some(e), Some(r)) => match (elem(e), elem(r)) {
+                (Some(e), Some(r)) => self.check(&format!("{}[]", path), e, r),
+                _ => self.mismatch(path, expected, received),
+            },
+            _ => self.mismatch(path, expected, received),
+        }
+    }
+
+    fn mismatch(&mut self, path: &str, expected: TypeId, received: TypeId) {
+        self.mismatches.push(Mismatch::TypeMismatch {
+            path: path.to_owned(),
+            expected: describe(self.expected, expected),
+            found: describe(self.received, received),
+        });
+    }
+}
+
+fn field_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+/// The element type of a slice or array.
+fn elem(def: &WireType) -> Option<TypeId> {
+    match def {
+        WireType::Slice(slice_type) => Some(slice_type.elem),
+        WireType::Array(array_type) => Some(array_type.elem),
+        _ => None,
+    }
+}
+
+fn describe(types: &Types, id: TypeId) -> String {
+    let builtin = match id {
+        TypeId::BOOL => "bool",
+        TypeId::INT => "int",
+        TypeId::UINT => "uint",
+        TypeId::FLOAT => "float",
+        TypeId::BYTES => "[]byte",
+        TypeId::STRING => "string",
+        TypeId::COMPLEX => "complex",
+        TypeId::INTERFACE => "interface",
+        _ => "",
+    };
+    if !builtin.is_empty() {
+        return builtin.to_owned();
+    }
+    if let Some(name) = types.name(id) {
+        return name.to_owned();
+    }
+    match types.lookup(id) {
+        Some(WireType::Struct(_)) => "struct".to_owned(),
+        Some(WireType::Slice(_)) => "slice".to_owned(),
+        Some(WireType::Array(array_type)) => format!("array of {}", array_type.len),
+        Some(WireType::Map(_)) => "map".to_owned(),
+        Some(WireType::GobEncoder(_))
+        | Some(WireType::BinaryMarshaler(_))
+        | Some(WireType::TextMarshaler(_)) => "encoded bytes".to_owned(),
+        None => format!("type {}", id.0),
+    }
+}
@@ -0,0 +1,196 @@
+use std::io::{self, Cursor};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Buf;
+use serde::de::DeserializeOwned;
+
+use crate::error::Error;
+use crate::internal::gob::{parse_section, Message, MessageReadError, SectionHeader};
+use crate::internal::types::TypeId;
+use crate::internal::utils::{Bow, Buffer};
+
+use super::{decode_interface_value, register_type, Deserializer, ExtraFields, Types};
+
+/// An asynchronous reader to deserialize from: any `tokio::io::AsyncRead`
+/// with the `tokio` feature, and a `futures::io::AsyncRead` wrapped in a
+/// `FuturesRead` with the `async` feature.
+pub trait AsyncSource: Unpin {
+    /// Reads into `buf`, returning how many bytes were read, or 0 at the
+    /// end of the input.
+    fn poll_read_into(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>>;
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> AsyncSource for R {
+    fn poll_read_into(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut buf = tokio::io::ReadBuf::new(buf);
+        match tokio::io::AsyncRead::poll_read(self, cx, &mut buf) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(buf.filled().len())),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Wraps a `futures::io::AsyncRead`, such as a smol or async-std socket,
+/// for an `AsyncStreamDeserializer` to read from.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct FuturesRead<R>(pub R);
+
+#[cfg(feature = "async")]
+impl<R: futures_io::AsyncRead + Unpin> AsyncSource for FuturesRead<R> {
+    fn poll_read_into(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+/// Deserializes a stream of values from an asynchronous reader.
+///
+/// Incoming bytes are buffered until a message is complete, so values
+/// are only decoded once all of their bytes have arrived.
+pub struct AsyncStreamDeserializer<R> {
+    defs: Types,
+    read: R,
+    buffer: Buffer,
+    byte_budget: Option<usize>,
+    extra_fields: ExtraFields,
+}
+
+impl<R: AsyncSource> AsyncStreamDeserializer<R> {
+    pub fn new(read: R) -> Self {
+        AsyncStreamDeserializer {
+            defs: Types::new(),
+            read,
+            buffer: Buffer::new(),
+            byte_budget: None,
+            extra_fields: ExtraFields::default(),
+        }
+    }
+
+    /// Limits the bytes decoding a single value may allocate, see
+    /// `StreamDeserializer::with_byte_budget`.
+    pub fn with_byte_budget(mut self, bytes: usize) -> Self {
+        self.byte_budget = Some(bytes);
+        self
+    }
+
+    /// Sets what happens to struct fields the Rust type does not declare,
+    /// see `StreamDeserializer::with_extra_fields`.
+    pub fn with_extra_fields(mut self, policy: ExtraFields) -> Self {
+        self.extra_fields = policy;
+        self
+    }
+
+    /// Registers `T` as the Rust type of interface values whose concrete Go
+    /// type was registered under `name`, see
+    /// `StreamDeserializer::register_interface_type`.
+    pub fn register_interface_type<T>(&mut self, name: &str)
+    where
+        T: DeserializeOwned + 'static,
+    {
+        self.defs.register_interface(name, decode_interface_value::<T>);
+    }
+
+    /// Returns the concrete Go type names registered with
+    /// `register_interface_type`, in no particular order and in the form
+    /// of `go::normalize_type_name`.
+    pub fn registered_interface_types(&self) -> impl Iterator<Item = &str> + '_ {
+        self.defs.interface_names()
+    }
+
+    /// Whether interface values of the concrete Go type `name` can be
+    /// decoded, see `StreamDeserializer::is_interface_type_registered`.
+    pub fn is_interface_type_registered(&self, name: &str) -> bool {
+        self.defs.interface_decoder(name).is_some()
+    }
+
+    /// Reads the next value, or returns `None` at the end of the stream.
+    ///
+    /// This is cancel-safe: if the future is dropped before it completes,
+    /// such as in a `tokio::select!` branch that loses, no data is lost,
+    /// and the next call carries on where it left off.
+    pub async fn next<T>(&mut self) -> Option<Result<T, Error>>
+    where
+        T: DeserializeOwned,
+    {
+        self.next_value().await.transpose()
+    }
+
+    async fn next_value<T>(&mut self) -> Result<Option<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        loop {
+            let header = match self.read_section().await? {
+                Some(header) => header,
+                None => return Ok(None),
+            };
+            let payload = &self.buffer.bytes()[header.payload_range.clone()];
+
+            if header.type_id >= 0 {
+                let mut msg = Message::new(Cursor::new(payload));
+                if let Some(budget) = self.byte_budget {
+                    msg.set_budget(budget);
+                }
+                msg.set_extra_fields(self.extra_fields);
+                let value = T::deserialize(Deserializer {
+                    defs: Bow::Borrowed(&mut self.defs),
+                    msg,
+                    type_id: Some(TypeId(header.type_id)),
+                });
+                self.buffer.advance(header.payload_range.end);
+                return value.map(Some);
+            }
+
+            register_type(&mut self.defs, header.type_id, payload)?;
+            self.buffer.advance(header.payload_range.end);
+        }
+    }
+
+    async fn read_section(&mut self) -> Result<Option<SectionHeader>, Error> {
+        if self.buffer.len() == 0 {
+            let n = self.buffer.read_from_async(&mut self.read).await?;
+            if n == 0 {
+                return Ok(None);
+            }
+        }
+        loop {
+            match parse_section(self.buffer.bytes()) {
+                Ok(header) => return Ok(Some(header)),
+                Err(MessageReadError::Incomplete) => {
+                    let n = self.buffer.read_from_async(&mut self.read).await?;
+                    if n == 0 {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                    }
+                }
+                Err(MessageReadError::Parse(reason)) => return Err(Error::deserialize(reason)),
+            }
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.read
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.read
+    }
+
+    pub fn into_inner(self) -> R {
+        self.read
+    }
+}
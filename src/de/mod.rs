@@ -1,24 +1,250 @@
 //! Deserialization
+//!
+//! # Optional fields
+//!
+//! Gob omits struct fields holding a zero value. A Rust `Option` field
+//! decodes to `None` when its field was omitted and to `Some` whenever it
+//! was transmitted, whatever the value. For slices this means that an
+//! `Option<Vec<T>>` is `None` for both nil and empty Go slices, since Go
+//! sends neither, and only becomes `Some(vec![])` if an encoder transmitted
+//! an empty slice explicitly.
+//!
+//! Go pointers are flattened on the wire, so a `**T` field is sent like a
+//! `T` field and decodes into an `Option<Option<T>>` just as well. Being
+//! omitted whenever any level is nil or the value is zero, it never decodes
+//! to `Some(None)`.
+//!
+//! The elements of a `[]*T` are sent like those of a `[]T`, and decode into
+//! a `Vec<T>` or a `Vec<Option<T>>`. Go refuses to encode a nil element, so
+//! the latter only ever holds `Some`.
+//!
+//! # Borrowing
+//!
+//! Strings and byte slices can be borrowed rather than copied, by fields
+//! like `&'de str`, `serde_bytes::Bytes<'de>` or a `Cow<'de, str>` marked
+//! `#[serde(borrow)]`. `Deserializer::from_slice` borrows from its input,
+//! and `StreamDeserializer` from its buffer, which holds on to the message
+//! until the next value is read.
 
-use std::io::{Cursor, Read};
+use std::any::Any;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
 
 use bytes::Buf;
-use serde::de::{IgnoredAny, Visitor};
+use serde::de::{DeserializeOwned, DeserializeSeed, IgnoredAny, Visitor};
 use serde::{self, Deserialize};
 
-use crate::error::Error;
-use crate::internal::gob::{Message, Stream};
-use crate::internal::types::{TypeId, Types, WireType};
+use crate::error::{Error, FieldError};
+use crate::internal::gob::{Message, SectionHeader, Stream};
+use crate::internal::types::{TypeId, WireType, FIRST_USER_ID};
 use crate::internal::utils::{Bow, Buffer};
 
+pub use crate::internal::gob::ExtraFields;
+pub use crate::internal::types::{Types, WireKind};
+
 use crate::internal::de::FieldValueDeserializer;
 use crate::internal::de::ValueDeserializer;
+use crate::internal::de::{SKIPPED_FIELDS, WIRE_KIND_TOKEN};
+
+#[cfg(any(feature = "tokio", feature = "async"))]
+mod async_stream;
+#[cfg(any(feature = "tokio", feature = "async"))]
+pub use self::async_stream::{AsyncSource, AsyncStreamDeserializer};
+#[cfg(feature = "async")]
+pub use self::async_stream::FuturesRead;
+
+/// Reads the type definitions at the start of a gob stream.
+///
+/// Stops in front of the first value message, leaving `reader` positioned
+/// at its start, or at the end of the stream if there is none.
+pub fn read_schema<R: Read + Seek>(reader: &mut R) -> Result<Types, Error> {
+    let mut defs = Types::new();
+    let mut buffer = Vec::new();
+    loop {
+        let (len, len_size) = match read_message_len(reader)? {
+            Some(len) => len,
+            None => return Ok(defs),
+        };
+        buffer.clear();
+        reader.by_ref().take(len as u64).read_to_end(&mut buffer)?;
+        if buffer.len() < len {
+            return Err(Error::deserialize("truncated message"));
+        }
+
+        let mut msg = Message::new(Cursor::new(&buffer[..]));
+        let type_id = msg.read_int()?;
+
+        if type_id >= 0 {
+            reader.seek(SeekFrom::Current(-((len_size + len) as i64)))?;
+            return Ok(defs);
+        }
+
+        let wire_type = {
+            let de = FieldValueDeserializer::new(TypeId::WIRE_TYPE, &defs, &mut msg);
+            WireType::deserialize(de)
+        }?;
+
+        if type_id.checked_neg() != Some(wire_type.common().id.0) {
+            return Err(Error::deserialize("type id mismatch"));
+        }
+
+        defs.insert(wire_type)?;
+    }
+}
+
+/// Deserializes a value with `visitor`, telling it the kind of the value's
+/// wire type first, for `Deserialize` implementations that decode a value
+/// depending on how it was sent, such as from either a struct or a map.
+///
+/// The visitor's `visit_seq` is handed two elements: the `WireKind`, and
+/// then the value, to be decoded as whatever type suits the kind. The value
+/// is skipped if the visitor does not read it. Deserializers of other
+/// formats are asked for a tuple struct instead.
+///
+/// ```
+/// # extern crate gob;
+/// # extern crate serde;
+/// use std::collections::HashMap;
+/// use std::fmt;
+///
+/// use gob::de::WireKind;
+/// use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+///
+/// /// Settings sent either as a `map[string]int64` or as a single `int64`.
+/// struct Settings(HashMap<String, i64>);
+///
+/// impl<'de> Deserialize<'de> for Settings {
+///     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Settings, D::Error> {
+///         struct SettingsVisitor;
+///
+///         impl<'de> Visitor<'de> for SettingsVisitor {
+///             type Value = Settings;
+///
+///             fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///                 f.write_str("a map or an int")
+///             }
+///
+///             fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Settings, A::Error> {
+///                 let kind = seq.next_element::<WireKind>()?;
+///                 let settings = match kind {
+///                     Some(WireKind::Map) => seq.next_element()?,
+///                     Some(WireKind::Int) => seq
+///                         .next_element()?
+///                         .map(|level| HashMap::from([("level".to_string(), level)])),
+///                     _ => return Err(A::Error::custom("unexpected kind")),
+///                 };
+///                 settings.map(Settings).ok_or_else(|| A::Error::custom("missing value"))
+///             }
+///         }
+///
+///         gob::de::deserialize_with_kind(deserializer, SettingsVisitor)
+///     }
+/// }
+///
+/// let mut buffer = Vec::new();
+/// gob::to_writer(&mut buffer, &3i64).unwrap();
+/// let settings = Settings::deserialize(gob::Deserializer::from_slice(&buffer)).unwrap();
+/// assert_eq!(settings.0["level"], 3);
+/// ```
+pub fn deserialize_with_kind<'de, D, V>(deserializer: D, visitor: V) -> Result<V::Value, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    V: Visitor<'de>,
+{
+    deserializer.deserialize_tuple_struct(WIRE_KIND_TOKEN, 2, visitor)
+}
+
+/// Checks whether `bytes` start like a gob stream, without decoding it.
+///
+/// Only the first message is looked at: it has to hold a type id and a
+/// payload, and be either a definition of a user type or a value of a
+/// predefined or user type. `bytes` may end anywhere after the type id, so
+/// the start of the input is enough. Streams written by Go or this crate
+/// always pass, but so may input that merely happens to look similar.
+pub fn looks_like_gob(bytes: &[u8]) -> bool {
+    let mut msg = Message::new(Cursor::new(bytes));
+    let len = match msg.read_uint() {
+        Ok(len) => len,
+        Err(_) => return false,
+    };
+    let offset = msg.get_ref().position();
+    let type_id = match msg.read_int() {
+        Ok(type_id) => type_id,
+        Err(_) => return false,
+    };
+    if len <= msg.get_ref().position() - offset {
+        return false;
+    }
+    if type_id < 0 {
+        // a `wireType`, which starts with the delta of its only field
+        let field_delta_ok =
+            msg.get_ref().remaining() == 0 || matches!(msg.read_uint(), Ok(1..=7));
+        type_id.checked_neg().is_some_and(|id| id >= FIRST_USER_ID) && field_delta_ok
+    } else {
+        (TypeId::BOOL.0..=TypeId::INTERFACE.0).contains(&type_id) || type_id >= FIRST_USER_ID
+    }
+}
+
+/// Reads a message length prefix, returning it along with its own size, or
+/// `None` at the end of the stream.
+fn read_message_len<R: Read>(reader: &mut R) -> Result<Option<(usize, usize)>, Error> {
+    let mut first = [0u8];
+    if reader.read(&mut first)? == 0 {
+        return Ok(None);
+    }
+    if first[0] < 128 {
+        return Ok(Some((first[0] as usize, 1)));
+    }
+    let n = (!first[0] + 1) as usize;
+    if n > 8 {
+        return Err(Error::deserialize(format!("invalid uint length {}", n)));
+    }
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes[8 - n..]).map_err(|err| {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            Error::deserialize("truncated message length")
+        } else {
+            Error::from(err)
+        }
+    })?;
+    Ok(Some((u64::from_be_bytes(bytes) as usize, 1 + n)))
+}
+
+/// Decodes the type definition sent as `payload` of a message with the
+/// (negative) `type_id` and adds it to `defs`.
+fn register_type(defs: &mut Types, type_id: i64, payload: &[u8]) -> Result<(), Error> {
+    let wire_type = {
+        let mut msg = Message::new(Cursor::new(payload));
+        let de = FieldValueDeserializer::new(TypeId::WIRE_TYPE, defs, &mut msg);
+        WireType::deserialize(de)
+    }?;
+
+    if type_id.checked_neg() != Some(wire_type.common().id.0) {
+        return Err(Error::deserialize("type id mismatch"));
+    }
+
+    gob_trace!(
+        "registered type definition {} {:?}",
+        -type_id,
+        wire_type.common().name
+    );
+    defs.insert(wire_type)?;
+    Ok(())
+}
+
+fn decode_interface_value<T>(de: FieldValueDeserializer) -> Result<Box<dyn Any>, Error>
+where
+    T: DeserializeOwned + 'static,
+{
+    Ok(Box::new(T::deserialize(de)?))
+}
 
 pub struct StreamDeserializer<R> {
     defs: Types,
     stream: Stream<R>,
     buffer: Buffer,
     prev_len: usize,
+    byte_budget: Option<usize>,
+    extra_fields: ExtraFields,
 }
 
 impl<R> StreamDeserializer<R> {
@@ -28,9 +254,62 @@ impl<R> StreamDeserializer<R> {
             stream: Stream::new(read),
             buffer: Buffer::new(),
             prev_len: 0,
+            byte_budget: None,
+            extra_fields: ExtraFields::default(),
         }
     }
 
+    /// Limits how many bytes decoding a single value may allocate for the
+    /// elements of its sequences and the entries of its maps, measured by
+    /// their size in memory. Decoding a value that goes over the budget
+    /// fails with a deserialize error.
+    ///
+    /// An element can take up a single byte on the wire, so without a
+    /// budget a small message may expand into a large allocation.
+    pub fn with_byte_budget(mut self, bytes: usize) -> Self {
+        self.byte_budget = Some(bytes);
+        self
+    }
+
+    /// Sets what happens to struct fields the Rust type does not declare.
+    ///
+    /// `Deny` and `Skip` rely on the list of fields that derived structs
+    /// pass to `deserialize_struct`. Types decoded as maps, including
+    /// structs with a `#[serde(flatten)]` field, are handed every field.
+    pub fn with_extra_fields(mut self, policy: ExtraFields) -> Self {
+        self.extra_fields = policy;
+        self
+    }
+
+    /// Registers `T` as the Rust type of interface values whose concrete Go
+    /// type was registered under `name`, like `main.Circle` for
+    /// `gob.Register(Circle{})`. Such values decode into a `go::Interface`
+    /// holding a `T`.
+    ///
+    /// Names of generic types are matched after `go::normalize_type_name`,
+    /// so `main.Pair[string, float64]` also matches the
+    /// `main.Pair[string,float64]` Go sends.
+    pub fn register_interface_type<T>(&mut self, name: &str)
+    where
+        T: DeserializeOwned + 'static,
+    {
+        self.defs.register_interface(name, decode_interface_value::<T>);
+    }
+
+    /// Returns the concrete Go type names registered with
+    /// `register_interface_type`, in no particular order and in the form
+    /// of `go::normalize_type_name`.
+    pub fn registered_interface_types(&self) -> impl Iterator<Item = &str> + '_ {
+        self.defs.interface_names()
+    }
+
+    /// Whether interface values of the concrete Go type `name` can be
+    /// decoded, i.e. whether it was registered with
+    /// `register_interface_type`.
+    pub fn is_interface_type_registered(&self, name: &str) -> bool {
+        self.defs.interface_decoder(name).is_some()
+    }
+
     pub fn deserialize<'de, T>(&'de mut self) -> Result<Option<T>, Error>
     where
         R: Read,
@@ -43,14 +322,153 @@ impl<R> StreamDeserializer<R> {
         }
     }
 
-    pub fn deserializer<'de>(&'de mut self) -> Result<Option<Deserializer<'de>>, Error>
+    /// Deserializes the next value like `deserialize`, but skips struct
+    /// fields that fail to decode instead of failing the whole value, such
+    /// as strings that are not valid UTF-8 or values the Rust field cannot
+    /// hold. Skipped fields are left at their zero value, like the empty
+    /// string or `None`, and reported next to the value.
+    ///
+    /// A field is skipped by reading past it as its Go type says, so errors
+    /// that leave no way to find where it ends, like a message cut short,
+    /// still fail the value.
+    pub fn deserialize_lenient<'de, T>(
+        &'de mut self,
+    ) -> Result<Option<(T, Vec<FieldError>)>, Error>
+    where
+        R: Read,
+        T: Deserialize<'de>,
+    {
+        let deserializer = match self.deserializer()? {
+            Some(deserializer) => deserializer,
+            None => return Ok(None),
+        };
+        let outer = SKIPPED_FIELDS.with(|skipped| skipped.replace(Some(Vec::new())));
+        let value = T::deserialize(deserializer);
+        let skipped = SKIPPED_FIELDS.with(|skipped| skipped.replace(outer));
+        Ok(Some((value?, skipped.unwrap_or_default())))
+    }
+
+    /// Deserializes the next value with `seed`, which allows passing state
+    /// into decoding, returning `None` at the end of the stream.
+    pub fn deserialize_seed<'de, S>(&'de mut self, seed: S) -> Result<Option<S::Value>, Error>
+    where
+        R: Read,
+        S: DeserializeSeed<'de>,
+    {
+        if let Some(deserializer) = self.deserializer()? {
+            Ok(Some(seed.deserialize(deserializer)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Deserializes the next value into `dst`, returning `false` at the end
+    /// of the stream.
+    ///
+    /// This goes through `Deserialize::deserialize_in_place`, so that
+    /// types supporting it reuse what they have allocated already, like
+    /// the buffers of `String` and `Vec` fields. Derived types only do so
+    /// with the `deserialize_in_place` feature of `serde_derive`, and are
+    /// replaced as a whole otherwise.
+    pub fn decode_into_existing<'de, T>(&'de mut self, dst: &mut T) -> Result<bool, Error>
     where
         R: Read,
+        T: Deserialize<'de>,
     {
+        if let Some(deserializer) = self.deserializer()? {
+            T::deserialize_in_place(deserializer, dst)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Returns the byte count of the next message, which the stream sends
+    /// ahead of it, or `None` at the end of the stream. Only that prefix is
+    /// read, so this does not wait for the message itself to arrive.
+    ///
+    /// Like the count, the message does not include the prefix. It is the
+    /// next value, unless type definitions are sent ahead of that.
+    pub fn peek_message_len(&mut self) -> Result<Option<usize>, Error>
+    where
+        R: Read,
+    {
+        self.release_previous();
+        self.stream.peek_len(&mut self.buffer)
+    }
+
+    /// Drops the message of the value decoded last from the buffer.
+    fn release_previous(&mut self) {
         if self.prev_len > 0 {
             self.buffer.advance(self.prev_len);
             self.prev_len = 0;
         }
+    }
+
+    pub fn deserializer<'de>(&'de mut self) -> Result<Option<Deserializer<'de>>, Error>
+    where
+        R: Read,
+    {
+        match self.read_to_value()? {
+            Some((header, _)) => Ok(Some(self.value_deserializer(header))),
+            None => Ok(None),
+        }
+    }
+
+    /// Deserializes a batch written by `StreamSerializer::serialize_batch`,
+    /// a count followed by that many values, returning `None` at the end of
+    /// the stream.
+    pub fn deserialize_batch<T>(&mut self) -> Result<Option<Vec<T>>, Error>
+    where
+        R: Read,
+        T: DeserializeOwned,
+    {
+        let count = match self.deserialize::<u64>()? {
+            Some(count) => count,
+            None => return Ok(None),
+        };
+        // the count alone does not show that the values are there
+        let mut values = Vec::with_capacity(count.min(4096) as usize);
+        while (values.len() as u64) < count {
+            match self.deserialize::<T>()? {
+                Some(value) => values.push(value),
+                None => {
+                    return Err(Error::truncated(format!(
+                        "batch ends after {} of {} values",
+                        values.len(),
+                        count
+                    )))
+                }
+            }
+        }
+        Ok(Some(values))
+    }
+
+    /// Deserializes the next value like `deserialize`, and also returns
+    /// how many bytes of the stream it took up, including the type
+    /// definitions sent ahead of it.
+    pub fn deserialize_with_len<'de, T>(&'de mut self) -> Result<Option<(T, usize)>, Error>
+    where
+        R: Read,
+        T: Deserialize<'de>,
+    {
+        match self.read_to_value()? {
+            Some((header, len)) => {
+                let value = T::deserialize(self.value_deserializer(header))?;
+                Ok(Some((value, len)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Registers the type definitions ahead of the next value, returning
+    /// the header of its message and the bytes read for all of them.
+    fn read_to_value(&mut self) -> Result<Option<(SectionHeader, usize)>, Error>
+    where
+        R: Read,
+    {
+        self.release_previous();
+        let mut len = 0;
         loop {
             let header = match self.stream.read_section(&mut self.buffer)? {
                 Some(header) => header,
@@ -58,29 +476,28 @@ impl<R> StreamDeserializer<R> {
             };
 
             if header.type_id >= 0 {
-                let slice = &self.buffer.bytes()[header.payload_range.clone()];
-                let msg = Message::new(Cursor::new(slice));
                 self.prev_len = header.payload_range.end;
-                return Ok(Some(Deserializer {
-                    defs: Bow::Borrowed(&mut self.defs),
-                    msg: msg,
-                    type_id: Some(TypeId(header.type_id)),
-                }));
-            }
-
-            let wire_type = {
-                let slice = &self.buffer.bytes()[header.payload_range.clone()];
-                let mut msg = Message::new(Cursor::new(slice));
-                let de = FieldValueDeserializer::new(TypeId::WIRE_TYPE, &self.defs, &mut msg);
-                WireType::deserialize(de)
-            }?;
-
-            if -header.type_id != wire_type.common().id.0 {
-                return Err(Error::deserialize("type id mismatch"));
+                return Ok(Some((header, len + self.prev_len)));
             }
 
-            self.defs.insert(wire_type);
+            let payload = &self.buffer.bytes()[header.payload_range.clone()];
+            register_type(&mut self.defs, header.type_id, payload)?;
             self.buffer.advance(header.payload_range.end);
+            len += header.payload_range.end;
+        }
+    }
+
+    fn value_deserializer(&mut self, header: SectionHeader) -> Deserializer<'_> {
+        let slice = &self.buffer.bytes()[header.payload_range];
+        let mut msg = Message::new(Cursor::new(slice));
+        if let Some(budget) = self.byte_budget {
+            msg.set_budget(budget);
+        }
+        msg.set_extra_fields(self.extra_fields);
+        Deserializer {
+            defs: Bow::Borrowed(&mut self.defs),
+            msg,
+            type_id: Some(TypeId(header.type_id)),
         }
     }
 
@@ -104,6 +521,10 @@ pub struct Deserializer<'de> {
 }
 
 impl<'de> Deserializer<'de> {
+    /// Creates a deserializer reading a single value from `input`.
+    ///
+    /// Strings and byte slices are borrowed from `input` when the target
+    /// type allows it, such as `&'de str` or `serde_bytes::Bytes<'de>`.
     pub fn from_slice(input: &'de [u8]) -> Deserializer<'de> {
         Deserializer {
             defs: Bow::Owned(Types::new()),
@@ -112,21 +533,31 @@ impl<'de> Deserializer<'de> {
         }
     }
 
+    /// Returns a deserializer for the value, whose leading type id has to
+    /// refer to a predefined type or one defined earlier in the stream.
     fn value_deserializer<'t>(&'t mut self) -> Result<ValueDeserializer<'t, 'de>, Error> {
-        if let Some(type_id) = self.type_id {
-            return Ok(ValueDeserializer::new(type_id, &self.defs, &mut self.msg));
+        let type_id = match self.type_id {
+            Some(type_id) => type_id,
+            None => self.read_definitions()?,
+        };
+        if self.defs.kind(type_id).is_none() {
+            return Err(Error::deserialize(format!(
+                "value of undefined type {}",
+                type_id.0
+            )));
         }
+        Ok(ValueDeserializer::new(type_id, &self.defs, &mut self.msg))
+    }
 
+    /// Registers the type definitions ahead of the value, returning its
+    /// type id.
+    fn read_definitions(&mut self) -> Result<TypeId, Error> {
         loop {
             let _len = self.msg.read_bytes_len()?;
             let type_id = self.msg.read_int()?;
 
             if type_id >= 0 {
-                return Ok(ValueDeserializer::new(
-                    TypeId(type_id),
-                    &self.defs,
-                    &mut self.msg,
-                ));
+                return Ok(TypeId(type_id));
             }
 
             let wire_type = {
@@ -134,11 +565,16 @@ impl<'de> Deserializer<'de> {
                 WireType::deserialize(de)
             }?;
 
-            if -type_id != wire_type.common().id.0 {
+            if type_id.checked_neg() != Some(wire_type.common().id.0) {
                 return Err(serde::de::Error::custom(format!("type id mismatch")));
             }
 
-            self.defs.insert(wire_type);
+            gob_trace!(
+                "registered type definition {} {:?}",
+                -type_id,
+                wire_type.common().name
+            );
+            self.defs.insert(wire_type)?;
         }
     }
 }
@@ -179,19 +615,64 @@ impl<'de> serde::Deserializer<'de> for Deserializer<'de> {
             .deserialize_struct(name, fields, visitor)
     }
 
-    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_char<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let int = i64::deserialize(self)?;
-        if let Some(c) = ::std::char::from_u32(int as u32) {
-            visitor.visit_char(c)
-        } else {
-            Err(serde::de::Error::custom(format!(
-                "invalid char code {}",
-                int
-            )))
-        }
+        self.value_deserializer()?.deserialize_char(visitor)
+    }
+
+    fn deserialize_str<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_str(visitor)
+    }
+
+    fn deserialize_string<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_string(visitor)
+    }
+
+    fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple<V>(mut self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        mut self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?
+            .deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        mut self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?
+            .deserialize_tuple_struct(name, len, visitor)
     }
 
     #[inline]
@@ -204,8 +685,8 @@ impl<'de> serde::Deserializer<'de> for Deserializer<'de> {
     }
 
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 str string bytes
-        byte_buf option unit_struct newtype_struct seq tuple
-        tuple_struct map identifier ignored_any
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 bytes
+        byte_buf option unit_struct
+        map identifier ignored_any
     }
 }
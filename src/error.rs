@@ -1,11 +1,18 @@
 use std::fmt;
 use std::io;
 
+use crate::schema::TypeId;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ErrorKind {
     Io(io::ErrorKind),
     Serialize,
+    /// The input is malformed, or does not match the requested type.
     Deserialize,
+    /// The input ended in the middle of a value. A length prefix that
+    /// overruns its message is reported as `Deserialize` instead.
+    Truncated,
 }
 
 #[derive(Debug)]
@@ -17,9 +24,37 @@ pub struct Error {
 #[derive(Debug)]
 enum ErrorInner {
     Io(io::Error),
+    SchemaMismatch(SchemaMismatch),
     Other(String),
 }
 
+/// A value that serde serializes differently from how its type was
+/// registered, typically because of an `interpret_as` that does not fit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaMismatch {
+    /// No type has been registered under the id.
+    TypeNotFound { type_id: TypeId },
+    /// The registered type is not of the kind serde asks for, such as a
+    /// struct serialized with the id of a slice type.
+    UnexpectedType {
+        type_id: TypeId,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaMismatch::TypeNotFound { type_id } => {
+                write!(f, "type {} not found", type_id.0)
+            }
+            SchemaMismatch::UnexpectedType { type_id, expected } => {
+                write!(f, "schema mismatch, type {} is not {}", type_id.0, expected)
+            }
+        }
+    }
+}
+
 impl Error {
     pub(crate) fn deserialize<S: Into<String>>(message: S) -> Error {
         Error {
@@ -28,29 +63,50 @@ impl Error {
         }
     }
 
+    pub(crate) fn truncated<S: Into<String>>(message: S) -> Error {
+        Error {
+            kind: ErrorKind::Truncated,
+            inner: ErrorInner::Other(message.into()),
+        }
+    }
+
     pub fn kind(&self) -> ErrorKind {
         self.kind
     }
+
+    /// The details of a schema mismatch, if this error is one.
+    pub fn schema_mismatch(&self) -> Option<&SchemaMismatch> {
+        match self.inner {
+            ErrorInner::SchemaMismatch(ref mismatch) => Some(mismatch),
+            _ => None,
+        }
+    }
+
+    fn kind_str(&self) -> &'static str {
+        match self.kind {
+            ErrorKind::Io(_) => "i/o error",
+            ErrorKind::Serialize => "serialize error",
+            ErrorKind::Deserialize => "deserialize error",
+            ErrorKind::Truncated => "truncated input",
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.inner {
             ErrorInner::Io(ref err) => write!(f, "i/o error: {}", err),
-            ErrorInner::Other(ref msg) => {
-                write!(f, "{}: {}", &self, msg)
+            ErrorInner::SchemaMismatch(ref mismatch) => {
+                write!(f, "{}: {}", self.kind_str(), mismatch)
             }
+            ErrorInner::Other(ref msg) => write!(f, "{}: {}", self.kind_str(), msg),
         }
     }
 }
 
 impl ::std::error::Error for Error {
     fn description(&self) -> &str {
-        match self.kind {
-            ErrorKind::Io(_) => "i/o error",
-            ErrorKind::Serialize => "serialize error",
-            ErrorKind::Deserialize => "deserialize error",
-        }
+        self.kind_str()
     }
 }
 
@@ -80,3 +136,27 @@ impl From<io::Error> for Error {
         }
     }
 }
+
+impl From<SchemaMismatch> for Error {
+    fn from(mismatch: SchemaMismatch) -> Error {
+        Error {
+            kind: ErrorKind::Serialize,
+            inner: ErrorInner::SchemaMismatch(mismatch),
+        }
+    }
+}
+
+/// A struct field that failed to decode and was skipped by
+/// `StreamDeserializer::deserialize_lenient`.
+#[derive(Debug)]
+pub struct FieldError {
+    /// The name of the field as Go sent it.
+    pub field: String,
+    pub error: Error,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "field {}: {}", self.field, self.error)
+    }
+}
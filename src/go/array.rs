@@ -0,0 +1,93 @@
+//! Go arrays longer than the 32 elements serde supports for Rust arrays.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTuple, Serializer};
+use serde_gob::types::Type;
+use serde_gob::{GobSerialize, Schema};
+
+/// A Go `[N]T` of any length, such as a `[64]byte` hash.
+///
+/// Like Go, this sends every element on its own, so a `[64]byte` is not
+/// a byte string on the wire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Array<T, const N: usize>(pub [T; N]);
+
+impl<T, const N: usize> From<[T; N]> for Array<T, N> {
+    fn from(array: [T; N]) -> Array<T, N> {
+        Array(array)
+    }
+}
+
+impl<T, const N: usize> Deref for Array<T, N> {
+    type Target = [T; N];
+
+    fn deref(&self) -> &[T; N] {
+        &self.0
+    }
+}
+
+impl<T, const N: usize> DerefMut for Array<T, N> {
+    fn deref_mut(&mut self) -> &mut [T; N] {
+        &mut self.0
+    }
+}
+
+impl<T: Serialize, const N: usize> Serialize for Array<T, N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_tuple(N)?;
+        for elem in &self.0 {
+            state.serialize_element(elem)?;
+        }
+        state.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for Array<T, N> {
+    fn deserialize<D>(deserializer: D) -> Result<Array<T, N>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ArrayVisitor<T, const N: usize>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for ArrayVisitor<T, N> {
+            type Value = Array<T, N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an array of length {}", N)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Array<T, N>, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut elems = Vec::with_capacity(N);
+                while let Some(elem) = seq.next_element()? {
+                    if elems.len() == N {
+                        return Err(de::Error::invalid_length(N + 1, &self));
+                    }
+                    elems.push(elem);
+                }
+                match <[T; N]>::try_from(elems) {
+                    Ok(array) => Ok(Array(array)),
+                    Err(elems) => Err(de::Error::invalid_length(elems.len(), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_tuple(N, ArrayVisitor(PhantomData))
+    }
+}
+
+impl<T: GobSerialize, const N: usize> GobSerialize for Array<T, N> {
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let id = T::schema_register(schema)?;
+        schema.register_type(Type::build().seq_type(Some(N), id))
+    }
+}
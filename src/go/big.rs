@@ -0,0 +1,59 @@
+//! Go's `math/big.Int` as a `num_bigint::BigInt`.
+//!
+//! `big.Int` encodes itself, as a header byte followed by its magnitude in
+//! big-endian order, without leading zeros. The header holds the version
+//! of the encoding, which is 1, shifted left by one, and a set low bit for
+//! negative numbers. Zero has no magnitude bytes, and a nil `*big.Int` is
+//! sent as an empty byte string.
+
+use std::fmt;
+
+use num_bigint::{BigInt, Sign};
+
+use super::{GobDecoder, GobEncoder};
+
+const INT_GOB_VERSION: u8 = 1;
+
+/// A byte string that is not a `big.Int` encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigIntError(u8);
+
+impl fmt::Display for BigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "big.Int encoding version {} not supported", self.0)
+    }
+}
+
+impl GobDecoder for BigInt {
+    type Error = BigIntError;
+
+    fn gob_decode(bytes: &[u8]) -> Result<BigInt, BigIntError> {
+        let (&header, magnitude) = match bytes.split_first() {
+            Some(split) => split,
+            None => return Ok(BigInt::default()),
+        };
+        if header >> 1 != INT_GOB_VERSION {
+            return Err(BigIntError(header >> 1));
+        }
+        let sign = if header & 1 == 1 { Sign::Minus } else { Sign::Plus };
+        Ok(BigInt::from_bytes_be(sign, magnitude))
+    }
+}
+
+impl GobEncoder for BigInt {
+    const GO_TYPE_NAME: &'static str = "Int";
+
+    fn gob_encode(&self) -> Vec<u8> {
+        let (sign, magnitude) = self.to_bytes_be();
+        let mut bytes = Vec::with_capacity(1 + magnitude.len());
+        bytes.push(INT_GOB_VERSION << 1 | (sign == Sign::Minus) as u8);
+        if sign != Sign::NoSign {
+            bytes.extend_from_slice(&magnitude);
+        }
+        bytes
+    }
+
+    fn is_zero(&self) -> bool {
+        self.sign() == Sign::NoSign
+    }
+}
@@ -0,0 +1,97 @@
+//! Go's `time.Duration`, which is sent as an `int64` count of nanoseconds.
+//!
+//! Use [`Duration`] where negative durations can occur, or this module with
+//! `#[serde(with = "gob::go::duration")]` on a `std::time::Duration` field,
+//! which rejects them.
+//!
+//! A `std::time::Instant` cannot be sent at all, as it only has a meaning
+//! within the running process, and `#[derive(GobSerialize)]` rejects fields
+//! of that type. Send a point in time as the duration since
+//! `SystemTime::UNIX_EPOCH`, for example with this module.
+
+use std::time;
+
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
+use serde_gob::{GobSerialize, Schema};
+
+/// A Go `time.Duration`: a signed count of nanoseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration {
+    nanos: i64,
+}
+
+impl Duration {
+    pub fn from_nanos(nanos: i64) -> Duration {
+        Duration { nanos }
+    }
+
+    pub fn as_nanos(&self) -> i64 {
+        self.nanos
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.nanos < 0
+    }
+
+    /// Returns the absolute length of the duration.
+    pub fn abs(&self) -> time::Duration {
+        time::Duration::from_nanos(self.nanos.unsigned_abs())
+    }
+
+    /// Converts to a `std::time::Duration`, or `None` if negative.
+    pub fn to_std(&self) -> Option<time::Duration> {
+        if self.is_negative() {
+            None
+        } else {
+            Some(self.abs())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        i64::deserialize(deserializer).map(Duration::from_nanos)
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i64(self.nanos)
+    }
+}
+
+impl GobSerialize for Duration {
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        i64::schema_register(schema)
+    }
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<time::Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let duration = Duration::deserialize(deserializer)?;
+    duration.to_std().ok_or_else(|| {
+        D::Error::custom(format!(
+            "negative duration {}ns",
+            duration.as_nanos()
+        ))
+    })
+}
+
+pub fn serialize<S>(duration: &time::Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if duration.as_nanos() > i64::MAX as u128 {
+        return Err(serde::ser::Error::custom("duration out of range"));
+    }
+    serializer.serialize_i64(duration.as_nanos() as i64)
+}
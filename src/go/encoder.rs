@@ -0,0 +1,114 @@
+//! Values of Go types that implement `GobEncoder` or `BinaryMarshaler`,
+//! which are sent as the opaque bytes their encoding method returns.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use serde_gob::types::{Marshaler, Type};
+use serde_gob::{GobSerialize, Schema};
+
+use crate::internal::ser::{NON_ZERO_VALUE_TOKEN, ZERO_VALUE_TOKEN};
+
+/// The Rust side of a Go `GobEncoder`.
+pub trait GobEncoder {
+    /// The name of the Go type, which its type definition carries, such
+    /// as `Int` for a `big.Int`.
+    const GO_TYPE_NAME: &'static str;
+
+    /// The method the Go type decodes itself with. Go only accepts a value
+    /// sent for the one it prefers, which is `GobDecode` if the type has
+    /// both, like `time.Time` does.
+    const MARSHALER: Marshaler = Marshaler::GobEncoder;
+
+    /// Encodes the value the way the Go type's `GobEncode` does.
+    fn gob_encode(&self) -> Vec<u8>;
+
+    /// Whether this is the Go zero value of the type.
+    ///
+    /// Go omits struct fields holding a zero value, judged by the value
+    /// itself rather than by its encoding, so a zero value may still
+    /// encode to a non-empty byte string. Returns `false` by default, in
+    /// which case the field is always sent.
+    fn is_zero(&self) -> bool {
+        false
+    }
+}
+
+/// The Rust side of a Go `GobDecoder`.
+pub trait GobDecoder: Sized {
+    type Error: fmt::Display;
+
+    /// Decodes the bytes the Go type's `GobEncode` or `MarshalBinary`
+    /// returned.
+    fn gob_decode(bytes: &[u8]) -> Result<Self, Self::Error>;
+}
+
+/// Serializes a [`GobEncoder`] as the bytes it encodes to, and
+/// deserializes a [`GobDecoder`] from them.
+///
+/// The value is sent as a Go type that encodes itself, which is what a Go
+/// program expects to decode it into, and a zero value is omitted from its
+/// struct like Go would. Either a `[]byte` or a value of a Go type that
+/// encodes itself can be decoded, such as a `time.Time`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GobEncoded<T>(pub T);
+
+impl<T: GobEncoder> Serialize for GobEncoded<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let bytes = self.0.gob_encode();
+        let token = if self.0.is_zero() {
+            ZERO_VALUE_TOKEN
+        } else {
+            NON_ZERO_VALUE_TOKEN
+        };
+        serializer.serialize_newtype_struct(token, &EncodedBytes(&bytes))
+    }
+}
+
+impl<'de, T: GobDecoder> Deserialize<'de> for GobEncoded<T> {
+    fn deserialize<D>(deserializer: D) -> Result<GobEncoded<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct EncodedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: GobDecoder> Visitor<'de> for EncodedVisitor<T> {
+            type Value = GobEncoded<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an encoded value")
+            }
+
+            fn visit_bytes<E>(self, bytes: &[u8]) -> Result<GobEncoded<T>, E>
+            where
+                E: de::Error,
+            {
+                T::gob_decode(bytes).map(GobEncoded).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(EncodedVisitor(PhantomData))
+    }
+}
+
+impl<T: GobEncoder> GobSerialize for GobEncoded<T> {
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        schema.register_type(Type::build().encoded_type(T::GO_TYPE_NAME, T::MARSHALER))
+    }
+}
+
+struct EncodedBytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for EncodedBytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
@@ -0,0 +1,156 @@
+use std::error;
+use std::fmt;
+
+use serde::de::{Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+/// A Go `error` value received through an interface.
+///
+/// Go can only transmit errors whose concrete type has been registered
+/// with `gob.Register` and has exported fields (the `*errors.errorString`
+/// returned by `errors.New` has neither). In practice such types are a
+/// struct carrying the message, e.g. `struct { Msg string }`, or a named
+/// string type. Both decode into a `GobError`: for structs, the first
+/// string field is taken as the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GobError {
+    message: String,
+}
+
+impl GobError {
+    pub fn new<S: Into<String>>(message: S) -> GobError {
+        GobError {
+            message: message.into(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for GobError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for GobError {}
+
+impl From<GobError> for String {
+    fn from(err: GobError) -> String {
+        err.message
+    }
+}
+
+impl<'de> Deserialize<'de> for GobError {
+    fn deserialize<D>(deserializer: D) -> Result<GobError, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct GobErrorVisitor;
+
+        impl<'de> Visitor<'de> for GobErrorVisitor {
+            type Value = GobError;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an error message or a struct containing one")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<GobError, E> {
+                Ok(GobError::new(v))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<GobError, E> {
+                Ok(GobError::new(v))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<GobError, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut message = None;
+                while map.next_key::<IgnoredAny>()?.is_some() {
+                    let value = map.next_value::<MaybeString>()?;
+                    if message.is_none() {
+                        message = value.0;
+                    }
+                }
+                message
+                    .map(GobError::new)
+                    .ok_or_else(|| serde::de::Error::custom("error struct has no string field"))
+            }
+        }
+
+        deserializer.deserialize_any(GobErrorVisitor)
+    }
+}
+
+/// A field value that is kept if it is a string and skipped otherwise.
+struct MaybeString(Option<String>);
+
+impl<'de> Deserialize<'de> for MaybeString {
+    fn deserialize<D>(deserializer: D) -> Result<MaybeString, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MaybeStringVisitor;
+
+        impl<'de> Visitor<'de> for MaybeStringVisitor {
+            type Value = MaybeString;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("any value")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<MaybeString, E> {
+                Ok(MaybeString(Some(v.to_owned())))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<MaybeString, E> {
+                Ok(MaybeString(Some(v)))
+            }
+
+            fn visit_bool<E>(self, _: bool) -> Result<MaybeString, E> {
+                Ok(MaybeString(None))
+            }
+
+            fn visit_i64<E>(self, _: i64) -> Result<MaybeString, E> {
+                Ok(MaybeString(None))
+            }
+
+            fn visit_u64<E>(self, _: u64) -> Result<MaybeString, E> {
+                Ok(MaybeString(None))
+            }
+
+            fn visit_f64<E>(self, _: f64) -> Result<MaybeString, E> {
+                Ok(MaybeString(None))
+            }
+
+            fn visit_bytes<E>(self, _: &[u8]) -> Result<MaybeString, E> {
+                Ok(MaybeString(None))
+            }
+
+            fn visit_unit<E>(self) -> Result<MaybeString, E> {
+                Ok(MaybeString(None))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<MaybeString, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(MaybeString(None))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<MaybeString, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+                Ok(MaybeString(None))
+            }
+        }
+
+        deserializer.deserialize_any(MaybeStringVisitor)
+    }
+}
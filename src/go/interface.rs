@@ -0,0 +1,138 @@
+use std::any::Any;
+use std::fmt;
+
+use serde::de::{Deserialize, Deserializer, Visitor};
+
+use crate::internal::de::{INTERFACE_TOKEN, INTERFACE_VALUE};
+
+/// A Go `interface{}` value whose concrete type was registered on the
+/// decoding stream.
+///
+/// Go sends an interface value along with the name its concrete type was
+/// registered under by `gob.Register`, e.g. `main.Circle`. The concrete
+/// value is decoded into the Rust type registered for that name with
+/// `StreamDeserializer::register_interface_type`, and can be retrieved
+/// with `downcast_ref` or `downcast`. A nil interface decodes to a value
+/// without a name.
+pub struct Interface {
+    name: String,
+    value: Option<Box<dyn Any>>,
+}
+
+impl Interface {
+    /// The name of the concrete type, which is empty for a nil interface.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_nil(&self) -> bool {
+        self.value.is_none()
+    }
+
+    /// Whether the concrete value was decoded into a `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        self.downcast_ref::<T>().is_some()
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.as_ref()?.downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.value.as_mut()?.downcast_mut()
+    }
+
+    /// Takes out the concrete value, or returns the interface unchanged if
+    /// it does not hold a `T`.
+    pub fn downcast<T: Any>(self) -> Result<T, Interface> {
+        match self.value {
+            Some(value) => match value.downcast() {
+                Ok(value) => Ok(*value),
+                Err(value) => Err(Interface {
+                    name: self.name,
+                    value: Some(value),
+                }),
+            },
+            None => Err(self),
+        }
+    }
+}
+
+impl fmt::Debug for Interface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_nil() {
+            f.write_str("Interface(nil)")
+        } else {
+            write!(f, "Interface({})", self.name)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Interface {
+    fn deserialize<D>(deserializer: D) -> Result<Interface, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct InterfaceVisitor;
+
+        impl<'de> Visitor<'de> for InterfaceVisitor {
+            type Value = Interface;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a gob interface value")
+            }
+
+            fn visit_str<E>(self, name: &str) -> Result<Interface, E>
+            where
+                E: serde::de::Error,
+            {
+                match INTERFACE_VALUE.with(|slot| slot.borrow_mut().take()) {
+                    Some(value) => Ok(Interface {
+                        name: name.to_owned(),
+                        value: Some(value),
+                    }),
+                    None => Err(E::custom("interface values can only be decoded from gob")),
+                }
+            }
+
+            fn visit_unit<E>(self) -> Result<Interface, E> {
+                Ok(Interface {
+                    name: String::new(),
+                    value: None,
+                })
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(INTERFACE_TOKEN, InterfaceVisitor)
+    }
+}
+
+/// Brings a Go type name into the form interface types are registered and
+/// looked up in.
+///
+/// `gob.Register` names a type after its reflect name, which for generic
+/// types includes the type arguments, like `main.Pair[string,float64]` or
+/// `main.Box[interface {}]`. Whitespace next to brackets, braces,
+/// parentheses, commas, semicolons and `*` is dropped, and any other run of
+/// whitespace becomes a single space, so `main.Pair[string, float64]`
+/// names the same type.
+pub fn normalize_type_name(name: &str) -> String {
+    fn is_punct(c: char) -> bool {
+        matches!(c, '[' | ']' | '{' | '}' | '(' | ')' | ',' | ';' | '*')
+    }
+
+    let mut normalized = String::with_capacity(name.len());
+    let mut space = false;
+    for c in name.trim().chars() {
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+        if space && !is_punct(c) && !normalized.ends_with(is_punct) {
+            normalized.push(' ');
+        }
+        space = false;
+        normalized.push(c);
+    }
+    normalized
+}
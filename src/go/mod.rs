@@ -0,0 +1,19 @@
+//! Rust counterparts of Go types that commonly appear in gob streams.
+
+mod array;
+#[cfg(feature = "num-bigint")]
+mod big;
+pub mod duration;
+mod encoder;
+mod error;
+mod interface;
+pub mod net;
+
+pub use self::array::Array;
+#[cfg(feature = "num-bigint")]
+pub use self::big::BigIntError;
+pub use self::duration::Duration;
+pub use self::encoder::{GobDecoder, GobEncoded, GobEncoder};
+pub use self::error::GobError;
+pub use self::interface::{normalize_type_name, Interface};
+pub use self::net::{IPNet, IP};
@@ -0,0 +1,314 @@
+//! Go's `net.IP` and `net.IPNet`.
+//!
+//! A `net.IP` encodes itself through `MarshalText`, so Go sends it as a
+//! type implementing `TextMarshaler`, named `IP`, whose values are byte
+//! strings holding the address in text form:
+//!
+//! ```text
+//! 192.0.2.1    ->  09 "192.0.2.1"
+//! 2001:db8::1  ->  0b "2001:db8::1"
+//! ```
+//!
+//! `IpAddr`, `Ipv4Addr` and `Ipv6Addr` are sent the same way, and can be
+//! decoded from it as well.
+//!
+//! A `net.IP` that was converted to a plain `[]byte` is sent as the address
+//! in network byte order instead, 4 bytes long for IPv4 and 16 bytes long
+//! for IPv6, and `IP` decodes either form:
+//!
+//! ```text
+//! 192.0.2.1    ->  04 c0 00 02 01
+//! 2001:db8::1  ->  10 20 01 0d b8 00 00 00 00 00 00 00 00 00 00 00 01
+//! ```
+//!
+//! Go also stores IPv4 addresses in the 16-byte IPv4-mapped form
+//! `::ffff:a.b.c.d`, which `net.ParseIP` returns for them. Those decode to
+//! an IPv4 address, as Go treats both forms as the same address.
+//!
+//! A `net.IPNet` is a struct with an `IP` field holding a `net.IP`, and a
+//! `Mask` field holding a `[]byte` of the same length as the address in
+//! byte form.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_gob::types::{Type, TypeId};
+use serde_gob::{GobSerialize, Schema};
+
+use crate::de::{deserialize_with_kind, WireKind};
+
+/// A Go `net.IP`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IP(pub IpAddr);
+
+impl From<IpAddr> for IP {
+    fn from(addr: IpAddr) -> IP {
+        IP(addr)
+    }
+}
+
+impl From<Ipv4Addr> for IP {
+    fn from(addr: Ipv4Addr) -> IP {
+        IP(IpAddr::V4(addr))
+    }
+}
+
+impl From<Ipv6Addr> for IP {
+    fn from(addr: Ipv6Addr) -> IP {
+        IP(IpAddr::V6(addr))
+    }
+}
+
+impl From<IP> for IpAddr {
+    fn from(ip: IP) -> IpAddr {
+        ip.0
+    }
+}
+
+impl IP {
+    fn from_octets(octets: &[u8]) -> Option<IP> {
+        if let Ok(octets) = <[u8; 4]>::try_from(octets) {
+            return Some(IP::from(Ipv4Addr::from(octets)));
+        }
+        let addr = Ipv6Addr::from(<[u8; 16]>::try_from(octets).ok()?);
+        Some(IP::from_v6(addr))
+    }
+
+    fn from_text(text: &[u8]) -> Option<IP> {
+        let addr = ::std::str::from_utf8(text).ok()?.parse().ok()?;
+        Some(match addr {
+            IpAddr::V4(v4) => IP::from(v4),
+            IpAddr::V6(v6) => IP::from_v6(v6),
+        })
+    }
+
+    fn from_v6(addr: Ipv6Addr) -> IP {
+        match addr.to_ipv4_mapped() {
+            Some(v4) => IP::from(v4),
+            None => IP::from(addr),
+        }
+    }
+}
+
+impl Serialize for IP {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for IP {
+    fn deserialize<D>(deserializer: D) -> Result<IP, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct IPVisitor;
+
+        impl<'de> Visitor<'de> for IPVisitor {
+            type Value = IP;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an IP address as text or bytes")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<IP, A::Error> {
+                let kind = seq.next_element::<WireKind>()?;
+                let bytes = seq
+                    .next_element::<Octets>()?
+                    .ok_or_else(|| de::Error::custom("missing IP address"))?;
+                match kind {
+                    Some(WireKind::Encoded) => IP::from_text(&bytes.0)
+                        .ok_or_else(|| de::Error::custom("invalid IP address text")),
+                    _ => IP::from_octets(&bytes.0).ok_or_else(|| {
+                        de::Error::custom(format!("invalid IP length {}", bytes.0.len()))
+                    }),
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<IP, E> {
+                IP::from_text(text.as_bytes()).ok_or_else(|| E::custom("invalid IP address text"))
+            }
+
+            // without the wire type at hand, a 4 or 16 byte long value is
+            // taken to be the byte form
+            fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<IP, E> {
+                IP::from_octets(bytes)
+                    .or_else(|| IP::from_text(bytes))
+                    .ok_or_else(|| E::custom("invalid IP address"))
+            }
+        }
+
+        deserialize_with_kind(deserializer, IPVisitor)
+    }
+}
+
+impl GobSerialize for IP {
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        IpAddr::schema_register(schema)
+    }
+}
+
+/// A Go `net.IPNet`, limited to canonical masks, i.e. a run of ones
+/// followed by zeros, as produced by `net.ParseCIDR` and `net.CIDRMask`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IPNet {
+    ip: IpAddr,
+    prefix_len: u8,
+}
+
+impl IPNet {
+    /// Creates a network from an address and the number of leading ones in
+    /// its mask, or returns `None` if the prefix is longer than the address.
+    pub fn new(ip: IpAddr, prefix_len: u8) -> Option<IPNet> {
+        if prefix_len as usize > octets(ip).len() * 8 {
+            return None;
+        }
+        Some(IPNet { ip, prefix_len })
+    }
+
+    pub fn ip(&self) -> IpAddr {
+        self.ip
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// The mask as Go stores it, as long as the address.
+    pub fn mask(&self) -> Vec<u8> {
+        let len = octets(self.ip).len();
+        let ones = self.prefix_len as usize;
+        (0..len)
+            .map(|i| match ones.saturating_sub(i * 8) {
+                0 => 0,
+                n if n >= 8 => 0xff,
+                n => !(0xffu8 >> n),
+            })
+            .collect()
+    }
+}
+
+fn octets(ip: IpAddr) -> Vec<u8> {
+    match ip {
+        IpAddr::V4(addr) => addr.octets().to_vec(),
+        IpAddr::V6(addr) => addr.octets().to_vec(),
+    }
+}
+
+/// Counts the leading ones of a mask, or returns `None` if it is not
+/// canonical.
+fn prefix_len(mask: &[u8]) -> Option<u8> {
+    let mut ones = 0;
+    let mut rest = mask.iter();
+    for &b in rest.by_ref() {
+        ones += b.leading_ones();
+        if b != 0xff {
+            if b.leading_ones() + b.trailing_zeros() != 8 {
+                return None;
+            }
+            break;
+        }
+    }
+    if rest.all(|&b| b == 0) {
+        Some(ones as u8)
+    } else {
+        None
+    }
+}
+
+impl Serialize for IPNet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("IPNet", 2)?;
+        state.serialize_field("IP", &IP(self.ip))?;
+        state.serialize_field("Mask", &Octets(self.mask()))?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for IPNet {
+    fn deserialize<D>(deserializer: D) -> Result<IPNet, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct WireIPNet {
+            #[serde(rename = "IP")]
+            ip: IP,
+            #[serde(rename = "Mask")]
+            mask: Octets,
+        }
+
+        let net = WireIPNet::deserialize(deserializer)?;
+        let ip = net.ip.0;
+        let mut mask = &net.mask.0[..];
+        // an IPv4 mask in its 16-byte form
+        if ip.is_ipv4() && mask.len() == 16 && mask[..12].iter().all(|&b| b == 0xff) {
+            mask = &mask[12..];
+        }
+        if mask.len() != octets(ip).len() {
+            return Err(de::Error::custom(format!(
+                "IP mask length {} does not match the address",
+                mask.len()
+            )));
+        }
+        match prefix_len(mask) {
+            Some(prefix_len) => Ok(IPNet { ip, prefix_len }),
+            None => Err(de::Error::custom("non-canonical IP mask")),
+        }
+    }
+}
+
+impl GobSerialize for IPNet {
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let ip = IP::schema_register(schema)?;
+        schema.register_type(
+            Type::build()
+                .struct_type("IPNet", 2)
+                .field("IP", ip)
+                .field("Mask", S::TypeId::BYTES)
+                .end(),
+        )
+    }
+}
+
+/// A `[]byte` decoded into an owned buffer.
+struct Octets(Vec<u8>);
+
+impl Serialize for Octets {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Octets {
+    fn deserialize<D>(deserializer: D) -> Result<Octets, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct OctetsVisitor;
+
+        impl<'de> Visitor<'de> for OctetsVisitor {
+            type Value = Octets;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a byte slice")
+            }
+
+            fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Octets, E> {
+                Ok(Octets(bytes.to_vec()))
+            }
+        }
+
+        deserializer.deserialize_bytes(OctetsVisitor)
+    }
+}
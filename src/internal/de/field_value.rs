@@ -1,18 +1,21 @@
 use std::io::Cursor;
 
 use bytes::Buf;
-use serde::de::{IgnoredAny, Visitor};
+
+use serde::de::value::SeqDeserializer;
+use serde::de::{IgnoredAny, IntoDeserializer, Visitor};
 use serde::{self, Deserialize};
 
 use crate::error::Error;
-//use crate::internal::de::InterfaceValueDeserializer;
 use crate::internal::gob::Message;
-use crate::internal::types::{TypeId, Types, WireType};
+use crate::internal::types::{ArrayType, SliceType, TypeId, Types, WireType};
 
 use super::complex_value::ComplexValueDeserializer;
+use super::interface_value::InterfaceValueDeserializer;
 use super::map_value::MapValueDeserializer;
 use super::seq_value::SeqValueDeserializer;
 use super::struct_value::StructValueDeserializer;
+use super::{char_from_rune, visit_with_kind, INTERFACE_TOKEN, WIRE_KIND_TOKEN};
 
 pub(crate) struct FieldValueDeserializer<'t, 'de>
 where
@@ -33,17 +36,45 @@ impl<'t, 'de> FieldValueDeserializer<'t, 'de> {
     }
 
     fn deserialize_byte_slice(&mut self) -> Result<&'de [u8], Error> {
-        let len = self.msg.read_bytes_len()?;
-        let pos = self.msg.get_ref().position() as usize;
-        self.msg.get_mut().advance(len);
-        let bytes = &self.msg.get_ref().get_ref()[pos..pos + len];
-        Ok(bytes)
+        Ok(self.msg.read_byte_slice()?)
     }
 
     fn deserialize_str_slice(&mut self) -> Result<&'de str, Error> {
         let bytes = self.deserialize_byte_slice()?;
         ::std::str::from_utf8(bytes).map_err(|err| serde::de::Error::custom(err))
     }
+
+    /// Runs `f` one nesting level deeper, for values of composite types.
+    fn nested<T, F>(self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&'t Types, &mut Message<Cursor<&'de [u8]>>) -> Result<T, Error>,
+    {
+        self.msg.enter_nested()?;
+        let result = f(self.defs, &mut *self.msg);
+        self.msg.leave_nested();
+        result
+    }
+
+    /// Whether the value is of a type that encodes itself, which Go sends
+    /// as the byte string its `GobEncode`, `MarshalBinary` or `MarshalText`
+    /// returned.
+    fn is_encoded(&self) -> bool {
+        matches!(
+            self.defs.lookup(self.type_id),
+            Some(WireType::GobEncoder(_))
+                | Some(WireType::BinaryMarshaler(_))
+                | Some(WireType::TextMarshaler(_))
+        )
+    }
+
+    fn deserialize_interface<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.nested(|defs, msg| {
+            serde::Deserializer::deserialize_any(InterfaceValueDeserializer::new(defs, msg), visitor)
+        })
+    }
 }
 // primitive!(deserialize_i64, i64, visit_i64, INT, |d: Self| d.msg
 //    .read_int());
@@ -52,6 +83,8 @@ macro_rules! primitive {
         fn $fname<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
             if self.type_id == TypeId::$id {
                 visitor.$visit($parse(self)? as $tname)
+            } else if self.type_id == TypeId::INTERFACE {
+                self.deserialize_interface(visitor)
             } else {
                 Err(serde::de::Error::custom(format!("primitive expected {}", stringify!($tname))))
             }
@@ -74,60 +107,37 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
             TypeId::BYTES => visitor.visit_borrowed_bytes(self.deserialize_byte_slice()?),
             TypeId::STRING => visitor.visit_borrowed_str(self.deserialize_str_slice()?),
             TypeId::COMPLEX => ComplexValueDeserializer::new(self.msg).deserialize_any(visitor),
-            TypeId::INTERFACE => {
-                let k_ty_name = self.deserialize_str_slice()?;
-                let _k_ty_id = self.msg.read_int()?;
-                
-                // Byte count of value is next; we don't care what it is (it's there
-                // in case we want to ignore the value by skipping it completely).
-                let _bytes_count = self.msg.read_uint()?;
-
-                let _singleton = self.msg.read_uint()?;
-                match k_ty_name {
-                    "string" => {
-                        visitor.visit_borrowed_str(self.deserialize_str_slice()?)
-                    }
-                    "int64" => {
-                        visitor.visit_i64(self.msg.read_int()?)
-                    }
-                    "bool" => {
-                        visitor.visit_bool(self.msg.read_bool()?)
-                    }
-                    _ => {
-                        Err(serde::de::Error::custom("not a map[interface{}]interface{}"))
-                    }
-                }
-            },
+            TypeId::INTERFACE => self.deserialize_interface(visitor),
             _ => {
                 if let Some(wire_type) = self.defs.lookup(self.type_id) {
-                    match wire_type {
+                    self.nested(|defs, msg| match wire_type {
                         &WireType::Struct(ref struct_type) => {
-                            let de = StructValueDeserializer::new(struct_type, self.defs, self.msg);
+                            let de = StructValueDeserializer::new(struct_type, defs, msg);
                             de.deserialize_any(visitor)
                         }
                         &WireType::Slice(ref slice_type) => {
-                            let de = SeqValueDeserializer::new(
-                                None,
-                                slice_type.elem,
-                                self.defs,
-                                self.msg,
-                            );
+                            let de = SeqValueDeserializer::new(None, slice_type.elem, defs, msg);
                             de.deserialize_any(visitor)
                         }
                         &WireType::Array(ref array_type) => {
                             let de = SeqValueDeserializer::new(
                                 Some(array_type.len as usize),
                                 array_type.elem,
-                                self.defs,
-                                self.msg,
+                                defs,
+                                msg,
                             );
                             de.deserialize_any(visitor)
                         }
                         &WireType::Map(ref map_type) => {
-                            let de = MapValueDeserializer::new(map_type, self.defs, self.msg);
+                            let de = MapValueDeserializer::new(map_type, defs, msg);
                             de.deserialize_any(visitor)
                         }
-                    }
+                        &WireType::GobEncoder(_)
+                        | &WireType::BinaryMarshaler(_)
+                        | &WireType::TextMarshaler(_) => {
+                            visitor.visit_borrowed_bytes(msg.read_byte_slice()?)
+                        }
+                    })
                 } else {
                     Err(serde::de::Error::custom(format!(
                         "unknown type id {:?}",
@@ -141,26 +151,11 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
     // primitive!(deserialize_bool, bool, visit_bool, BOOL, |d: Self| d.msg
     //     .read_bool());
 
-    fn deserialize_bool<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         if self.type_id == TypeId::BOOL {
             visitor.visit_bool(self.msg.read_bool()?)
         } else if self.type_id == TypeId::INTERFACE {
-            let v_ty_name = self.deserialize_str_slice()?;
-            let _v_ty_id = self.msg.read_int()?;
-            
-            // Byte count of value is next; we don't care what it is (it's there
-            // in case we want to ignore the value by skipping it completely).
-            self.msg.read_uint()?;
-
-            let _singleton = self.msg.read_uint()?;
-            match v_ty_name {
-                "bool" => {
-                    visitor.visit_bool(self.msg.read_bool()?)
-                }
-                _ => {
-                    Err(serde::de::Error::custom("not a map[interface{}]interface{}"))
-                }
-            }
+            self.deserialize_interface(visitor)
         } else {
             Err(serde::de::Error::custom("expected bool"))
         }
@@ -175,33 +170,11 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
     // primitive!(deserialize_i64, i64, visit_i64, INT, |d: Self| d.msg
     //     .read_int());
 
-    fn deserialize_i64<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         if self.type_id == TypeId::INT {
             visitor.visit_i64(self.msg.read_int()?)
         } else if self.type_id == TypeId::INTERFACE {
-            let v_ty_name = self.deserialize_str_slice()?;
-            let _v_ty_id = self.msg.read_int()?;
-            
-            // Byte count of value is next; we don't care what it is (it's there
-            // in case we want to ignore the value by skipping it completely).
-            let _bytes_count = self.msg.read_uint()?;
-
-            let _singleton = self.msg.read_uint()?;
-            match v_ty_name {
-                "string" => {
-                    visitor.visit_borrowed_str(self.deserialize_str_slice()?)
-                }
-                "int64" => {
-                    visitor.visit_i64(self.msg.read_int()?)
-                }
-                "bool" => {
-                    visitor.visit_bool(self.msg.read_bool()?)
-                }
-                _ => {
-                    Err(serde::de::Error::custom("not a map[interface{}]interface{}"))
-                }
-            }
-            //visitor.visit_i64(self.msg.read_int()?)
+            self.deserialize_interface(visitor)
         } else {
             Err(serde::de::Error::custom("expected i64"))
         }
@@ -216,37 +189,20 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
     primitive!(deserialize_u64, u64, visit_u64, UINT, |d: Self| d.msg
         .read_uint());
 
+    // gob has a single float type, which Go sends as a float64 even for a
+    // float32, so both widths read the same and convert
     primitive!(deserialize_f32, f32, visit_f32, FLOAT, |d: Self| d.msg
         .read_float());
     primitive!(deserialize_f64, f64, visit_f64, FLOAT, |d: Self| d.msg
         .read_float());
 
     fn deserialize_str<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
-        if self.type_id == TypeId::STRING {
+        // a `TextMarshaler` sends text, such as the address of a `net.IP`
+        let is_text = matches!(self.defs.lookup(self.type_id), Some(WireType::TextMarshaler(_)));
+        if self.type_id == TypeId::STRING || is_text {
             visitor.visit_borrowed_str(self.deserialize_str_slice()?)
         } else if self.type_id == TypeId::INTERFACE {
-            let v_ty_name = self.deserialize_str_slice()?;
-            let _v_ty_id = self.msg.read_int()?;
-            
-            // Byte count of value is next; we don't care what it is (it's there
-            // in case we want to ignore the value by skipping it completely).
-            let _bytes_count = self.msg.read_uint()?;
-
-            let _singleton = self.msg.read_uint()?;
-            match v_ty_name {
-                "string" => {
-                    visitor.visit_borrowed_str(self.deserialize_str_slice()?)
-                }
-                "int64" => {
-                    visitor.visit_i64(self.msg.read_int()?)
-                }
-                "bool" => {
-                    visitor.visit_bool(self.msg.read_bool()?)
-                }
-                _ => {
-                    Err(serde::de::Error::custom("not a map[interface{}]interface{}"))
-                }
-            }
+            self.deserialize_interface(visitor)
         } else {
             Err(serde::de::Error::custom("expected str"))
         }
@@ -257,10 +213,31 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
     }
 
     fn deserialize_bytes<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
-        if self.type_id == TypeId::BYTES {
-            visitor.visit_borrowed_bytes(self.deserialize_byte_slice()?)
-        } else {
-            Err(serde::de::Error::custom("expected bytes"))
+        if self.type_id == TypeId::BYTES || self.is_encoded() {
+            return visitor.visit_borrowed_bytes(self.deserialize_byte_slice()?);
+        }
+        match self.defs.lookup(self.type_id) {
+            // a `[N]byte`, which Go sends element by element rather than
+            // as a byte string
+            Some(&WireType::Array(ArrayType { elem: TypeId::UINT, len, .. })) => {
+                if self.msg.read_uint()? != len as u64 {
+                    return Err(serde::de::Error::custom(format!(
+                        "sequence len mismatch (expected {})",
+                        len
+                    )));
+                }
+                self.msg.charge(len as usize)?;
+                // every element takes up at least one byte
+                let mut bytes = Vec::with_capacity((len as usize).min(self.msg.get_ref().remaining()));
+                for _ in 0..len {
+                    let byte = self.msg.read_uint()?;
+                    bytes.push(u8::try_from(byte).map_err(|_| {
+                        Error::deserialize(format!("array element {} out of range for a byte", byte))
+                    })?);
+                }
+                visitor.visit_byte_buf(bytes)
+            }
+            _ => Err(serde::de::Error::custom("expected bytes")),
         }
     }
 
@@ -268,24 +245,40 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
         self.deserialize_bytes(visitor)
     }
 
+    fn deserialize_seq<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.type_id == TypeId::BYTES {
+            // a `Vec<u8>` asks for a sequence rather than bytes
+            let bytes = self.deserialize_byte_slice()?;
+            visitor.visit_seq(SeqDeserializer::new(bytes.iter().cloned()))
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let int = i64::deserialize(self)?;
-        if let Some(c) = ::std::char::from_u32(int as u32) {
-            visitor.visit_char(c)
+        let rune = i64::deserialize(self)?;
+        visitor.visit_char(char_from_rune(rune)?)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.type_id == TypeId::INTERFACE {
+            self.nested(|defs, msg| {
+                InterfaceValueDeserializer::new(defs, msg).deserialize_option(visitor)
+            })
         } else {
-            Err(serde::de::Error::custom(format!(
-                "invalid char code {}",
-                int
-            )))
+            visitor.visit_some(self)
         }
     }
 
     #[inline]
     fn deserialize_enum<V>(
-        self,
+        mut self,
         name: &'static str,
         variants: &'static [&'static str],
         visitor: V,
@@ -294,10 +287,33 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
         V: Visitor<'de>,
     {
         if let Some(&WireType::Struct(ref struct_type)) = self.defs.lookup(self.type_id) {
-            let de = StructValueDeserializer::new(struct_type, self.defs, self.msg);
-            de.deserialize_enum(name, variants, visitor)
-        } else {
-            Err(serde::de::Error::custom("not an enum type"))
+            return self.nested(|defs, msg| {
+                let de = StructValueDeserializer::new(struct_type, defs, msg);
+                de.deserialize_enum(name, variants, visitor)
+            });
+        }
+
+        // a C-like enum, sent as the name or the index of its variant
+        match self.type_id {
+            TypeId::STRING => {
+                let variant = self.deserialize_str_slice()?;
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            TypeId::INT | TypeId::UINT => {
+                let index = if self.type_id == TypeId::INT {
+                    u32::try_from(self.msg.read_int()?).ok()
+                } else {
+                    u32::try_from(self.msg.read_uint()?).ok()
+                };
+                match index {
+                    Some(index) => visitor.visit_enum(index.into_deserializer()),
+                    None => Err(serde::de::Error::custom(format!(
+                        "variant index out of range for enum {}",
+                        name
+                    ))),
+                }
+            }
+            _ => Err(serde::de::Error::custom("not an enum type")),
         }
     }
 
@@ -312,10 +328,41 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
         V: Visitor<'de>,
     {
         if let Some(&WireType::Struct(ref struct_type)) = self.defs.lookup(self.type_id) {
-            let de = StructValueDeserializer::new(struct_type, self.defs, self.msg);
-            de.deserialize_struct(name, fields, visitor)
+            self.nested(|defs, msg| {
+                let de = StructValueDeserializer::new(struct_type, defs, msg);
+                de.deserialize_struct(name, fields, visitor)
+            })
+        } else if self.type_id == TypeId::INTERFACE {
+            self.nested(|defs, msg| {
+                InterfaceValueDeserializer::new(defs, msg).deserialize_struct(name, fields, visitor)
+            })
         } else {
-            Err(serde::de::Error::custom("not a struct type"))
+            Err(serde::de::Error::custom(format!(
+                "cannot decode {} type {} into struct {}",
+                self.defs.kind(self.type_id).unwrap_or("unknown"),
+                self.type_id.0,
+                name
+            )))
+        }
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // Fixed-size arrays are deserialized as tuples. Their length has to
+        // match the sequence's, or the elements left over would be read as
+        // whatever follows.
+        match self.defs.lookup(self.type_id) {
+            Some(WireType::Struct(struct_type)) => self.nested(|defs, msg| {
+                let de = StructValueDeserializer::new(struct_type, defs, msg);
+                de.deserialize_tuple(len, visitor)
+            }),
+            Some(WireType::Array(ArrayType { elem, .. }))
+            | Some(WireType::Slice(SliceType { elem, .. })) => self.nested(|defs, msg| {
+                SeqValueDeserializer::new(Some(len), *elem, defs, msg).deserialize_any(visitor)
+            }),
+            _ => self.deserialize_any(visitor),
         }
     }
 
@@ -328,8 +375,58 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
         visitor.visit_unit()
     }
 
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == INTERFACE_TOKEN && self.type_id == TypeId::INTERFACE {
+            self.nested(|defs, msg| {
+                InterfaceValueDeserializer::new(defs, msg).deserialize_newtype_struct(name, visitor)
+            })
+        } else if name == INTERFACE_TOKEN {
+            self.deserialize_any(visitor)
+        } else {
+            // a named type, sent like the type it wraps
+            visitor.visit_newtype_struct(self)
+        }
+    }
+
+    fn deserialize_ignored_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // skipping a string does not need it to be valid UTF-8
+        if self.type_id == TypeId::STRING {
+            self.deserialize_byte_slice()?;
+            visitor.visit_unit()
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == WIRE_KIND_TOKEN {
+            let kind = self.defs.wire_kind(self.type_id).ok_or_else(|| {
+                Error::deserialize(format!("unknown type id {:?}", self.type_id))
+            })?;
+            return visit_with_kind(kind, self, visitor);
+        }
+        self.deserialize_any(visitor)
+    }
+
     forward_to_deserialize_any! {
-        option unit_struct newtype_struct seq tuple
-        tuple_struct map identifier ignored_any
+        unit_struct map identifier
     }
 }
@@ -1,84 +1,190 @@
 use std::io::Cursor;
+
 use bytes::Buf;
-use serde::de::{DeserializeSeed, Deserializer, IntoDeserializer, SeqAccess, Visitor};
+use serde::de::{Deserializer, IgnoredAny, Visitor};
+
+use super::{FieldValueDeserializer, INTERFACE_TOKEN, INTERFACE_VALUE};
 use crate::error::Error;
 use crate::internal::gob::Message;
+use crate::internal::types::{TypeId, Types, WireType};
 
-struct InterfaceSeqAccess<'t, 'de>
+/// Deserializes an `interface{}` value.
+///
+/// On the wire this is the registered name of the concrete type, its type id,
+/// the byte count of the value and then the value itself, which (unless it is
+/// a struct) is preceded by a singleton field delta. A nil interface is sent
+/// as an empty name and nothing else.
+///
+/// Values sent without a byte count are not supported; Go always sends it.
+pub(crate) struct InterfaceValueDeserializer<'t, 'de>
 where
     'de: 't,
 {
-    remaining_count: u64,
+    defs: &'t Types,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
 
-impl<'t, 'de> InterfaceSeqAccess<'t, 'de> {
+impl<'t, 'de> InterfaceValueDeserializer<'t, 'de> {
     #[inline]
-    fn new(msg: &'t mut Message<Cursor<&'de [u8]>>) -> InterfaceSeqAccess<'t, 'de> {
-        InterfaceSeqAccess {
-            remaining_count: 2,
-            msg,
+    pub(crate) fn new(
+        defs: &'t Types,
+        msg: &'t mut Message<Cursor<&'de [u8]>>,
+    ) -> InterfaceValueDeserializer<'t, 'de> {
+        InterfaceValueDeserializer { defs, msg }
+    }
+
+    /// Reads the interface header, returning the name and id of the
+    /// concrete type, or `None` for a nil interface.
+    fn read_header(&mut self) -> Result<Option<(&'de str, TypeId)>, Error> {
+        let name = self.msg.read_byte_slice()?;
+        if name.is_empty() {
+            return Ok(None);
+        }
+        let name = ::std::str::from_utf8(name).map_err(<Error as serde::de::Error>::custom)?;
+
+        let type_id = TypeId(self.msg.read_int()?);
+
+        // Go always sends the byte count, so that decoders can skip values
+        // of unknown types; we go by the type definition and only check
+        // that the value fits in the message
+        let count = self.msg.read_uint()?;
+        if count > self.msg.get_ref().remaining() as u64 {
+            return Err(serde::de::Error::custom(format!(
+                "interface value of {} bytes exceeds the message",
+                count
+            )));
+        }
+
+        if let Some(&WireType::Struct(_)) = self.defs.lookup(type_id) {
+            return Ok(Some((name, type_id)));
+        }
+        if self.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom(
+                "neither a singleton nor a struct value",
+            ));
         }
+        Ok(Some((name, type_id)))
+    }
+
+    fn concrete(mut self) -> Result<FieldValueDeserializer<'t, 'de>, Error> {
+        match self.read_header()? {
+            Some((_, type_id)) => Ok(FieldValueDeserializer::new(type_id, self.defs, self.msg)),
+            None => Err(serde::de::Error::custom("nil interface value")),
+        }
+    }
+
+    /// Decodes the concrete value into the type registered for its name and
+    /// passes it on to `go::Interface` along with the name.
+    fn deserialize_registered<V>(mut self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (name, type_id) = match self.read_header()? {
+            Some(header) => header,
+            None => return visitor.visit_unit(),
+        };
+        let decoder = match self.defs.interface_decoder(name) {
+            Some(decoder) => decoder,
+            None => {
+                return Err(serde::de::Error::custom(format!(
+                    "interface type {:?} is not registered",
+                    name
+                )))
+            }
+        };
+        let value = decoder(FieldValueDeserializer::new(type_id, self.defs, self.msg))?;
+        INTERFACE_VALUE.with(|slot| *slot.borrow_mut() = Some(value));
+        let result = visitor.visit_borrowed_str(name);
+        INTERFACE_VALUE.with(|slot| slot.borrow_mut().take());
+        result
     }
 }
 
-impl<'t, 'de> SeqAccess<'de> for InterfaceSeqAccess<'t, 'de> {
+impl<'t, 'de> Deserializer<'de> for InterfaceValueDeserializer<'t, 'de> {
     type Error = Error;
 
-    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        T: DeserializeSeed<'de>,
+        V: Visitor<'de>,
     {
-        if self.remaining_count == 0 {
-            return Ok(None);
+        match self.read_header()? {
+            Some((_, type_id)) => {
+                FieldValueDeserializer::new(type_id, self.defs, self.msg).deserialize_any(visitor)
+            }
+            None => visitor.visit_unit(),
         }
-        self.remaining_count -= 1;
-        
-        let len = self.msg.read_bytes_len()?;
-        let pos = self.msg.get_ref().position() as usize;
-        self.msg.get_mut().advance(len);
-        let bytes = &self.msg.get_ref().get_ref()[pos..pos + len];
-        println!("bytes: {:?}", bytes);
-
-        let float = self.msg.read_float()?;
-        seed.deserialize(float.into_deserializer()).map(Some)
     }
 
-    fn size_hint(&self) -> Option<usize> {
-        Some(self.remaining_count as usize)
+    fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.read_header()? {
+            Some((_, type_id)) => {
+                visitor.visit_some(FieldValueDeserializer::new(type_id, self.defs, self.msg))
+            }
+            None => visitor.visit_none(),
+        }
     }
-}
 
-pub(crate) struct InterfaceValueDeserializer<'t, 'de>
-where
-    'de: 't,
-{
-    msg: &'t mut Message<Cursor<&'de [u8]>>,
-}
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.concrete()?.deserialize_char(visitor)
+    }
 
-impl<'t, 'de> InterfaceValueDeserializer<'t, 'de> {
-    #[inline]
-    pub(crate) fn new(
-        msg: &'t mut Message<Cursor<&'de [u8]>>,
-    ) -> InterfaceValueDeserializer<'t, 'de> {
-        InterfaceValueDeserializer { msg }
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.concrete()?.deserialize_enum(name, variants, visitor)
     }
-}
 
-impl<'t, 'de> Deserializer<'de> for InterfaceValueDeserializer<'t, 'de> {
-    type Error = Error;
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == INTERFACE_TOKEN {
+            self.deserialize_registered(visitor)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.concrete()?.deserialize_struct(name, fields, visitor)
+    }
 
     #[inline]
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(InterfaceSeqAccess::new(self.msg))
+        self.deserialize_ignored_any(IgnoredAny)?;
+        visitor.visit_unit()
     }
 
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
-        byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 str string bytes
+        byte_buf unit_struct seq tuple
+        tuple_struct map identifier ignored_any
     }
 }
@@ -0,0 +1,53 @@
+use serde::de::{Deserialize, DeserializeSeed, Deserializer, IgnoredAny, IntoDeserializer, SeqAccess, Visitor};
+
+use crate::error::Error;
+use crate::internal::types::WireKind;
+
+/// Hands the kind of a value's wire type to a visitor, followed by the
+/// value itself, as the two elements of a tuple.
+struct KindAccess<D> {
+    kind: Option<WireKind>,
+    value: Option<D>,
+}
+
+impl<'de, D> SeqAccess<'de> for KindAccess<D>
+where
+    D: Deserializer<'de, Error = Error>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(kind) = self.kind.take() {
+            return seed.deserialize(kind.as_str().into_deserializer()).map(Some);
+        }
+        match self.value.take() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.kind.is_some() as usize + self.value.is_some() as usize)
+    }
+}
+
+/// Visits the value `de` reads as the tuple of its wire type's kind and
+/// the value, skipping the value if the visitor did not read it.
+pub(crate) fn visit_with_kind<'de, D, V>(kind: WireKind, de: D, visitor: V) -> Result<V::Value, Error>
+where
+    D: Deserializer<'de, Error = Error>,
+    V: Visitor<'de>,
+{
+    let mut access = KindAccess {
+        kind: Some(kind),
+        value: Some(de),
+    };
+    let value = visitor.visit_seq(&mut access)?;
+    if let Some(de) = access.value {
+        IgnoredAny::deserialize(de)?;
+    }
+    Ok(value)
+}
@@ -1,10 +1,19 @@
 use std::io::Cursor;
+use std::mem;
 
+use bytes::Buf;
+
+#[cfg(feature = "trace")]
+use serde::Deserialize;
 use serde::de::{DeserializeSeed, Deserializer, MapAccess, Visitor};
 
 use super::FieldValueDeserializer;
-use crate::error::Error;
+use crate::error::{Error, ErrorKind};
+#[cfg(feature = "trace")]
+use crate::internal::gob::Checkpoint;
 use crate::internal::gob::Message;
+#[cfg(feature = "trace")]
+use crate::internal::types::TypeId;
 use crate::internal::types::{MapType, Types};
 
 struct MapMapAccess<'t, 'de>
@@ -13,6 +22,7 @@ where
 {
     def: &'t MapType,
     defs: &'t Types,
+    len: u64,
     remaining_count: u64,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
@@ -27,10 +37,47 @@ impl<'t, 'de> MapMapAccess<'t, 'de> {
         Ok(MapMapAccess {
             def,
             defs,
+            len,
             remaining_count: len,
             msg,
         })
     }
+
+    /// Points out the entry at which the message ran out, since a map
+    /// declaring more entries than it holds would otherwise fail with
+    /// whatever its key or value type makes of the missing bytes.
+    fn entry_error(&self, err: Error) -> Error {
+        if err.kind() != ErrorKind::Truncated {
+            return err;
+        }
+        Error::truncated(format!(
+            "map ended at entry {} of {} declared",
+            self.len - self.remaining_count - 1,
+            self.len
+        ))
+    }
+
+    /// Traces the key just read from `start` when the map is a
+    /// `map[interface{}]interface{}`, whose string keys stand in for the
+    /// fields of a struct.
+    #[cfg(feature = "trace")]
+    fn trace_key(&mut self, start: Checkpoint) {
+        if self.def.key != TypeId::INTERFACE {
+            return;
+        }
+        let end = self.msg.checkpoint();
+        self.msg.restore(start);
+        let de = FieldValueDeserializer::new(self.def.key, self.defs, self.msg);
+        let key = String::deserialize(de);
+        self.msg.restore(end);
+        if let Ok(key) = key {
+            gob_trace!(
+                "decoding field {:?} at offset {}",
+                key,
+                self.msg.get_ref().position()
+            );
+        }
+    }
 }
 
 impl<'f, 'de> MapAccess<'de> for MapMapAccess<'f, 'de> {
@@ -44,20 +91,33 @@ impl<'f, 'de> MapAccess<'de> for MapMapAccess<'f, 'de> {
             return Ok(None);
         }
         self.remaining_count -= 1;
+        self.msg.charge(mem::size_of::<K::Value>())?;
+        #[cfg(feature = "trace")]
+        let start = self.msg.checkpoint();
         let de = FieldValueDeserializer::new(self.def.key, self.defs, &mut self.msg);
-        seed.deserialize(de).map(Some)
+        match seed.deserialize(de) {
+            Ok(key) => {
+                #[cfg(feature = "trace")]
+                self.trace_key(start);
+                Ok(Some(key))
+            }
+            Err(err) => Err(self.entry_error(err)),
+        }
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
         V: DeserializeSeed<'de>,
     {
+        self.msg.charge(mem::size_of::<V::Value>())?;
         let de = FieldValueDeserializer::new(self.def.elem, self.defs, &mut self.msg);
-        seed.deserialize(de)
+        seed.deserialize(de).map_err(|err| self.entry_error(err))
     }
 
     fn size_hint(&self) -> Option<usize> {
-        Some(self.remaining_count as usize)
+        // every entry takes up at least one byte
+        let remaining_bytes = self.msg.get_ref().remaining() as u64;
+        Some(self.remaining_count.min(remaining_bytes) as usize)
     }
 }
 
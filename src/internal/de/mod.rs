@@ -1,11 +1,47 @@
+use std::any::Any;
+use std::cell::RefCell;
+
 mod complex_value;
 mod field_value;
+mod interface_value;
+mod kind_value;
 mod map_value;
 mod seq_value;
 mod struct_value;
-// use mod interface_value;
 mod value;
+mod zero_value;
 
 pub(crate) use self::field_value::FieldValueDeserializer;
 pub(crate) use value::ValueDeserializer;
-//pub(crate) use interface_value::InterfaceValueDeserializer;
\ No newline at end of file
+pub(crate) use self::kind_value::visit_with_kind;
+
+use crate::error::{Error, FieldError};
+
+/// Converts a rune, which Go sends as an int, to a `char`, rejecting
+/// surrogates and values beyond the Unicode range.
+pub(crate) fn char_from_rune(rune: i64) -> Result<char, Error> {
+    u32::try_from(rune)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| Error::deserialize(format!("invalid char code {}", rune)))
+}
+
+/// Newtype struct name by which `go::Interface` asks for the concrete value
+/// of an interface to be decoded into the type registered for its name.
+pub(crate) const INTERFACE_TOKEN: &str = "$gob::Interface";
+
+/// Tuple struct name by which `de::deserialize_with_kind` asks for the kind
+/// of a value's wire type ahead of the value.
+pub(crate) const WIRE_KIND_TOKEN: &str = "$gob::WireKind";
+
+thread_local! {
+    /// Hands the decoded concrete value over to `go::Interface`, as visitors
+    /// can only be passed values of the serde data model.
+    pub(crate) static INTERFACE_VALUE: RefCell<Option<Box<dyn Any>>> = RefCell::new(None);
+}
+
+thread_local! {
+    /// Collects the struct fields skipped while decoding leniently, and is
+    /// `None` otherwise, when the first field failing fails the value.
+    pub(crate) static SKIPPED_FIELDS: RefCell<Option<Vec<FieldError>>> = const { RefCell::new(None) };
+}
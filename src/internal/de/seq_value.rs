@@ -1,4 +1,7 @@
 use std::io::Cursor;
+use std::mem;
+
+use bytes::Buf;
 
 use serde::de::{self, DeserializeSeed, Deserializer, Visitor};
 
@@ -55,12 +58,15 @@ impl<'f, 'de> de::SeqAccess<'de> for SeqAccess<'f, 'de> {
             return Ok(None);
         }
         self.remaining_count -= 1;
+        self.msg.charge(mem::size_of::<T::Value>())?;
         let de = FieldValueDeserializer::new(self.element, self.defs, &mut self.msg);
         seed.deserialize(de).map(Some)
     }
 
     fn size_hint(&self) -> Option<usize> {
-        Some(self.remaining_count as usize)
+        // every entry takes up at least one byte
+        let remaining_bytes = self.msg.get_ref().remaining() as u64;
+        Some(self.remaining_count.min(remaining_bytes) as usize)
     }
 }
 
@@ -1,12 +1,13 @@
 use std::io::Cursor;
 
 use serde;
-use serde::de::{DeserializeSeed, Deserializer, IntoDeserializer, Visitor};
-use serde::de::{EnumAccess, MapAccess, VariantAccess};
+use serde::de::{Deserialize, DeserializeSeed, Deserializer, IgnoredAny, IntoDeserializer, Visitor};
+use serde::de::{EnumAccess, MapAccess, SeqAccess, VariantAccess};
 
-use super::FieldValueDeserializer;
-use crate::error::Error;
-use crate::internal::gob::Message;
+use super::zero_value::ZeroValueDeserializer;
+use super::{FieldValueDeserializer, SKIPPED_FIELDS};
+use crate::error::{Error, FieldError};
+use crate::internal::gob::{ExtraFields, Message};
 use crate::internal::types::{FieldType, StructType, TypeId, Types};
 
 struct StructAccess<'t, 'de>
@@ -17,6 +18,8 @@ where
     defs: &'t Types,
     field_no: i64,
     field_id: TypeId,
+    // the fields the Rust type declares, if it told us
+    known: Option<&'static [&'static str]>,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
 
@@ -31,10 +34,35 @@ impl<'t, 'de> StructAccess<'t, 'de> {
             defs,
             field_no: -1,
             field_id: TypeId(0),
+            known: None,
             msg,
         }
     }
 
+    /// Whether the field is one the Rust type declares, or else is to be
+    /// handed to it anyway. Fails or skips the field's value otherwise.
+    fn accept_field(&mut self, field: &FieldType) -> Result<bool, Error> {
+        let known = match self.known {
+            Some(known) => known,
+            None => return Ok(true),
+        };
+        if known.contains(&&*field.name) {
+            return Ok(true);
+        }
+        match self.msg.extra_fields() {
+            ExtraFields::Deny => Err(Error::deserialize(format!(
+                "unknown field `{}` of Go type {}",
+                field.name, self.def.common.name
+            ))),
+            ExtraFields::Skip => {
+                let de = FieldValueDeserializer::new(field.id, self.defs, self.msg);
+                IgnoredAny::deserialize(de)?;
+                Ok(false)
+            }
+            ExtraFields::CollectIntoMap => Ok(true),
+        }
+    }
+
     fn current_field(&self) -> Result<&'t FieldType, Error> {
         let field_no = self.field_no as usize;
         self.def.fields.get(field_no).ok_or_else(|| {
@@ -53,15 +81,32 @@ impl<'t, 'de> MapAccess<'de> for StructAccess<'t, 'de> {
     where
         K: DeserializeSeed<'de>,
     {
-        let field_delta = self.msg.read_uint()?;
+        let field = loop {
+            let field_delta = self.msg.read_uint()?;
 
-        if field_delta == 0 {
-            return Ok(None);
-        }
+            if field_delta == 0 {
+                return Ok(None);
+            }
 
-        self.field_no += field_delta as i64;
-        let field = self.current_field()?;
+            if field_delta > self.def.fields.len() as u64 {
+                return Err(serde::de::Error::custom(format!(
+                    "field delta overflow ({}) on type {:?}",
+                    field_delta, self.def
+                )));
+            }
+            self.field_no += field_delta as i64;
+            let field = self.current_field()?;
+            if self.accept_field(field)? {
+                break field;
+            }
+        };
         self.field_id = field.id;
+        gob_trace!(
+            "decoding field {:?} (type {}) at offset {}",
+            field.name,
+            field.id.0,
+            self.msg.get_ref().position()
+        );
 
         let de = <&str as IntoDeserializer<'_, Error>>::into_deserializer(&field.name);
         let value = seed.deserialize(de)?;
@@ -75,6 +120,41 @@ impl<'t, 'de> MapAccess<'de> for StructAccess<'t, 'de> {
         let de = FieldValueDeserializer::new(self.field_id, self.defs, &mut self.msg);
         seed.deserialize(de)
     }
+
+    fn next_value<V>(&mut self) -> Result<V, Self::Error>
+    where
+        V: Deserialize<'de>,
+    {
+        if SKIPPED_FIELDS.with(|skipped| skipped.borrow().is_none()) {
+            return self.next_value_seed(::std::marker::PhantomData);
+        }
+
+        let start = self.msg.checkpoint();
+        let de = FieldValueDeserializer::new(self.field_id, self.defs, self.msg);
+        let error = match V::deserialize(de) {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+
+        // skip the field by its type, which fails again if the message
+        // itself is cut off or malformed
+        self.msg.restore(start);
+        let de = FieldValueDeserializer::new(self.field_id, self.defs, self.msg);
+        if IgnoredAny::deserialize(de).is_err() {
+            return Err(error);
+        }
+        let value = match V::deserialize(ZeroValueDeserializer) {
+            Ok(value) => value,
+            Err(_) => return Err(error),
+        };
+        let field = self.current_field()?.name.to_string();
+        SKIPPED_FIELDS.with(|skipped| {
+            if let Some(ref mut skipped) = *skipped.borrow_mut() {
+                skipped.push(FieldError { field, error });
+            }
+        });
+        Ok(value)
+    }
 }
 
 impl<'t, 'de> EnumAccess<'de> for StructAccess<'t, 'de> {
@@ -119,14 +199,14 @@ impl<'t, 'de> VariantAccess<'de> for StructAccess<'t, 'de> {
         }
     }
 
-    fn tuple_variant<V>(mut self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    fn tuple_variant<V>(mut self, len: usize, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
         let field = self.current_field()?;
         let val = {
             let de = FieldValueDeserializer::new(field.id, self.defs, &mut self.msg);
-            de.deserialize_seq(visitor)?
+            de.deserialize_tuple(len, visitor)?
         };
         let field_delta = self.msg.read_uint()?;
         if field_delta != 0 {
@@ -162,6 +242,159 @@ impl<'t, 'de> VariantAccess<'de> for StructAccess<'t, 'de> {
     }
 }
 
+/// Reads the fields of a struct in order, as the elements of a tuple.
+struct TupleAccess<'t, 'de>
+where
+    'de: 't,
+{
+    def: &'t StructType,
+    defs: &'t Types,
+    field_no: usize,
+    done: bool,
+    msg: &'t mut Message<Cursor<&'de [u8]>>,
+}
+
+impl<'t, 'de> SeqAccess<'de> for TupleAccess<'t, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+        match self.msg.read_uint()? {
+            0 => {
+                self.done = true;
+                return Ok(None);
+            }
+            1 => {}
+            _ => {
+                return Err(serde::de::Error::custom(format!(
+                    "tuple element {} omitted on type {:?}",
+                    self.field_no, self.def
+                )))
+            }
+        }
+        let field = self.def.fields.get(self.field_no).ok_or_else(|| {
+            <Error as serde::de::Error>::custom(format!(
+                "field number overflow ({}) on type {:?}",
+                self.field_no, self.def
+            ))
+        })?;
+        self.field_no += 1;
+        let de = FieldValueDeserializer::new(field.id, self.defs, self.msg);
+        seed.deserialize(de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.def.fields.len() - self.field_no)
+    }
+}
+
+/// Whether `def` is the struct a `Result` is sent as, with an `Ok` tag
+/// followed by the `Value` and the `Error` fields.
+fn is_result_struct(def: &StructType) -> bool {
+    match &def.fields[..] {
+        [ok, value, error] => {
+            ok.name == "Ok" && ok.id == TypeId::BOOL && value.name == "Value" && error.name == "Error"
+        }
+        _ => false,
+    }
+}
+
+/// Reads a `Result` from its tagged struct, where the `Value` or `Error`
+/// is left out if it is zero.
+struct ResultAccess<'t, 'de>
+where
+    'de: 't,
+{
+    def: &'t StructType,
+    defs: &'t Types,
+    // the `Value` or `Error` field, if it was sent
+    field: Option<&'t FieldType>,
+    msg: &'t mut Message<Cursor<&'de [u8]>>,
+}
+
+impl<'t, 'de> EnumAccess<'de> for ResultAccess<'t, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant), Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let mut field_no = self.msg.read_uint()?;
+        let mut is_ok = false;
+        if field_no == 1 {
+            is_ok = self.msg.read_bool()?;
+            match self.msg.read_uint()? {
+                0 => field_no = 0,
+                delta => field_no += delta,
+            }
+        }
+        match (is_ok, field_no) {
+            (_, 0) => {}
+            (true, 2) | (false, 3) => {
+                self.field = Some(&self.def.fields[field_no as usize - 1]);
+            }
+            _ => {
+                return Err(serde::de::Error::custom(format!(
+                    "Result field {} does not match its Ok tag",
+                    field_no
+                )))
+            }
+        }
+        let variant = if is_ok { "Ok" } else { "Err" };
+        let variant = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'t, 'de> VariantAccess<'de> for ResultAccess<'t, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(serde::de::Error::custom("Result has no unit variants"))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let field = match self.field {
+            Some(field) => field,
+            None => return seed.deserialize(ZeroValueDeserializer),
+        };
+        let val = seed.deserialize(FieldValueDeserializer::new(field.id, self.defs, self.msg))?;
+        if self.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom(
+                "Result has both a value and an error",
+            ));
+        }
+        Ok(val)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(serde::de::Error::custom("Result has no tuple variants"))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(serde::de::Error::custom("Result has no struct variants"))
+    }
+}
+
 pub(crate) struct StructValueDeserializer<'t, 'de>
 where
     'de: 't,
@@ -197,18 +430,61 @@ impl<'t, 'de> Deserializer<'de> for StructValueDeserializer<'t, 'de> {
     fn deserialize_enum<V>(
         self,
         _: &'static str,
-        _: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if variants == ["Ok", "Err"] && is_result_struct(self.def) {
+            return visitor.visit_enum(ResultAccess {
+                def: self.def,
+                defs: self.defs,
+                field: None,
+                msg: self.msg,
+            });
+        }
         visitor.visit_enum(StructAccess::new(self.def, self.defs, self.msg))
     }
 
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut access = TupleAccess {
+            def: self.def,
+            defs: self.defs,
+            field_no: 0,
+            done: false,
+            msg: self.msg,
+        };
+        let value = visitor.visit_seq(&mut access)?;
+        if !access.done && access.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom(format!(
+                "too many tuple elements on type {:?}",
+                access.def
+            )));
+        }
+        Ok(value)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut access = StructAccess::new(self.def, self.defs, self.msg);
+        access.known = Some(fields);
+        visitor.visit_map(access)
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
-        byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct identifier ignored_any
+        byte_buf option unit unit_struct newtype_struct seq
+        tuple_struct map identifier ignored_any
     }
 }
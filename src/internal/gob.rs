@@ -16,19 +16,93 @@ pub(crate) enum MessageReadError {
 impl From<MessageReadError> for Error {
     fn from(err: MessageReadError) -> Error {
         match err {
-            MessageReadError::Incomplete => Error::deserialize("message incomplete"),
+            MessageReadError::Incomplete => Error::truncated("message incomplete"),
             MessageReadError::Parse(reason) => Error::deserialize(reason),
         }
     }
 }
 
+/// What decoding a struct does with fields that Go sent but the Rust type
+/// does not declare, like those added to a newer version of the Go struct.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ExtraFields {
+    /// Fails the value, naming the field.
+    Deny,
+    /// Reads past them without handing them to the Rust type, even one
+    /// with `#[serde(deny_unknown_fields)]`.
+    Skip,
+    /// Hands them to the Rust type like any other field, so that a
+    /// `#[serde(flatten)]` field such as a `HashMap<String, gob::Value>`
+    /// collects them. Derived types without one ignore them, unless they
+    /// deny unknown fields.
+    #[default]
+    CollectIntoMap,
+}
+
+/// How deeply composite values may nest before decoding is aborted,
+/// so that self-referential types cannot exhaust the stack.
+const MAX_NESTING_DEPTH: usize = 256;
+
 pub(crate) struct Message<B> {
     buf: B,
+    depth: usize,
+    budget: Option<usize>,
+    extra_fields: ExtraFields,
 }
 
 impl<B> Message<B> {
     pub fn new(buf: B) -> Message<B> {
-        Message { buf }
+        Message {
+            buf,
+            depth: 0,
+            budget: None,
+            extra_fields: ExtraFields::default(),
+        }
+    }
+
+    /// Sets what decoding structs in this message does with fields the
+    /// Rust type does not declare.
+    pub fn set_extra_fields(&mut self, policy: ExtraFields) {
+        self.extra_fields = policy;
+    }
+
+    pub fn extra_fields(&self) -> ExtraFields {
+        self.extra_fields
+    }
+
+    /// Limits the bytes that decoding this message may allocate for
+    /// sequence elements and map entries, see `charge`.
+    pub fn set_budget(&mut self, bytes: usize) {
+        self.budget = Some(bytes);
+    }
+
+    /// Accounts for `bytes` about to be allocated, failing once the budget
+    /// is spent. Elements can take a single byte on the wire but many more
+    /// in memory, so a small message could otherwise make us allocate a lot.
+    pub fn charge(&mut self, bytes: usize) -> Result<(), MessageReadError> {
+        if let Some(ref mut budget) = self.budget {
+            *budget = budget.checked_sub(bytes).ok_or_else(|| {
+                MessageReadError::Parse("decoded value exceeds the byte budget".into())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Enters a nested composite value.
+    pub fn enter_nested(&mut self) -> Result<(), MessageReadError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(MessageReadError::Parse(format!(
+                "values nested more than {} levels deep",
+                MAX_NESTING_DEPTH
+            )));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Leaves a nested composite value entered with `enter_nested`.
+    pub fn leave_nested(&mut self) {
+        self.depth -= 1;
     }
 
     pub fn get_ref(&self) -> &B {
@@ -45,6 +119,11 @@ impl<B> Message<B> {
 }
 
 impl<B: LinearBuf> Message<B> {
+    /// Reads an unsigned integer.
+    ///
+    /// Values below 128 are sent as a single byte. Larger values are sent
+    /// as the negated byte count followed by the value in big-endian order,
+    /// so `256` arrives as `fe 01 00`.
     #[inline]
     pub fn read_uint(&mut self) -> Result<u64, MessageReadError> {
         if self.buf.remaining() < 1 {
@@ -55,6 +134,12 @@ impl<B: LinearBuf> Message<B> {
             return Ok(u7_or_len as u64);
         }
         let len = !u7_or_len + 1;
+        if len > 8 {
+            return Err(MessageReadError::Parse(format!(
+                "invalid uint length {}",
+                len
+            )));
+        }
         if self.buf.remaining() < len as usize {
             return Err(MessageReadError::Incomplete);
         }
@@ -73,6 +158,8 @@ impl<B: LinearBuf> Message<B> {
         }
     }
 
+    /// Reads a float, which is sent as the byte-reversed bits of the
+    /// `f64` so that the exponent lands in the low-order bytes.
     #[inline]
     pub fn read_float(&mut self) -> Result<f64, MessageReadError> {
         let bits = self.read_uint()?;
@@ -98,13 +185,59 @@ impl<B: LinearBuf> Message<B> {
     }
 }
 
+/// A read position in a slice-backed message, saved with
+/// `Message::checkpoint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Checkpoint(u64);
+
+impl<'a> Message<Cursor<&'a [u8]>> {
+    /// Saves the current read position.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.buf.position())
+    }
+
+    /// Goes back (or forward) to a position saved with `checkpoint`.
+    pub fn restore(&mut self, checkpoint: Checkpoint) {
+        self.buf.set_position(checkpoint.0);
+    }
+
+    /// Returns the bytes read since `checkpoint`, borrowed from the
+    /// underlying slice.
+    pub fn read_since(&self, checkpoint: Checkpoint) -> &'a [u8] {
+        let bytes: &'a [u8] = self.buf.get_ref();
+        &bytes[checkpoint.0 as usize..self.buf.position() as usize]
+    }
+
+    /// Reads a length-prefixed byte string without copying it.
+    ///
+    /// The message around it has already been read in full, so a length
+    /// running past its end is malformed rather than cut off.
+    pub fn read_byte_slice(&mut self) -> Result<&'a [u8], MessageReadError> {
+        let len = self.read_uint()?;
+        if (self.buf.remaining() as u64) < len {
+            return Err(MessageReadError::Parse(format!(
+                "byte length {} exceeds the {} bytes left in the message",
+                len,
+                self.buf.remaining()
+            )));
+        }
+        let len = len as usize;
+        let start = self.checkpoint();
+        self.buf.advance(len);
+        Ok(self.read_since(start))
+    }
+}
+
 impl<B: BufMut> Message<B> {
+    /// Writes an unsigned integer using the big-endian layout described
+    /// on `read_uint`.
     #[inline]
     pub fn write_uint(&mut self, n: u64) {
         if n < 128 {
             self.buf.put_u8(n as u8);
         } else {
             let nbytes = 8 - (n.leading_zeros() / 8) as u8;
+            debug_assert!((1..=8).contains(&nbytes));
             self.buf.put_u8(!(nbytes - 1));
             self.buf.put_uint_be(n, nbytes as usize);
         }
@@ -129,6 +262,8 @@ impl<B: BufMut> Message<B> {
         self.write_uint(u);
     }
 
+    /// Writes a float using the byte-reversed layout described on
+    /// `read_float`.
     #[inline]
     pub fn write_float(&mut self, n: f64) {
         let bits = n.to_bits();
@@ -205,31 +340,57 @@ pub(crate) struct SectionHeader {
     pub(crate) payload_range: Range<usize>,
 }
 
+/// Splits off the header of the message at the start of `bytes`, or fails
+/// with `Incomplete` if the message has not been read completely yet.
+pub(crate) fn parse_section(bytes: &[u8]) -> Result<SectionHeader, MessageReadError> {
+    let mut msg = Message::new(Cursor::new(bytes));
+    //
+    //  <---> message offset
+    //        <--------------------> message length
+    // [ len | type id | payload... ]
+    //  <-------------> payload offset
+    //                  <----------> payload length
+    //
+    let msg_length = msg.read_uint()?;
+    let msg_offset = msg.get_ref().position() as usize;
+    if ((bytes.len() - msg_offset) as u64) < msg_length {
+        return Err(MessageReadError::Incomplete);
+    }
+    let msg_length = msg_length as usize;
+    let type_id = msg.read_int()?;
+    let payload_offset = msg.get_ref().position() as usize;
+    let payload_length = msg_length
+        .checked_sub(payload_offset - msg_offset)
+        .ok_or_else(|| MessageReadError::Parse("message shorter than its type id".into()))?;
+    Ok(SectionHeader {
+        type_id,
+        payload_range: Range {
+            start: payload_offset,
+            end: payload_offset + payload_length,
+        },
+    })
+}
+
 impl<Io: Read> Stream<Io> {
-    fn parse_section(&mut self, bytes: &[u8]) -> Result<SectionHeader, MessageReadError> {
-        let mut msg = Message::new(Cursor::new(bytes));
-        //
-        //  <---> message offset
-        //        <--------------------> message length
-        // [ len | type id | payload... ]
-        //  <-------------> payload offset
-        //                  <----------> payload length
-        //
-        let msg_length = msg.read_uint()? as usize;
-        let msg_offset = msg.get_ref().position() as usize;
-        if bytes.len() < msg_offset + msg_length {
-            return Err(MessageReadError::Incomplete);
+    /// Reads the length prefix of the next message into `buf` and returns
+    /// it, without waiting for the rest of the message.
+    pub fn peek_len(&mut self, buf: &mut Buffer) -> Result<Option<usize>, Error> {
+        loop {
+            match Message::new(Cursor::new(buf.bytes())).read_uint() {
+                Ok(len) => return Ok(Some(len as usize)),
+                Err(MessageReadError::Incomplete) => {
+                    let n = buf.read_from(&mut self.inner)?;
+                    if n == 0 && buf.len() == 0 {
+                        return Ok(None);
+                    } else if n == 0 {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                    }
+                }
+                Err(MessageReadError::Parse(reason)) => {
+                    return Err(Error::deserialize(reason));
+                }
+            }
         }
-        let type_id = msg.read_int()?;
-        let payload_offset = msg.get_ref().position() as usize;
-        let payload_length = msg_length as usize - (payload_offset - msg_offset);
-        Ok(SectionHeader {
-            type_id,
-            payload_range: Range {
-                start: payload_offset,
-                end: payload_offset + payload_length,
-            },
-        })
     }
 
     pub fn read_section(&mut self, buf: &mut Buffer) -> Result<Option<SectionHeader>, Error> {
@@ -240,7 +401,7 @@ impl<Io: Read> Stream<Io> {
             }
         }
         loop {
-            match self.parse_section(buf.bytes()) {
+            match parse_section(buf.bytes()) {
                 Ok(header) => {
                     return Ok(Some(header));
                 }
@@ -264,6 +425,7 @@ pub trait LinearBuf: Buf {
     fn remaining_bytes(&self) -> &[u8];
 
     fn fast_get_uint_be(&mut self, nbytes: usize) -> u64 {
+        debug_assert!((1..=8).contains(&nbytes));
         let ret = {
             let src = &self.remaining_bytes()[..nbytes];
             BigEndian::read_uint(src, nbytes)
@@ -278,3 +440,117 @@ impl<T: AsRef<[u8]>> LinearBuf for io::Cursor<T> {
         &self.get_ref().as_ref()[self.position() as usize..]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::Message;
+
+    fn encode_uint(n: u64) -> Vec<u8> {
+        let mut msg = Message::new(Vec::new());
+        msg.write_uint(n);
+        msg.into_inner()
+    }
+
+    fn encode_float(n: f64) -> Vec<u8> {
+        let mut msg = Message::new(Vec::new());
+        msg.write_float(n);
+        msg.into_inner()
+    }
+
+    #[test]
+    fn uint_single_byte() {
+        assert_eq!(encode_uint(0), &[0x00]);
+        assert_eq!(encode_uint(127), &[0x7f]);
+    }
+
+    #[test]
+    fn uint_big_endian() {
+        assert_eq!(encode_uint(128), &[0xff, 0x80]);
+        assert_eq!(encode_uint(256), &[0xfe, 0x01, 0x00]);
+        assert_eq!(encode_uint(0x010203), &[0xfd, 0x01, 0x02, 0x03]);
+
+        let mut msg = Message::new(Cursor::new(&[0xfe, 0x01, 0x00][..]));
+        assert_eq!(msg.read_uint().unwrap(), 256);
+        assert_eq!(msg.get_ref().position(), 3);
+    }
+
+    #[test]
+    fn uint_width_boundaries() {
+        let cases: &[(u64, &[u8])] = &[
+            (0x7f, &[0x7f]),
+            (0x80, &[0xff, 0x80]),
+            (0xff, &[0xff, 0xff]),
+            (0x100, &[0xfe, 0x01, 0x00]),
+            (
+                u64::MAX / 2,
+                &[0xf8, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+            (
+                u64::MAX,
+                &[0xf8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+        ];
+        for &(n, bytes) in cases {
+            assert_eq!(encode_uint(n), bytes, "encoding {:#x}", n);
+            let mut msg = Message::new(Cursor::new(bytes));
+            assert_eq!(msg.read_uint().unwrap(), n);
+            assert_eq!(msg.get_ref().position() as usize, bytes.len());
+        }
+    }
+
+    #[test]
+    fn uint_invalid_length() {
+        let mut msg = Message::new(Cursor::new(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0][..]));
+        assert!(msg.read_uint().is_err());
+    }
+
+    #[test]
+    fn float_byte_reversed() {
+        // 42.0 is 0x4045000000000000, reversed to 0x4540
+        assert_eq!(encode_float(42.0), &[0xfe, 0x45, 0x40]);
+
+        let mut msg = Message::new(Cursor::new(&[0xfe, 0x45, 0x40][..]));
+        assert_eq!(msg.read_float().unwrap(), 42.0);
+    }
+
+    #[test]
+    fn checkpoint_restore() {
+        // the string "go" followed by the uint 256
+        let bytes = [0x02, b'g', b'o', 0xfe, 0x01, 0x00];
+        let mut msg = Message::new(Cursor::new(&bytes[..]));
+
+        let start = msg.checkpoint();
+        assert_eq!(msg.read_byte_slice().unwrap(), b"go");
+        let after_str = msg.checkpoint();
+        assert_eq!(msg.read_uint().unwrap(), 256);
+        assert_eq!(msg.read_since(after_str), &[0xfe, 0x01, 0x00]);
+
+        msg.restore(start);
+        assert_eq!(msg.get_ref().position(), 0);
+        assert_eq!(msg.read_uint().unwrap(), 2);
+
+        msg.restore(after_str);
+        assert_eq!(msg.read_uint().unwrap(), 256);
+        assert_eq!(msg.read_since(start), &bytes[..]);
+    }
+
+    quickcheck! {
+        fn uint_roundtrip(n: u64) -> bool {
+            let bytes = encode_uint(n);
+            let mut msg = Message::new(Cursor::new(&bytes[..]));
+            msg.read_uint().unwrap() == n && msg.get_ref().position() as usize == bytes.len()
+        }
+    }
+
+    quickcheck! {
+        fn int_roundtrip(n: i64) -> bool {
+            let mut msg = Message::new(Vec::new());
+            msg.write_int(n);
+            let bytes = msg.into_inner();
+            let mut msg = Message::new(Cursor::new(&bytes[..]));
+            msg.read_int().unwrap() == n
+        }
+    }
+}
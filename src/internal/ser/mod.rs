@@ -2,6 +2,7 @@ use std::borrow::{Borrow, BorrowMut};
 
 use serde::ser::{self, Impossible};
 use serde::Serialize;
+use serde_gob::types::Type;
 
 use crate::internal::gob::Message;
 use crate::internal::types::TypeId;
@@ -19,11 +20,19 @@ pub(crate) use self::serialize_tuple::SerializeTupleValue;
 mod serialize_map;
 pub(crate) use self::serialize_map::SerializeMapValue;
 mod serialize_variant;
-pub(crate) use self::serialize_variant::{SerializeStructVariantValue, SerializeVariantValue};
+pub(crate) use self::serialize_variant::{
+    is_result_struct, serialize_result_variant, SerializeStructVariantValue,
+    SerializeTupleVariantValue, SerializeVariantValue,
+};
 mod serialize_empty;
 pub(crate) use self::serialize_empty::SerializeEmptyValue;
 mod serialize_wire_types;
-pub(crate) use self::serialize_wire_types::SerializeWireTypes;
+pub(crate) use self::serialize_wire_types::{SerializeWireTypes, WireTypeDef};
+
+/// Newtype struct names by which a value tells the serializer whether it
+/// is zero, for types whose emptiness doesn't follow from their encoding.
+pub(crate) const ZERO_VALUE_TOKEN: &str = "$gob::ZeroValue";
+pub(crate) const NON_ZERO_VALUE_TOKEN: &str = "$gob::NonZeroValue";
 
 pub(crate) struct SerializationOk<S> {
     pub ctx: SerializationCtx<S>,
@@ -90,6 +99,17 @@ impl<S> FieldValueSerializer<S> {
     }
 }
 
+impl<S: Borrow<Schema>> FieldValueSerializer<S> {
+    /// Like `check_type`, but also accepts a type that encodes itself, as
+    /// its values are sent as the string or bytes they encode to.
+    fn check_encoded_type(&self, got: TypeId) -> Result<(), Error> {
+        match self.ctx.schema.borrow().lookup(self.type_id) {
+            Some(ref schema_type) if matches!(**schema_type, Type::Encoded(_)) => Ok(()),
+            _ => self.check_type(got),
+        }
+    }
+}
+
 impl<S> ser::Serializer for FieldValueSerializer<S>
 where
     S: Borrow<Schema>,
@@ -100,7 +120,7 @@ where
     type SerializeSeq = SerializeSeqValue<S>;
     type SerializeTuple = SerializeTupleValue<S>;
     type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
-    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = SerializeTupleVariantValue<S>;
     type SerializeMap = SerializeMapValue<S>;
     type SerializeStruct = SerializeStructValue<S>;
     type SerializeStructVariant = SerializeStructVariantValue<S>;
@@ -186,6 +206,7 @@ where
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        // Go has no float32 encoding of its own, it widens to float64
         self.serialize_f64(v as f64)
     }
 
@@ -203,7 +224,8 @@ where
         }
         Ok(SerializationOk {
             ctx: self.ctx,
-            is_empty: false,
+            // like Go, which compares with 0, this holds for -0.0 as well
+            is_empty: v == 0.0,
         })
     }
 
@@ -220,7 +242,7 @@ where
             self.ctx.value.write_uint(0); // singleton
             self.ctx.value.write_bytes(v.as_bytes());
         } else {
-            self.check_type(TypeId::STRING)?;
+            self.check_encoded_type(TypeId::STRING)?;
             self.ctx.value.write_bytes(v.as_bytes());
         }
         Ok(SerializationOk {
@@ -231,10 +253,14 @@ where
 
     fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
         if self.type_id == TypeId::INTERFACE {
+            self.ctx.value.write_bytes("[]uint8".as_bytes());
             self.ctx.value.write_int(TypeId::BYTES.0);
+            let val_len = Message::<Vec<u8>>::get_bytes_len(v);
+            self.ctx.value.write_uint(val_len);
+            self.ctx.value.write_uint(0); // singleton
             self.ctx.value.write_bytes(v);
         } else {
-            self.check_type(TypeId::BYTES)?;
+            self.check_encoded_type(TypeId::BYTES)?;
             self.ctx.value.write_bytes(v);
         }
         Ok(SerializationOk {
@@ -281,22 +307,40 @@ where
 
     fn serialize_unit_variant(
         self,
-        _name: &'static str,
-        _variant_index: u32,
-        _variant: &'static str,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        Err(ser::Error::custom("not implemented yet"))
+        // a C-like enum, sent as the variant's name or its index depending
+        // on the Go type it was registered as; serde does not pass on an
+        // explicit discriminant, so the derive rejects those for integers
+        match self.type_id {
+            TypeId::STRING | TypeId::INTERFACE => self.serialize_str(variant),
+            TypeId::INT => self.serialize_i64(variant_index as i64),
+            TypeId::UINT => self.serialize_u64(variant_index as u64),
+            _ => Err(ser::Error::custom(format!(
+                "cannot serialize variant {}::{} as type {}",
+                name, variant, self.type_id.0
+            ))),
+        }
     }
 
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _name: &'static str,
-        _value: &T,
+        name: &'static str,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
-        Err(ser::Error::custom("not implemented yet"))
+        let mut ok = value.serialize(self)?;
+        match name {
+            ZERO_VALUE_TOKEN => ok.is_empty = true,
+            NON_ZERO_VALUE_TOKEN => ok.is_empty = false,
+            // any other newtype is a named type, sent like the type it wraps
+            _ => {}
+        }
+        Ok(ok)
     }
 
     fn serialize_newtype_variant<T: ?Sized>(
@@ -309,6 +353,10 @@ where
     where
         T: Serialize,
     {
+        let schema_type = self.ctx.schema.borrow().lookup(self.type_id);
+        if schema_type.is_some_and(|typ| is_result_struct(&typ)) {
+            return serialize_result_variant(self.ctx, self.type_id, variant_index, value);
+        }
         let ser = SerializeVariantValue::new(self.ctx, self.type_id, variant_index)?;
         ser.serialize_newtype(value)
     }
@@ -332,11 +380,12 @@ where
     fn serialize_tuple_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Err(ser::Error::custom("tuple variants not implemented yet"))
+        let ser = SerializeVariantValue::new(self.ctx, self.type_id, variant_index)?;
+        ser.serialize_tuple()
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
@@ -64,6 +64,7 @@ impl<S: Borrow<Schema>> SerializeEmptyValue<S> {
                 }
                 serde::ser::SerializeStruct::end(ser_struct)
             }
+            &Type::Encoded(_) => ser.serialize_bytes(&[]),
             _ => Err(serde::ser::Error::custom(format!(
                 "empty representation not available for type with id {}",
                 self.type_id.0
@@ -3,7 +3,7 @@ use std::borrow::Borrow;
 use serde::ser::{self, Serialize};
 use serde_gob::types::Type;
 
-use crate::error::Error;
+use crate::error::{Error, SchemaMismatch};
 use crate::internal::types::TypeId;
 use crate::schema::Schema;
 
@@ -15,6 +15,8 @@ pub(crate) struct SerializeMapValue<S> {
     len: usize,
     key: TypeId,
     value: TypeId,
+    /// Where each entry starts in the value buffer, if entries are sorted.
+    entry_offsets: Option<Vec<usize>>,
 }
 
 impl<S: Borrow<Schema>> SerializeMapValue<S> {
@@ -33,10 +35,20 @@ impl<S: Borrow<Schema>> SerializeMapValue<S> {
                     ));
                 }
             } else {
-                return Err(ser::Error::custom("schema mismatch, not a map"));
+                return Err(SchemaMismatch::UnexpectedType {
+                    type_id,
+                    expected: "a map",
+                }
+                .into());
             }
         } else {
-            return Err(ser::Error::custom("type not found"));
+            return Err(SchemaMismatch::TypeNotFound { type_id }.into());
+        };
+
+        let entry_offsets = if ctx.schema.borrow().sorted_maps {
+            Some(Vec::with_capacity(len))
+        } else {
+            None
         };
 
         Ok(SerializeMapValue {
@@ -45,6 +57,7 @@ impl<S: Borrow<Schema>> SerializeMapValue<S> {
             len,
             key,
             value,
+            entry_offsets,
         })
     }
 }
@@ -61,6 +74,9 @@ impl<S: Borrow<Schema>> ser::SerializeMap for SerializeMapValue<S> {
             self.ctx.value.write_uint(self.len as u64);
             self.needs_init = false;
         }
+        if let Some(offsets) = &mut self.entry_offsets {
+            offsets.push(self.ctx.value.get_ref().len());
+        }
         let type_id = self.key;
         self.ctx.with_borrow(|ctx| {
             let de = FieldValueSerializer { ctx, type_id };
@@ -88,9 +104,33 @@ impl<S: Borrow<Schema>> ser::SerializeMap for SerializeMapValue<S> {
             self.ctx.value.write_uint(0);
         }
 
+        if let Some(offsets) = self.entry_offsets {
+            sort_entries(self.ctx.value.get_mut(), &offsets);
+        }
+
         Ok(SerializationOk {
             ctx: self.ctx,
             is_empty,
         })
     }
 }
+
+/// Reorders the encoded entries starting at `offsets` by their bytes, which
+/// sorts them by their encoded keys, so that equal maps always encode the
+/// same regardless of their iteration order.
+fn sort_entries(buf: &mut Vec<u8>, offsets: &[usize]) {
+    let start = match offsets.first() {
+        Some(&start) => start,
+        None => return,
+    };
+    let mut entries = offsets
+        .iter()
+        .zip(offsets[1..].iter().chain(Some(&buf.len())))
+        .map(|(&begin, &end)| buf[begin..end].to_vec())
+        .collect::<Vec<_>>();
+    entries.sort();
+    buf.truncate(start);
+    for entry in entries {
+        buf.extend_from_slice(&entry);
+    }
+}
@@ -3,7 +3,7 @@ use std::borrow::Borrow;
 use serde::ser::{self, Serialize};
 use serde_gob::types::Type;
 
-use crate::error::Error;
+use crate::error::{Error, SchemaMismatch};
 use crate::internal::types::TypeId;
 use crate::schema::Schema;
 
@@ -32,10 +32,14 @@ impl<S: Borrow<Schema>> SerializeSeqValue<S> {
                     ));
                 }
             } else {
-                return Err(ser::Error::custom("schema mismatch, not a sequence"));
+                return Err(SchemaMismatch::UnexpectedType {
+                    type_id,
+                    expected: "a sequence",
+                }
+                .into());
             }
         } else {
-            return Err(ser::Error::custom("type not found"));
+            return Err(SchemaMismatch::TypeNotFound { type_id }.into());
         };
 
         Ok(SerializeSeqValue {
@@ -4,7 +4,7 @@ use owning_ref::OwningRef;
 use serde::ser::{self, Serialize};
 use serde_gob::types::{StructField, Type};
 
-use crate::error::Error;
+use crate::error::{Error, SchemaMismatch};
 use crate::internal::types::TypeId;
 use crate::schema::{Schema, SchemaType};
 
@@ -38,7 +38,7 @@ impl<S: Borrow<Schema>> SerializeStructValue<S> {
         let schema_type = if let Some(schema_type) = ctx.schema.borrow().lookup(type_id) {
             schema_type
         } else {
-            return Err(ser::Error::custom("type not found"));
+            return Err(SchemaMismatch::TypeNotFound { type_id }.into());
         };
 
         match *schema_type {
@@ -68,7 +68,11 @@ impl<S: Borrow<Schema>> SerializeStructValue<S> {
                     needs_init: true,
                 },
             }),
-            _ => Err(ser::Error::custom("schema mismatch, not a struct or map")),
+            _ => Err(SchemaMismatch::UnexpectedType {
+                type_id,
+                expected: "a struct or map",
+            }
+            .into()),
         }
     }
 
@@ -162,12 +166,9 @@ impl<S: Borrow<Schema>> ser::SerializeStruct for SerializeStructValue<S> {
                 Ok(())
             }
             StructMode::Map { .. } => {
-                // Should we decrement len? Or just ignore?
-                // If we skip a field in map mode, we end up with fewer items than declared.
-                // This might be invalid Gob.
-                // But SerializeStruct::skip_field is called for Option::None usually?
-                // No, skip_field is rarely called by derived Serialize.
-                // We'll ignore it for Map for now.
+                // Derived `Serialize` impls leave fields skipped with
+                // `skip_serializing_if` out of the length they pass to
+                // `serialize_struct`, so the declared entry count holds.
                 Ok(())
             }
         }
@@ -178,13 +179,17 @@ impl<S: Borrow<Schema>> ser::SerializeStruct for SerializeStructValue<S> {
             StructMode::Struct { .. } => {
                 self.ctx.value.write_uint(0);
             }
-            StructMode::Map { len, .. } => {
-                if len == 0 {
-                    self.ctx.value.write_uint(0);
+            StructMode::Map { needs_init, len, .. } => {
+                // no entries, e.g. because serde skipped all fields
+                if needs_init {
+                    self.ctx.value.write_uint(0); // singleton marker
+                    self.ctx.value.write_uint(len as u64);
                 }
             }
         }
 
+        // Go sends struct fields even if all of their fields are zero, so
+        // a struct never counts as empty
         Ok(SerializationOk {
             ctx: self.ctx,
             is_empty: false,
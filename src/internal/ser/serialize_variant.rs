@@ -4,7 +4,7 @@ use owning_ref::OwningRef;
 use serde::ser::{self, Serialize};
 use serde_gob::types::{EnumVariant, Type};
 
-use crate::error::Error;
+use crate::error::{Error, SchemaMismatch};
 use crate::internal::types::TypeId;
 use crate::schema::{Schema, SchemaType};
 
@@ -34,11 +34,15 @@ impl<S: Borrow<Schema>> SerializeVariantValue<S> {
                         Err(ser::Error::custom("unknown enum variant type"))
                     }
                 } else {
-                    Err(ser::Error::custom("schema mismatch, not an enum"))
+                    Err(SchemaMismatch::UnexpectedType {
+                        type_id,
+                        expected: "an enum",
+                    }
+                    .into())
                 }
             })?;
         } else {
-            return Err(ser::Error::custom("type not found"));
+            return Err(SchemaMismatch::TypeNotFound { type_id }.into());
         }
 
         Ok(SerializeVariantValue {
@@ -105,6 +109,111 @@ impl<S: Borrow<Schema>> SerializeVariantValue<S> {
             inner: SerializeStructValue::from_parts(self.ctx, struct_variant),
         })
     }
+
+    pub(crate) fn serialize_tuple(mut self) -> Result<SerializeTupleVariantValue<S>, Error> {
+        Self::write_header(&mut self.ctx, self.variant_idx)?;
+        let element_types = self.variant.clone().try_map::<_, _, Error>(|variant| {
+            if let Some(tuple_variant) = variant.as_tuple_variant() {
+                Ok(tuple_variant.element_types())
+            } else {
+                Err(ser::Error::custom(
+                    "variant type mismatch, expected tuple variant",
+                ))
+            }
+        })?;
+
+        Ok(SerializeTupleVariantValue {
+            ctx: self.ctx,
+            element_types,
+            current_idx: 0,
+        })
+    }
+}
+
+/// Whether `schema_type` is the struct a `Result` is registered as, with an
+/// `Ok` tag followed by the `Value` and the `Error` fields.
+pub(crate) fn is_result_struct(schema_type: &Type<TypeId>) -> bool {
+    match schema_type {
+        Type::Struct(struct_type) => {
+            let names: Vec<&str> = struct_type.fields().iter().map(|f| f.name()).collect();
+            names == ["Ok", "Value", "Error"]
+        }
+        _ => false,
+    }
+}
+
+/// Serializes the `Ok` or `Err` variant of a `Result` as its struct, setting
+/// the `Ok` tag and either the `Value` or the `Error`. Like any field, they
+/// are left out if zero.
+pub(crate) fn serialize_result_variant<S, T>(
+    ctx: SerializationCtx<S>,
+    type_id: TypeId,
+    variant_idx: u32,
+    value: &T,
+) -> Result<SerializationOk<S>, Error>
+where
+    S: Borrow<Schema>,
+    T: ?Sized + Serialize,
+{
+    use serde::ser::SerializeStruct;
+
+    let mut s = SerializeStructValue::new(ctx, type_id, 3)?;
+    match variant_idx {
+        0 => {
+            s.serialize_field("Ok", &true)?;
+            s.serialize_field("Value", value)?;
+            s.skip_field("Error")?;
+        }
+        1 => {
+            s.serialize_field("Ok", &false)?;
+            s.skip_field("Value")?;
+            s.serialize_field("Error", value)?;
+        }
+        _ => return Err(ser::Error::custom("unknown Result variant")),
+    }
+    s.end()
+}
+
+/// Serializes the elements of a tuple variant as the fields of a struct.
+///
+/// Unlike struct fields, elements are written even if they are empty, as
+/// they are identified by position only.
+pub(crate) struct SerializeTupleVariantValue<S> {
+    ctx: SerializationCtx<S>,
+    element_types: OwningRef<SchemaType, [TypeId]>,
+    current_idx: usize,
+}
+
+impl<S: Borrow<Schema>> ser::SerializeTupleVariant for SerializeTupleVariantValue<S> {
+    type Ok = SerializationOk<S>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let type_id = match self.element_types.get(self.current_idx) {
+            Some(type_id) => *type_id,
+            None => return Err(ser::Error::custom("too many tuple variant elements")),
+        };
+        self.ctx.value.write_uint(1);
+        self.ctx.with_borrow(|ctx| {
+            let de = FieldValueSerializer { ctx, type_id };
+            value.serialize(de)
+        })?;
+        self.current_idx += 1;
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        // terminate the element struct, then the enum struct
+        self.ctx.value.write_uint(0);
+        self.ctx.value.write_uint(0);
+        Ok(SerializationOk {
+            ctx: self.ctx,
+            is_empty: false,
+        })
+    }
 }
 
 pub(crate) struct SerializeStructVariantValue<S> {
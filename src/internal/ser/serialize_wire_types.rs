@@ -1,19 +1,26 @@
 use serde::ser::{SerializeSeq, SerializeStruct};
 use serde::{Serialize, Serializer};
-use serde_gob::types::{EnumVariant, StructField, Type};
+use serde_gob::types::{EnumVariant, Marshaler, StructField, Type};
 
 use crate::error::Error;
 use crate::schema::{Schema, TypeId};
 
 use super::{FieldValueSerializer, SerializationCtx};
 
+/// An encoded type definition, along with the ids of the types it refers to.
+pub(crate) struct WireTypeDef {
+    pub id: TypeId,
+    pub deps: Vec<TypeId>,
+    pub buffer: Vec<u8>,
+}
+
 pub(crate) struct SerializeWireTypes<'a> {
     len_pre: usize,
-    wire_types: &'a mut Vec<Vec<u8>>,
+    wire_types: &'a mut Vec<WireTypeDef>,
 }
 
 impl<'a> SerializeWireTypes<'a> {
-    pub fn new(wire_types: &'a mut Vec<Vec<u8>>) -> Self {
+    pub fn new(wire_types: &'a mut Vec<WireTypeDef>) -> Self {
         SerializeWireTypes {
             len_pre: wire_types.len(),
             wire_types,
@@ -88,11 +95,31 @@ impl<'a> SerializeWireTypes<'a> {
                     variants: enum_type.variants(),
                 },
             )?,
+            Type::Encoded(encoded_type) => {
+                let (index, variant) = match encoded_type.marshaler() {
+                    Marshaler::GobEncoder => (4, "GobEncoderT"),
+                    Marshaler::BinaryMarshaler => (5, "BinaryMarshalerT"),
+                    Marshaler::TextMarshaler => (6, "TextMarshalerT"),
+                };
+                ser.serialize_newtype_variant(
+                    "WireType",
+                    index,
+                    variant,
+                    &SerializeGobEncoderType {
+                        id,
+                        name: encoded_type.name(),
+                    },
+                )?
+            }
             _ => {
                 return Err(::serde::de::Error::custom("unsupported type"));
             }
         };
-        self.wire_types.push(ok.ctx.value.into_inner());
+        self.wire_types.push(WireTypeDef {
+            id,
+            deps: dependencies(id, ty),
+            buffer: ok.ctx.value.into_inner(),
+        });
         Ok(())
     }
 
@@ -104,31 +131,91 @@ impl<'a> SerializeWireTypes<'a> {
         if let &Type::Enum(ref enum_type) = ty {
             for variant in enum_type.variants() {
                 if let Some(struct_variant) = variant.as_struct_variant() {
-                    let mut ctx = SerializationCtx::with_schema(Schema::new());
-                    ctx.value.write_int(-next_id.0);
-                    let ok = {
-                        let ser = FieldValueSerializer {
-                            ctx,
-                            type_id: TypeId::WIRE_TYPE,
-                        };
-                        ser.serialize_newtype_variant(
-                            "WireType",
-                            2,
-                            "StructT",
-                            &SerializeStructType {
-                                id: next_id,
-                                name: struct_variant.name(),
-                                fields: struct_variant.fields(),
-                            },
-                        )?
-                    };
-                    self.wire_types.push(ok.ctx.value.into_inner());
+                    self.serialize_variant_struct(
+                        next_id,
+                        struct_variant
+                            .fields()
+                            .iter()
+                            .map(|f| *f.field_type())
+                            .collect(),
+                        &SerializeStructType {
+                            id: next_id,
+                            name: struct_variant.name(),
+                            fields: struct_variant.fields(),
+                        },
+                    )?;
+                    next_id = next_id.next();
+                } else if let Some(tuple_variant) = variant.as_tuple_variant() {
+                    self.serialize_variant_struct(
+                        next_id,
+                        tuple_variant.element_types().to_vec(),
+                        &SerializeTupleStructType {
+                            id: next_id,
+                            name: tuple_variant.name(),
+                            elements: tuple_variant.element_types(),
+                        },
+                    )?;
                     next_id = next_id.next();
                 }
             }
         }
         Ok(())
     }
+
+    fn serialize_variant_struct<T: Serialize>(
+        &mut self,
+        id: TypeId,
+        deps: Vec<TypeId>,
+        struct_type: &T,
+    ) -> Result<(), Error> {
+        let mut ctx = SerializationCtx::with_schema(Schema::new());
+        ctx.value.write_int(-id.0);
+        let ok = {
+            let ser = FieldValueSerializer {
+                ctx,
+                type_id: TypeId::WIRE_TYPE,
+            };
+            ser.serialize_newtype_variant("WireType", 2, "StructT", struct_type)?
+        };
+        self.wire_types.push(WireTypeDef {
+            id,
+            deps,
+            buffer: ok.ctx.value.into_inner(),
+        });
+        Ok(())
+    }
+}
+
+/// The ids of the types referred to by the definition of `ty`, where the
+/// struct and tuple variants of an enum take the ids following `id`.
+fn dependencies(id: TypeId, ty: &Type<TypeId>) -> Vec<TypeId> {
+    match ty {
+        Type::Struct(struct_type) => struct_type
+            .fields()
+            .iter()
+            .map(|field| *field.field_type())
+            .collect(),
+        Type::Seq(seq_type) => vec![*seq_type.element_type()],
+        Type::Map(map_type) => vec![*map_type.key_type(), *map_type.value_type()],
+        Type::Enum(enum_type) => {
+            let mut next_id = id.next();
+            let mut deps = Vec::new();
+            for variant in enum_type.variants() {
+                match variant {
+                    EnumVariant::Newtype(newtype_variant) => {
+                        deps.push(*newtype_variant.inner_type());
+                    }
+                    EnumVariant::Struct(_) | EnumVariant::Tuple(_) => {
+                        deps.push(next_id);
+                        next_id = next_id.next();
+                    }
+                    _ => {}
+                }
+            }
+            deps
+        }
+        _ => Vec::new(),
+    }
 }
 
 struct SerializeEnumStructType<'a> {
@@ -182,6 +269,13 @@ impl<'a> Serialize for SerializeEnumStructFields<'a> {
                     })?;
                     next_id = next_id.next();
                 }
+                EnumVariant::Tuple(tuple_variant) => {
+                    s.serialize_element(&SerializeStructField {
+                        name: tuple_variant.name(),
+                        id: next_id,
+                    })?;
+                    next_id = next_id.next();
+                }
                 _ => {
                     return Err(::serde::ser::Error::custom("unsupported variant type"));
                 }
@@ -255,6 +349,25 @@ impl Serialize for SerializeArrayType {
     }
 }
 
+struct SerializeGobEncoderType<'a> {
+    id: TypeId,
+    name: &'a str,
+}
+
+impl<'a> Serialize for SerializeGobEncoderType<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("GobEncoderType", 1)?;
+        s.serialize_field(
+            "common",
+            &SerializeCommonType {
+                id: self.id,
+                name: self.name,
+            },
+        )?;
+        s.end()
+    }
+}
+
 struct SerializeStructType<'a> {
     id: TypeId,
     name: &'a str,
@@ -281,6 +394,51 @@ impl<'a> Serialize for SerializeStructType<'a> {
     }
 }
 
+/// The struct a tuple variant is sent as, with its elements named
+/// `F0`, `F1` and so on.
+struct SerializeTupleStructType<'a> {
+    id: TypeId,
+    name: &'a str,
+    elements: &'a [TypeId],
+}
+
+impl<'a> Serialize for SerializeTupleStructType<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("StructType", 2)?;
+        s.serialize_field(
+            "common",
+            &SerializeCommonType {
+                id: self.id,
+                name: self.name,
+            },
+        )?;
+        s.serialize_field(
+            "Fields",
+            &SerializeTupleStructFields {
+                elements: self.elements,
+            },
+        )?;
+        s.end()
+    }
+}
+
+struct SerializeTupleStructFields<'a> {
+    elements: &'a [TypeId],
+}
+
+impl<'a> Serialize for SerializeTupleStructFields<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_seq(Some(self.elements.len()))?;
+        for (idx, id) in self.elements.iter().enumerate() {
+            s.serialize_element(&SerializeStructField {
+                name: &format!("F{}", idx),
+                id: *id,
+            })?;
+        }
+        s.end()
+    }
+}
+
 struct SerializeStructFields<'a> {
     fields: &'a [StructField<TypeId>],
 }
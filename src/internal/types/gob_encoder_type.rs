@@ -0,0 +1,34 @@
+use std::borrow::Cow;
+
+use serde_gob::types::Type;
+
+use super::{CommonType, FieldType, StructType, TypeId, WireType};
+
+/// A type that encodes itself, through `GobEncoder`, `BinaryMarshaler` or
+/// `TextMarshaler`. Its values are sent as a byte string.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct GobEncoderType {
+    pub common: CommonType,
+}
+
+lazy_static! {
+    pub static ref GOB_ENCODER_TYPE_DEF: Type<TypeId> = {
+        Type::build()
+            .struct_type("GobEncoderType", 1)
+            .field("common", TypeId::COMMON_TYPE)
+            .end()
+    };
+}
+
+pub static GOB_ENCODER_TYPE_DEF_2: WireType = {
+    WireType::Struct(StructType {
+        common: CommonType {
+            name: Cow::Borrowed("GobEncoderType"),
+            id: TypeId::GOB_ENCODER_TYPE,
+        },
+        fields: Cow::Borrowed(&[FieldType {
+            name: Cow::Borrowed("common"),
+            id: TypeId::COMMON_TYPE,
+        }]),
+    })
+};
@@ -1,7 +1,12 @@
-use std::collections::BTreeMap;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
 
 use serde_gob::types::Type;
 
+use crate::error::Error;
+use crate::go::normalize_type_name;
+use crate::internal::de::FieldValueDeserializer;
+
 mod wire_type;
 pub(crate) use self::wire_type::WireType;
 
@@ -20,11 +25,26 @@ pub(crate) use self::struct_type::{FieldType, StructType};
 mod map_type;
 pub(crate) use self::map_type::MapType;
 
+mod gob_encoder_type;
+pub(crate) use self::gob_encoder_type::GobEncoderType;
+
+mod wire_kind;
+pub use self::wire_kind::WireKind;
+
 pub use crate::schema::TypeId;
 
+/// The lowest id Go assigns to a user-defined type.
+pub(crate) const FIRST_USER_ID: i64 = 64;
+
+/// Decodes the concrete value of an interface into the Rust type it was
+/// registered with.
+pub(crate) type InterfaceDecoder =
+    for<'t, 'de> fn(FieldValueDeserializer<'t, 'de>) -> Result<Box<dyn Any>, Error>;
+
 #[derive(Debug)]
 pub struct Types {
     map: BTreeMap<TypeId, WireType>,
+    interfaces: HashMap<String, InterfaceDecoder>,
 }
 
 pub(crate) fn lookup_builtin(id: TypeId) -> Option<&'static Type<TypeId>> {
@@ -37,6 +57,7 @@ pub(crate) fn lookup_builtin(id: TypeId) -> Option<&'static Type<TypeId>> {
         TypeId::STRUCT_TYPE => Some(&self::struct_type::STRUCT_TYPE_DEF),
         TypeId::WIRE_TYPE => Some(&self::wire_type::WIRE_TYPE_DEF),
         TypeId::COMMON_TYPE => Some(&self::common_type::COMMON_TYPE_DEF),
+        TypeId::GOB_ENCODER_TYPE => Some(&self::gob_encoder_type::GOB_ENCODER_TYPE_DEF),
         _ => None,
     }
 }
@@ -51,22 +72,122 @@ pub(crate) fn lookup_builtin2(id: TypeId) -> Option<&'static WireType> {
         TypeId::STRUCT_TYPE => Some(&self::struct_type::STRUCT_TYPE_DEF_2),
         TypeId::WIRE_TYPE => Some(&self::wire_type::WIRE_TYPE_DEF_2),
         TypeId::COMMON_TYPE => Some(&self::common_type::COMMON_TYPE_DEF_2),
+        TypeId::GOB_ENCODER_TYPE => Some(&self::gob_encoder_type::GOB_ENCODER_TYPE_DEF_2),
         _ => None,
     }
 }
 
+impl Default for Types {
+    fn default() -> Types {
+        Types::new()
+    }
+}
+
 impl Types {
     pub fn new() -> Types {
         Types {
             map: BTreeMap::new(),
+            interfaces: HashMap::new(),
         }
     }
 
-    pub(crate) fn insert(&mut self, def: WireType) {
-        self.map.insert(def.common().id, def);
+    pub(crate) fn register_interface(&mut self, name: &str, decoder: InterfaceDecoder) {
+        self.interfaces.insert(normalize_type_name(name), decoder);
+    }
+
+    pub(crate) fn interface_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.interfaces.keys().map(String::as_str)
+    }
+
+    pub(crate) fn interface_decoder(&self, name: &str) -> Option<InterfaceDecoder> {
+        self.interfaces
+            .get(name)
+            .or_else(|| self.interfaces.get(&normalize_type_name(name)))
+            .cloned()
+    }
+
+    /// Adds a received type definition.
+    ///
+    /// A definition replaces any earlier one with the same id, as when a
+    /// stream is continued by a fresh Go encoder that numbers its types from
+    /// the start again. Values are decoded with the definitions in effect
+    /// when they arrive, and since a definition is a message of its own, it
+    /// never changes in the middle of a value. Ids below 64 are reserved for
+    /// the predefined types, which cannot be redefined.
+    pub(crate) fn insert(&mut self, def: WireType) -> Result<(), Error> {
+        let id = def.common().id;
+        if id.0 < FIRST_USER_ID {
+            return Err(Error::deserialize(format!(
+                "type id {} is reserved for predefined types",
+                id.0
+            )));
+        }
+        if self.map.insert(id, def).is_some() {
+            gob_trace!("type id {} redefined", id.0);
+        }
+        Ok(())
     }
 
     pub(crate) fn lookup(&self, id: TypeId) -> Option<&WireType> {
         lookup_builtin2(id).or_else(|| self.map.get(&id))
     }
+
+    /// Names the kind of type `id` refers to for error messages, or returns
+    /// `None` if it is neither predefined nor received.
+    pub(crate) fn kind(&self, id: TypeId) -> Option<&'static str> {
+        self.wire_kind(id).map(|kind| kind.as_str())
+    }
+
+    /// Returns the kind of type `id` refers to, or `None` if it is neither
+    /// predefined nor received.
+    pub fn wire_kind(&self, id: TypeId) -> Option<WireKind> {
+        let kind = match id {
+            TypeId::BOOL => WireKind::Bool,
+            TypeId::INT => WireKind::Int,
+            TypeId::UINT => WireKind::Uint,
+            TypeId::FLOAT => WireKind::Float,
+            TypeId::BYTES => WireKind::Bytes,
+            TypeId::STRING => WireKind::String,
+            TypeId::COMPLEX => WireKind::Complex,
+            TypeId::INTERFACE => WireKind::Interface,
+            _ => match self.lookup(id)? {
+                WireType::Array(_) => WireKind::Array,
+                WireType::Slice(_) => WireKind::Slice,
+                WireType::Struct(_) => WireKind::Struct,
+                WireType::Map(_) => WireKind::Map,
+                WireType::GobEncoder(_)
+                | WireType::BinaryMarshaler(_)
+                | WireType::TextMarshaler(_) => WireKind::Encoded,
+            },
+        };
+        Some(kind)
+    }
+
+    /// Returns the ids of all received type definitions in ascending order.
+    pub fn ids(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.map.keys().cloned()
+    }
+
+    /// Returns the name a type was defined with, if any.
+    pub fn name(&self, id: TypeId) -> Option<&str> {
+        let name = &self.map.get(&id)?.common().name;
+        if name.is_empty() {
+            None
+        } else {
+            Some(name)
+        }
+    }
+
+    /// Returns the names and type ids of the fields of a struct type.
+    pub fn fields(&self, id: TypeId) -> Option<impl Iterator<Item = (&str, TypeId)>> {
+        match self.map.get(&id)? {
+            WireType::Struct(struct_type) => Some(
+                struct_type
+                    .fields
+                    .iter()
+                    .map(|field| (&*field.name, field.id)),
+            ),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,60 @@
+use std::fmt;
+
+/// The kind of a gob type, which tells how its values are sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum WireKind {
+    #[serde(rename = "bool")]
+    Bool,
+    #[serde(rename = "int")]
+    Int,
+    #[serde(rename = "uint")]
+    Uint,
+    #[serde(rename = "float")]
+    Float,
+    #[serde(rename = "[]byte")]
+    Bytes,
+    #[serde(rename = "string")]
+    String,
+    #[serde(rename = "complex")]
+    Complex,
+    #[serde(rename = "interface")]
+    Interface,
+    #[serde(rename = "array")]
+    Array,
+    #[serde(rename = "slice")]
+    Slice,
+    #[serde(rename = "struct")]
+    Struct,
+    #[serde(rename = "map")]
+    Map,
+    /// A type encoding itself, sent as the bytes of its `GobEncode`,
+    /// `MarshalBinary` or `MarshalText`.
+    #[serde(rename = "encoded")]
+    Encoded,
+}
+
+impl WireKind {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            WireKind::Bool => "bool",
+            WireKind::Int => "int",
+            WireKind::Uint => "uint",
+            WireKind::Float => "float",
+            WireKind::Bytes => "[]byte",
+            WireKind::String => "string",
+            WireKind::Complex => "complex",
+            WireKind::Interface => "interface",
+            WireKind::Array => "array",
+            WireKind::Slice => "slice",
+            WireKind::Struct => "struct",
+            WireKind::Map => "map",
+            WireKind::Encoded => "encoded",
+        }
+    }
+}
+
+impl fmt::Display for WireKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -55,6 +55,27 @@ impl Buffer {
     }
 }
 
+#[cfg(any(feature = "tokio", feature = "async"))]
+impl Buffer {
+    /// Reads from `r` like `read_from`. Cancel-safe: dropping the future
+    /// before it completes leaves the buffer as it was.
+    pub async fn read_from_async<R>(&mut self, r: &mut R) -> IoResult<usize>
+    where
+        R: crate::de::AsyncSource,
+    {
+        use std::future::poll_fn;
+        use std::pin::Pin;
+
+        // read into scratch space rather than the buffer, so that nothing
+        // is left behind in it if the future is dropped while pending
+        let mut scratch = [0; 4096];
+        let len = poll_fn(|cx| Pin::new(&mut *r).poll_read_into(cx, &mut scratch)).await?;
+        self.make_space();
+        self.bytes.extend_from_slice(&scratch[..len]);
+        Ok(len)
+    }
+}
+
 impl Buf for Buffer {
     #[inline]
     fn remaining(&self) -> usize {
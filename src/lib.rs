@@ -19,17 +19,35 @@ extern crate serde;
 extern crate serde_derive;
 extern crate serde_gob;
 
+macro_rules! gob_trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "trace")]
+        ::log::trace!(target: "gob", $($arg)*);
+    };
+}
+
 mod internal;
+mod reader;
 mod schema;
+mod value;
 
+pub mod compat;
 pub mod error;
 
 pub mod de;
+pub mod go;
+#[cfg(feature = "json")]
+pub mod raw_json;
 pub mod ser;
 
-pub use error::Error;
+pub use compat::{check_compatibility, schema_diff, FieldChange, Mismatch, SchemaDiff};
+pub use error::{Error, FieldError};
 
-pub use de::{Deserializer, StreamDeserializer};
-pub use ser::StreamSerializer;
+pub use de::{looks_like_gob, read_schema, Deserializer, StreamDeserializer};
+#[cfg(any(feature = "tokio", feature = "async"))]
+pub use de::AsyncStreamDeserializer;
+pub use ser::{serialized_size, to_writer, StreamSerializer};
+pub use reader::Reader;
+pub use value::Value;
 pub use serde_gob_derive::*;
 pub use serde_gob::*;
\ No newline at end of file
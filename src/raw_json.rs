@@ -0,0 +1,59 @@
+//! Fields holding a JSON document as bytes, like a Go `json.RawMessage`,
+//! which gob sends as a `[]byte`.
+//!
+//! Use it with `#[serde(with = "gob::raw_json")]` to decode such a field
+//! straight into a `serde_json::Value`, or any other type that can be
+//! deserialized from JSON:
+//!
+//! ```
+//! # #[macro_use]
+//! # extern crate serde_derive;
+//! #[derive(Deserialize)]
+//! struct Event {
+//!     #[serde(rename = "Payload", with = "gob::raw_json")]
+//!     payload: serde_json::Value,
+//! }
+//! # fn main() {}
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+
+/// Serializes `value` as JSON, sent as a byte string.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    let json = serde_json::to_vec(value).map_err(ser::Error::custom)?;
+    serializer.serialize_bytes(&json)
+}
+
+/// Deserializes a byte string holding a JSON document.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: DeserializeOwned,
+    D: Deserializer<'de>,
+{
+    struct JsonVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: DeserializeOwned> Visitor<'de> for JsonVisitor<T> {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a byte string holding JSON")
+        }
+
+        fn visit_bytes<E>(self, bytes: &[u8]) -> Result<T, E>
+        where
+            E: de::Error,
+        {
+            serde_json::from_slice(bytes).map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_bytes(JsonVisitor(PhantomData))
+}
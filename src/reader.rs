@@ -0,0 +1,57 @@
+//! Reading gob primitives by hand.
+
+use std::io::Cursor;
+
+use crate::error::Error;
+use crate::internal::gob::Message;
+
+/// Reads the primitives a gob stream is made of from a byte slice, for
+/// parsing a stream by hand where deserializing does not fit.
+///
+/// Every read advances past what it read. Message lengths and type ids are
+/// not treated specially: a message starts with its length as a uint,
+/// followed by the type id as an int.
+pub struct Reader<'a> {
+    msg: Message<Cursor<&'a [u8]>>,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader {
+            msg: Message::new(Cursor::new(bytes)),
+        }
+    }
+
+    /// Returns how many bytes have been read.
+    pub fn position(&self) -> usize {
+        self.msg.get_ref().position() as usize
+    }
+
+    /// Returns the bytes that have not been read yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        let bytes: &'a [u8] = self.msg.get_ref().get_ref();
+        &bytes[self.position().min(bytes.len())..]
+    }
+
+    pub fn read_uint(&mut self) -> Result<u64, Error> {
+        Ok(self.msg.read_uint()?)
+    }
+
+    pub fn read_int(&mut self) -> Result<i64, Error> {
+        Ok(self.msg.read_int()?)
+    }
+
+    pub fn read_float(&mut self) -> Result<f64, Error> {
+        Ok(self.msg.read_float()?)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, Error> {
+        Ok(self.msg.read_bool()?)
+    }
+
+    /// Reads a length-prefixed byte string, such as a `[]byte` or a
+    /// `string`, borrowing it from the input.
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], Error> {
+        Ok(self.msg.read_byte_slice()?)
+    }
+}
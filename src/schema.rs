@@ -6,11 +6,12 @@ use std::sync::Arc;
 
 use owning_ref::{CloneStableAddress, StableAddress};
 use serde::{Deserialize, Deserializer};
+use serde::ser;
 use serde::{Serialize, Serializer};
 use serde_gob::types::Type;
 
 use crate::error::Error;
-use crate::internal::ser::SerializeWireTypes;
+use crate::internal::ser::{SerializeWireTypes, WireTypeDef};
 use crate::ser::{Output, OutputPart};
 
 #[derive(Clone)]
@@ -36,22 +37,53 @@ impl Deref for SchemaType {
 const CUSTOM_TYPE_ID_OFFSET: i64 = 65;
 
 pub struct Schema {
-    pending_wire_types: Vec<Vec<u8>>,
+    pending_wire_types: Vec<WireTypeDef>,
+    type_id_base: TypeId,
     next_type_id: TypeId,
     schema_types: Vec<(TypeId, Arc<Type<TypeId>>)>,
     schema_types_reverse: BTreeMap<Arc<Type<TypeId>>, TypeId>,
+    pub(crate) sorted_maps: bool,
 }
 
 impl Schema {
     pub fn new() -> Schema {
         Schema {
             pending_wire_types: Vec::new(),
+            type_id_base: TypeId(CUSTOM_TYPE_ID_OFFSET),
             next_type_id: TypeId(CUSTOM_TYPE_ID_OFFSET),
             schema_types: Vec::new(),
             schema_types_reverse: BTreeMap::new(),
+            sorted_maps: false,
         }
     }
 
+    /// Makes the ids of types registered from now on start at `base`.
+    pub(crate) fn set_type_id_base(&mut self, base: i64) -> Result<(), Error> {
+        if base < CUSTOM_TYPE_ID_OFFSET {
+            return Err(ser::Error::custom(format!(
+                "type id base {} is below the first user type id {}",
+                base, CUSTOM_TYPE_ID_OFFSET
+            )));
+        }
+        if !self.schema_types.is_empty() {
+            return Err(ser::Error::custom(
+                "cannot change the type id base after registering types",
+            ));
+        }
+        self.type_id_base = TypeId(base);
+        self.next_type_id = TypeId(base);
+        Ok(())
+    }
+
+    /// Forgets all registered types, so that they are assigned ids and
+    /// written again when next used.
+    pub(crate) fn reset(&mut self) {
+        self.pending_wire_types.clear();
+        self.next_type_id = self.type_id_base;
+        self.schema_types.clear();
+        self.schema_types_reverse.clear();
+    }
+
     #[inline]
     pub(crate) fn lookup(&self, id: TypeId) -> Option<SchemaType> {
         if id.0 < CUSTOM_TYPE_ID_OFFSET {
@@ -66,14 +98,61 @@ impl Schema {
         }
     }
 
+    /// Writes the definitions of all types registered since the last call.
+    ///
+    /// Definitions are written in the order Go sends them: a type comes
+    /// before the types its fields, elements and variants refer to, which
+    /// decoders resolve once all definitions ahead of the value are read.
     pub(crate) fn write_pending<O: Output>(&mut self, mut o: O) -> Result<(), Error> {
-        for wire_type_buffer in self.pending_wire_types.drain(..) {
-            o.serialize_part(OutputPart::new(wire_type_buffer))?;
+        let pending = std::mem::take(&mut self.pending_wire_types);
+        let order = go_order(&pending);
+        let mut buffers: Vec<_> = pending.into_iter().map(|def| Some(def.buffer)).collect();
+        for idx in order {
+            if let Some(wire_type_buffer) = buffers[idx].take() {
+                o.serialize_part(OutputPart::new(wire_type_buffer))?;
+            }
         }
         Ok(())
     }
 }
 
+/// Orders the pending definitions like Go's `sendActualType` does: each
+/// type not referred to by another pending one, in registration order,
+/// followed by the pending types it refers to, depth first.
+fn go_order(pending: &[WireTypeDef]) -> Vec<usize> {
+    fn visit(pending: &[WireTypeDef], idx: usize, seen: &mut [bool], order: &mut Vec<usize>) {
+        if seen[idx] {
+            return;
+        }
+        seen[idx] = true;
+        order.push(idx);
+        for dep in &pending[idx].deps {
+            if let Ok(dep_idx) = pending.binary_search_by_key(dep, |def| def.id) {
+                visit(pending, dep_idx, seen, order);
+            }
+        }
+    }
+
+    let mut referred = vec![false; pending.len()];
+    for def in pending {
+        for dep in &def.deps {
+            if let Ok(dep_idx) = pending.binary_search_by_key(dep, |def| def.id) {
+                referred[dep_idx] |= pending[dep_idx].id != def.id;
+            }
+        }
+    }
+    let mut seen = vec![false; pending.len()];
+    let mut order = Vec::with_capacity(pending.len());
+    for idx in (0..pending.len()).filter(|&idx| !referred[idx]) {
+        visit(pending, idx, &mut seen, &mut order);
+    }
+    // types only reachable through a cycle
+    for idx in 0..pending.len() {
+        visit(pending, idx, &mut seen, &mut order);
+    }
+    order
+}
+
 impl ::serde_gob::Schema for Schema {
     type TypeId = TypeId;
     type Error = Error;
@@ -135,6 +214,7 @@ impl TypeId {
     pub(crate) const FIELD_TYPE: TypeId = TypeId(21);
     pub(crate) const FIELD_TYPE_SLICE: TypeId = TypeId(22);
     pub(crate) const MAP_TYPE: TypeId = TypeId(23);
+    pub(crate) const GOB_ENCODER_TYPE: TypeId = TypeId(24);
 
     pub(crate) fn next(&self) -> TypeId {
         TypeId(self.0 + 1)
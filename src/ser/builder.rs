@@ -0,0 +1,62 @@
+use std::io::Write;
+
+use super::{OutputBuffer, OutputWrite, StreamSerializer};
+
+/// How the messages written by a `StreamSerializer` are framed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// Every type definition and value is sent as a length-prefixed
+    /// message, exactly as Go's `Encoder` writes them.
+    #[default]
+    Messages,
+    /// The messages written by each `serialize` call are preceded by their
+    /// total byte count as a big-endian `u32`, for transports that carry
+    /// discrete frames. Such frames have to be unwrapped before decoding.
+    LengthDelimited,
+}
+
+/// Configures a `StreamSerializer`.
+///
+/// The defaults produce the same stream as `StreamSerializer::new_with_write`
+/// and `StreamSerializer::new_with_buffer`.
+#[derive(Clone, Debug, Default)]
+pub struct StreamSerializerBuilder {
+    pub(crate) sorted_maps: bool,
+    pub(crate) capacity: usize,
+    pub(crate) framing: Framing,
+}
+
+impl StreamSerializerBuilder {
+    pub fn new() -> Self {
+        StreamSerializerBuilder::default()
+    }
+
+    /// Whether map entries are written in the order of their encoded keys
+    /// rather than in iteration order, so that equal maps, such as two
+    /// `HashMap`s with the same contents, always produce the same bytes.
+    pub fn sorted_maps(mut self, sorted_maps: bool) -> Self {
+        self.sorted_maps = sorted_maps;
+        self
+    }
+
+    /// The number of bytes to allocate up front for each value message.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Creates a stream serializer writing to `w`.
+    pub fn build<W: Write>(self, w: W) -> StreamSerializer<OutputWrite<W>> {
+        StreamSerializer::with_config(OutputWrite::new(w), self)
+    }
+
+    /// Creates a stream serializer writing into a buffer.
+    pub fn build_with_buffer(self) -> StreamSerializer<OutputBuffer> {
+        StreamSerializer::with_config(OutputBuffer::new(), self)
+    }
+}
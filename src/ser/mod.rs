@@ -1,17 +1,22 @@
 //! Serialization
 
-use std::io::Write;
+use std::io::{self, Write};
+use std::mem;
 
+use bytes::Buf;
 use serde::ser::{self, Impossible};
 use serde::Serialize;
 use serde_gob::GobSerialize;
 
 use crate::internal::ser::{FieldValueSerializer, SerializationCtx, SerializeVariantValue};
+use crate::internal::gob::Message;
 use crate::internal::utils::Bow;
 
 use crate::error::Error;
 pub use crate::schema::{Schema, TypeId};
 
+mod builder;
+pub use self::builder::{Framing, StreamSerializerBuilder};
 mod output;
 pub use self::output::{Output, OutputBuffer, OutputPart, OutputWrite};
 
@@ -25,6 +30,8 @@ mod serialize_map;
 pub use self::serialize_map::SerializeMap;
 mod serialize_struct_variant;
 pub use self::serialize_struct_variant::SerializeStructVariant;
+mod serialize_tuple_variant;
+pub use self::serialize_tuple_variant::SerializeTupleVariant;
 
 /// Serializes a single value.
 pub struct Serializer<'t, O> {
@@ -37,6 +44,15 @@ pub struct Serializer<'t, O> {
 pub struct StreamSerializer<O> {
     schema: Schema,
     out: O,
+    capacity: usize,
+    framing: Framing,
+}
+
+impl StreamSerializer<()> {
+    /// Configures a stream serializer before creating it.
+    pub fn builder() -> StreamSerializerBuilder {
+        StreamSerializerBuilder::new()
+    }
 }
 
 impl StreamSerializer<OutputBuffer> {
@@ -56,16 +72,52 @@ impl<W: Write> StreamSerializer<OutputWrite<W>> {
 
 impl<O> StreamSerializer<O> {
     fn new(out: O) -> StreamSerializer<O> {
-        let schema = Schema::new();
-        StreamSerializer { schema, out }
+        StreamSerializer::with_config(out, StreamSerializerBuilder::default())
+    }
+
+    fn with_config(out: O, config: StreamSerializerBuilder) -> StreamSerializer<O> {
+        let mut schema = Schema::new();
+        schema.sorted_maps = config.sorted_maps;
+        StreamSerializer {
+            schema,
+            out,
+            capacity: config.capacity,
+            framing: config.framing,
+        }
+    }
+
+    /// Assigns type ids starting at `base` rather than 65, the first id
+    /// for user types.
+    ///
+    /// Go numbers types per process, so the ids a Go `Encoder` sends start
+    /// higher when other types were registered or encoded before. This
+    /// reproduces its output byte for byte. Fails if `base` is below 65,
+    /// or if types were registered already.
+    pub fn with_type_id_base(mut self, base: i64) -> Result<Self, Error> {
+        self.schema.set_type_id_base(base)?;
+        Ok(self)
+    }
+
+    /// Forgets which type definitions have been written, so that they are
+    /// written again before the next values that use them.
+    ///
+    /// This allows starting a new, independent stream on the same
+    /// serializer, e.g. after switching to another file, which can then be
+    /// decoded on its own. Types are numbered from the start again.
+    pub fn reset_type_definitions(&mut self) {
+        self.schema.reset();
     }
 
     pub fn schema_mut(&mut self) -> &mut Schema {
         &mut self.schema
     }
 
+    /// Returns a serializer for a value of type `id`.
+    ///
+    /// Its messages are written unframed, whatever the configured
+    /// `Framing`.
     pub fn serializer<'a>(&'a mut self, id: TypeId) -> Result<Serializer<'a, &'a mut O>, Error> {
-        let ctx = SerializationCtx::with_schema(Bow::Borrowed(&mut self.schema));
+        let ctx = stream_ctx(&mut self.schema, self.capacity);
         Ok(Serializer {
             type_id: id,
             ctx,
@@ -88,7 +140,202 @@ impl<O> StreamSerializer<O> {
         T: Serialize,
         O: Output,
     {
-        value.serialize(self.serializer(type_id)?)
+        match self.framing {
+            Framing::Messages => value.serialize(self.serializer(type_id)?),
+            Framing::LengthDelimited => {
+                let mut frame = OutputBuffer::new();
+                value.serialize(Serializer {
+                    type_id,
+                    ctx: stream_ctx(&mut self.schema, self.capacity),
+                    out: &mut frame,
+                })?;
+                self.write_frame(frame)
+            }
+        }
+    }
+
+    /// Serializes `values` as a batch: their count as a `uint` value,
+    /// followed by each of them on its own, which a Go program reads by
+    /// decoding the count and then that many values. Like for any values
+    /// of the same type, the type definitions are only written once, ahead
+    /// of the first value.
+    ///
+    /// `StreamDeserializer::deserialize_batch` reads such a batch.
+    pub fn serialize_batch<T>(&mut self, values: &[T]) -> Result<(), Error>
+    where
+        T: GobSerialize,
+        O: Output,
+    {
+        self.serialize(&(values.len() as u64))?;
+        let type_id = T::schema_register(&mut self.schema)?;
+        for value in values {
+            self.serialize_with_type_id(type_id, value)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the type definitions of `T` that have not been written yet,
+    /// without a value, and returns the id of `T`.
+    ///
+    /// Values of `T` serialized afterwards are written without any
+    /// definitions, so this allows sending the definitions ahead of time,
+    /// e.g. at the start of a connection.
+    pub fn write_type_definition<T>(&mut self) -> Result<TypeId, Error>
+    where
+        T: GobSerialize,
+        O: Output,
+    {
+        let type_id = T::schema_register(&mut self.schema)?;
+        match self.framing {
+            Framing::Messages => self.schema.write_pending(&mut self.out)?,
+            Framing::LengthDelimited => {
+                let mut frame = OutputBuffer::new();
+                self.schema.write_pending(&mut frame)?;
+                if frame.remaining() > 0 {
+                    self.write_frame(frame)?;
+                }
+            }
+        }
+        Ok(type_id)
+    }
+
+    /// Serializes `elems` as a `[]T`, like a `Vec<T>` holding them, writing
+    /// the value message out as it is encoded instead of encoding all of
+    /// it in memory first.
+    ///
+    /// Since a message is preceded by its length, every element is encoded
+    /// twice, once to measure the message and once to write it. Only a few
+    /// kilobytes of encoded elements are held at a time though, so this
+    /// keeps the memory use of large slices down.
+    ///
+    /// The elements must serialize the same way both times. If the second
+    /// pass encodes to a different length or fails, an error is returned,
+    /// but the part of the message already written leaves the stream
+    /// unusable.
+    pub fn serialize_slice<T>(&mut self, elems: &[T]) -> Result<(), Error>
+    where
+        T: GobSerialize,
+        O: Output,
+    {
+        let type_id = <[T]>::schema_register(&mut self.schema)?;
+        let elem = T::schema_register(&mut self.schema)?;
+
+        let mut defs = OutputBuffer::new();
+        self.schema.write_pending(&mut defs)?;
+
+        let mut header = Message::new(Vec::new());
+        header.write_int(type_id.0);
+        header.write_uint(0);
+        header.write_uint(elems.len() as u64);
+
+        let mut len = header.get_ref().len();
+        encode_elements(&mut self.schema, elem, elems, |chunk| {
+            len += chunk.len();
+            chunk.clear();
+            Ok(())
+        })?;
+
+        let mut start = Vec::new();
+        let mut prefix = Message::new(Vec::new());
+        prefix.write_uint(len as u64);
+        if self.framing == Framing::LengthDelimited {
+            let frame_len = defs.remaining() + prefix.get_ref().len() + len;
+            if frame_len > u32::MAX as usize {
+                return Err(ser::Error::custom("frame exceeds u32::MAX bytes"));
+            }
+            start.extend_from_slice(&(frame_len as u32).to_be_bytes());
+        }
+        start.extend(defs.iter());
+        start.extend_from_slice(prefix.get_ref());
+        start.extend_from_slice(header.get_ref());
+        self.out.serialize_part(OutputPart::raw(start))?;
+
+        let out = &mut self.out;
+        let mut remaining = len - header.get_ref().len();
+        let changed = || ser::Error::custom("slice elements encoded differently the second time");
+        encode_elements(&mut self.schema, elem, elems, |chunk| {
+            remaining = remaining.checked_sub(chunk.len()).ok_or_else(changed)?;
+            out.serialize_part(OutputPart::raw(mem::take(chunk)))
+        })?;
+        if remaining != 0 {
+            return Err(changed());
+        }
+        Ok(())
+    }
+
+    /// Serializes `value` and appends its messages to `buf`, keeping what
+    /// `buf` already holds.
+    ///
+    /// Definitions are tracked like for values written to the stream, so
+    /// each type is only defined the first time. Appending every value
+    /// from the same serializer therefore leaves a single valid stream in
+    /// `buf`, even when it is flushed to a log file in between.
+    ///
+    /// That tracking is shared with `serialize` and `serialize_slice`
+    /// though: a type first sent to the stream is not defined again in
+    /// `buf`, and the other way around. Mixing the two splits the
+    /// definitions between both outputs, so that neither decodes on its
+    /// own; use a serializer with a single sink instead.
+    pub fn serialize_append<T>(&mut self, buf: &mut Vec<u8>, value: &T) -> Result<(), Error>
+    where
+        T: GobSerialize,
+    {
+        let type_id = T::schema_register(&mut self.schema)?;
+        let mut frame = OutputBuffer::new();
+        value.serialize(Serializer {
+            type_id,
+            ctx: stream_ctx(&mut self.schema, self.capacity),
+            out: &mut frame,
+        })?;
+        if self.framing == Framing::LengthDelimited {
+            let len = frame.remaining();
+            if len > u32::MAX as usize {
+                return Err(ser::Error::custom("frame exceeds u32::MAX bytes"));
+            }
+            buf.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        buf.extend(frame.iter());
+        Ok(())
+    }
+
+    fn write_frame(&mut self, frame: OutputBuffer) -> Result<(), Error>
+    where
+        O: Output,
+    {
+        let len = frame.remaining();
+        if len > u32::MAX as usize {
+            return Err(ser::Error::custom("frame exceeds u32::MAX bytes"));
+        }
+        let mut bytes = Vec::with_capacity(4 + len);
+        bytes.extend_from_slice(&(len as u32).to_be_bytes());
+        bytes.extend(frame.iter());
+        self.out.serialize_part(OutputPart::raw(bytes))
+    }
+
+    /// Encodes `value` without writing it to the stream, returning the type
+    /// definitions it needs separately from the value message.
+    ///
+    /// The schema bytes only hold the definitions this serializer has not
+    /// produced before, so they are empty for any further value of the same
+    /// type. Fed to a decoder ahead of the value bytes, they decode like a
+    /// regular stream.
+    pub fn encode_schema_and_value<T>(&mut self, value: &T) -> Result<(Vec<u8>, Vec<u8>), Error>
+    where
+        T: GobSerialize,
+    {
+        let type_id = T::schema_register(&mut self.schema)?;
+
+        let mut schema = OutputBuffer::new();
+        self.schema.write_pending(&mut schema)?;
+
+        let mut out = OutputBuffer::new();
+        value.serialize(Serializer {
+            type_id,
+            ctx: stream_ctx(&mut self.schema, self.capacity),
+            out: &mut out,
+        })?;
+
+        Ok((schema.iter().collect(), out.iter().collect()))
     }
 
     pub fn get_ref(&self) -> &O {
@@ -104,6 +351,81 @@ impl<O> StreamSerializer<O> {
     }
 }
 
+/// Serializes `value` into `writer` as a stream of its own.
+///
+/// The bytes are appended to whatever `writer` holds, so a `&mut Vec<u8>`
+/// is extended rather than overwritten. Each call starts a new stream with
+/// its own type definitions though; to append several values to one
+/// stream, use `StreamSerializer::serialize_append`.
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<(), Error>
+where
+    W: Write,
+    T: GobSerialize,
+{
+    StreamSerializer::new_with_write(writer).serialize(value)
+}
+
+/// Returns how many bytes `to_writer` would write for `value`, which
+/// includes the type definitions it needs, without keeping the bytes.
+pub fn serialized_size<T>(value: &T) -> Result<usize, Error>
+where
+    T: GobSerialize,
+{
+    let mut counter = ByteCounter(0);
+    to_writer(&mut counter, value)?;
+    Ok(counter.0)
+}
+
+/// Drops what is written to it, counting the bytes.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// How many bytes of encoded elements `serialize_slice` collects before
+/// passing them on.
+const SLICE_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Encodes `elems` one after the other as elements of type `elem`, handing
+/// the encoded bytes to `f` in chunks of about `SLICE_CHUNK_SIZE`. `f` has
+/// to empty the chunk.
+fn encode_elements<T, F>(
+    schema: &mut Schema,
+    elem: TypeId,
+    elems: &[T],
+    mut f: F,
+) -> Result<(), Error>
+where
+    T: Serialize,
+    F: FnMut(&mut Vec<u8>) -> Result<(), Error>,
+{
+    let mut ctx = stream_ctx(schema, SLICE_CHUNK_SIZE);
+    for value in elems {
+        ctx.with_borrow(|ctx| value.serialize(FieldValueSerializer { ctx, type_id: elem }))?;
+        if ctx.value.get_ref().len() >= SLICE_CHUNK_SIZE {
+            f(ctx.value.get_mut())?;
+        }
+    }
+    if !ctx.value.get_ref().is_empty() {
+        f(ctx.value.get_mut())?;
+    }
+    Ok(())
+}
+
+fn stream_ctx(schema: &mut Schema, capacity: usize) -> SerializationCtx<Bow<'_, Schema>> {
+    let mut ctx = SerializationCtx::with_schema(Bow::Borrowed(schema));
+    *ctx.value.get_mut() = Vec::with_capacity(capacity);
+    ctx
+}
+
 impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     type Ok = ();
     type Error = Error;
@@ -111,7 +433,7 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     type SerializeSeq = SerializeSeq<'t, O>;
     type SerializeTuple = SerializeTuple<'t, O>;
     type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
-    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = SerializeTupleVariant<'t, O>;
     type SerializeMap = SerializeMap<'t, O>;
     type SerializeStruct = SerializeStruct<'t, O>;
     type SerializeStructVariant = SerializeStructVariant<'t, O>;
@@ -255,23 +577,34 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     }
 
     fn serialize_unit_variant(
-        self,
-        _name: &'static str,
-        _variant_index: u32,
-        _variant: &'static str,
+        mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        Err(ser::Error::custom("not implemented yet"))
+        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_uint(0);
+        let mut ok = {
+            let ser = FieldValueSerializer {
+                ctx: self.ctx,
+                type_id: self.type_id,
+            };
+            ser.serialize_unit_variant(name, variant_index, variant)?
+        };
+        ok.ctx.flush(self.out)
     }
 
     fn serialize_newtype_struct<T: ?Sized>(
         self,
         _name: &'static str,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
-        Err(ser::Error::custom("not implemented yet"))
+        // top-level values are sent even if they are zero, and other
+        // newtypes like the type they wrap
+        value.serialize(self)
     }
 
     fn serialize_newtype_variant<T: ?Sized>(
@@ -316,13 +649,16 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     }
 
     fn serialize_tuple_variant(
-        self,
+        mut self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Err(ser::Error::custom("not implemented yet"))
+        self.ctx.value.write_int(self.type_id.0);
+        let inner =
+            SerializeVariantValue::new(self.ctx, self.type_id, variant_index)?.serialize_tuple()?;
+        SerializeTupleVariant::new(inner, self.out)
     }
 
     fn serialize_map(mut self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
@@ -31,6 +31,16 @@ impl OutputPart {
             pos: 0,
         }
     }
+
+    /// Creates a part that is written as is, without a length prefix.
+    pub(crate) fn raw(buf: Vec<u8>) -> Self {
+        OutputPart {
+            len_buf_len: 0,
+            len_buf: [0u8; 9],
+            buf,
+            pos: 0,
+        }
+    }
 }
 
 impl Buf for OutputPart {
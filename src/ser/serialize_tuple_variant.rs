@@ -0,0 +1,39 @@
+use serde::ser::{self, Serialize};
+
+use crate::error::Error;
+use crate::internal::ser::SerializeTupleVariantValue;
+use crate::internal::utils::Bow;
+use crate::schema::Schema;
+
+use super::output::Output;
+
+pub struct SerializeTupleVariant<'t, O> {
+    inner: SerializeTupleVariantValue<Bow<'t, Schema>>,
+    out: O,
+}
+
+impl<'t, O: Output> SerializeTupleVariant<'t, O> {
+    pub(crate) fn new(
+        inner: SerializeTupleVariantValue<Bow<'t, Schema>>,
+        out: O,
+    ) -> Result<Self, Error> {
+        Ok(SerializeTupleVariant { inner, out })
+    }
+}
+
+impl<'t, O: Output> ser::SerializeTupleVariant for SerializeTupleVariant<'t, O> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_field(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        let mut ok = self.inner.end()?;
+        ok.ctx.flush(self.out)
+    }
+}
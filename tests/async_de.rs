@@ -0,0 +1,96 @@
+#![cfg(feature = "tokio")]
+
+extern crate futures;
+extern crate gob;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_gob;
+#[macro_use]
+extern crate serde_gob_derive;
+extern crate tokio;
+
+use gob::{AsyncStreamDeserializer, StreamSerializer};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+struct Point {
+    #[serde(rename = "X")]
+    x: i64,
+    #[serde(rename = "Y")]
+    y: i64,
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread().build().unwrap()
+}
+
+#[test]
+fn values_from_duplex_pipe() {
+    let points = (1..100).map(|i| Point { x: i, y: -i }).collect::<Vec<_>>();
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for point in &points {
+            stream.serialize(point).unwrap();
+        }
+    }
+
+    runtime().block_on(async {
+        // a tiny pipe makes messages arrive in several pieces
+        let (mut writer, reader) = tokio::io::duplex(7);
+        let write = tokio::spawn(async move {
+            writer.write_all(&buffer).await.unwrap();
+        });
+
+        let mut stream = AsyncStreamDeserializer::new(reader);
+        for point in &points {
+            assert_eq!(&stream.next::<Point>().await.unwrap().unwrap(), point);
+        }
+        assert!(stream.next::<Point>().await.is_none());
+        write.await.unwrap();
+    });
+}
+
+#[test]
+fn truncated_stream() {
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+
+    runtime().block_on(async {
+        let reader = &buffer[..buffer.len() - 1];
+        let mut stream = AsyncStreamDeserializer::new(reader);
+        assert!(stream.next::<Point>().await.unwrap().is_err());
+    });
+}
+
+#[test]
+fn next_is_cancel_safe() {
+    use futures::FutureExt;
+
+    let points = (1..10).map(|i| Point { x: i, y: -i }).collect::<Vec<_>>();
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for point in &points {
+            stream.serialize(point).unwrap();
+        }
+    }
+
+    runtime().block_on(async {
+        let (mut writer, reader) = tokio::io::duplex(4096);
+        let mut stream = AsyncStreamDeserializer::new(reader);
+
+        // the first message arrives in part, and the pending read is dropped
+        writer.write_all(&buffer[..10]).await.unwrap();
+        assert!(stream.next::<Point>().now_or_never().is_none());
+
+        writer.write_all(&buffer[10..]).await.unwrap();
+        drop(writer);
+        for point in &points {
+            assert_eq!(&stream.next::<Point>().await.unwrap().unwrap(), point);
+        }
+        assert!(stream.next::<Point>().await.is_none());
+    });
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "num-bigint")]
+
+extern crate gob;
+extern crate num_bigint;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+use std::io::Cursor;
+
+use gob::go::{GobDecoder, GobEncoded, GobEncoder};
+use gob::StreamDeserializer;
+use num_bigint::BigInt;
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[allow(non_snake_case)]
+struct Account {
+    Balance: GobEncoded<BigInt>,
+    Debt: GobEncoded<BigInt>,
+    #[serde(default)]
+    Fee: Option<GobEncoded<BigInt>>,
+}
+
+#[test]
+fn struct_with_big_ints() {
+    let buffer = include_bytes!("reference/output/struct_with_big_ints.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let account = stream.deserialize::<Account>().unwrap().unwrap();
+    assert_eq!(
+        account.Balance.0,
+        "12345678901234567890123".parse::<BigInt>().unwrap()
+    );
+    assert_eq!(account.Debt.0, BigInt::from(-42));
+    assert_eq!(account.Fee, None);
+}
+
+#[test]
+fn big_int_layout() {
+    assert_eq!(BigInt::from(0).gob_encode(), [2]);
+    assert_eq!(BigInt::from(-258).gob_encode(), [3, 1, 2]);
+    assert_eq!(BigInt::gob_decode(&[2, 1, 2]).unwrap(), BigInt::from(258));
+    assert_eq!(BigInt::gob_decode(&[]).unwrap(), BigInt::from(0));
+    assert_eq!(
+        BigInt::gob_decode(&[4, 1]).unwrap_err().to_string(),
+        "big.Int encoding version 2 not supported"
+    );
+}
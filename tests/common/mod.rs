@@ -0,0 +1,127 @@
+//! Helpers shared by the integration tests.
+
+use std::io::Cursor;
+
+use gob::{Reader, StreamDeserializer, Value};
+
+/// A message of a gob stream, as found at `offset`.
+pub struct RawMessage<'a> {
+    pub offset: usize,
+    pub type_id: i64,
+    pub bytes: &'a [u8],
+}
+
+/// Splits `stream` into its messages, with negative type ids denoting
+/// type definitions.
+pub fn split_messages(stream: &[u8]) -> Result<Vec<RawMessage<'_>>, gob::Error> {
+    let mut reader = Reader::new(stream);
+    let mut messages = Vec::new();
+    while !reader.remaining().is_empty() {
+        let offset = reader.position();
+        let bytes = reader.read_bytes()?;
+        let type_id = Reader::new(bytes).read_int()?;
+        messages.push(RawMessage {
+            offset,
+            type_id,
+            bytes,
+        });
+    }
+    Ok(messages)
+}
+
+/// Decodes the `index`th value of `stream`, skipping definitions.
+fn decode_value(stream: &[u8], index: usize) -> Result<Value, gob::Error> {
+    let mut de = StreamDeserializer::new(Cursor::new(stream));
+    for _ in 0..index {
+        de.deserialize::<Value>()?;
+    }
+    de.deserialize::<Value>()?
+        .ok_or_else(|| serde::de::Error::custom("stream ended early"))
+}
+
+/// Finds where two values first differ, comparing maps and structs by key
+/// since Go sends map entries in random order.
+fn value_diff(path: &str, rust: &Value, go: &Value) -> Option<String> {
+    match (rust, go) {
+        (Value::Seq(r), Value::Seq(g)) if r.len() == g.len() => r
+            .iter()
+            .zip(g)
+            .enumerate()
+            .find_map(|(i, (r, g))| value_diff(&format!("{}[{}]", path, i), r, g)),
+        (Value::Map(r), Value::Map(g)) if r.len() == g.len() => r.iter().find_map(|(key, r)| {
+            let entry_path = match key.as_str() {
+                Some(name) => format!("{}.{}", path, name),
+                None => format!("{}[{:?}]", path, key),
+            };
+            match g.iter().find(|(k, _)| k == key) {
+                Some((_, g)) => value_diff(&entry_path, r, g),
+                None => Some(format!("{}: only sent by Rust", entry_path)),
+            }
+        }),
+        _ if rust == go => None,
+        _ => Some(format!("{}: Rust sent {:?}, Go sent {:?}", path, rust, go)),
+    }
+}
+
+fn describe_difference(rust: &[u8], go: &[u8]) -> String {
+    let (rust_msgs, go_msgs) = match (split_messages(rust), split_messages(go)) {
+        (Ok(r), Ok(g)) => (r, g),
+        (r, g) => {
+            return format!(
+                "cannot split into messages: Rust {:?}, Go {:?}",
+                r.err(),
+                g.err()
+            )
+        }
+    };
+
+    let mut values = 0;
+    for (i, (r, g)) in rust_msgs.iter().zip(&go_msgs).enumerate() {
+        if r.bytes == g.bytes {
+            if r.type_id >= 0 {
+                values += 1;
+            }
+            continue;
+        }
+        let at = format!(
+            "message {} (offset {} in Rust's stream, {} in Go's)",
+            i, r.offset, g.offset
+        );
+        if r.type_id != g.type_id {
+            return format!("{}: Rust sent type id {}, Go sent {}", at, r.type_id, g.type_id);
+        }
+        if r.type_id < 0 {
+            let byte = r
+                .bytes
+                .iter()
+                .zip(g.bytes)
+                .position(|(r, g)| r != g)
+                .unwrap_or(r.bytes.len().min(g.bytes.len()));
+            return format!(
+                "{}: definitions of type {} differ from byte {}:\n  Rust: {:?}\n  Go:   {:?}",
+                at, -r.type_id, byte, r.bytes, g.bytes
+            );
+        }
+        return match (decode_value(rust, values), decode_value(go, values)) {
+            (Ok(r), Ok(g)) => match value_diff("value", &r, &g) {
+                Some(diff) => format!("{}: {}", at, diff),
+                None => format!("{}: equal values encoded differently, e.g. map order", at),
+            },
+            (r, g) => format!("{}: cannot decode: Rust {:?}, Go {:?}", at, r.err(), g.err()),
+        };
+    }
+    format!(
+        "Rust sent {} messages, Go sent {}",
+        rust_msgs.len(),
+        go_msgs.len()
+    )
+}
+
+/// Asserts that Rust encoded the same stream as Go, describing the first
+/// difference in terms of messages, type ids and decoded values rather than
+/// as a byte dump.
+pub fn assert_gob_eq(rust: &[u8], go: &[u8]) {
+    if rust != go {
+        panic!("gob streams differ: {}", describe_difference(rust, go));
+    }
+}
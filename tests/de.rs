@@ -4,14 +4,23 @@ extern crate serde;
 extern crate serde_bytes;
 #[macro_use]
 extern crate serde_derive;
+extern crate serde_gob;
+#[macro_use]
+extern crate serde_gob_derive;
 #[macro_use]
 extern crate quickcheck;
 
-use std::collections::HashMap;
-use std::io::Cursor;
-
-use gob::{error::ErrorKind, Deserializer, StreamDeserializer};
-use partial_io::{GenWouldBlock, PartialRead, PartialWithErrors};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Cursor, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use gob::{
+    de::{ExtraFields, WireKind},
+    error::ErrorKind,
+    go::{normalize_type_name, GobDecoder, GobEncoded, GobError, IPNet, Interface, IP},
+    Deserializer, Reader, StreamDeserializer, StreamSerializer, Value,
+};
+use partial_io::{GenWouldBlock, PartialOp, PartialRead, PartialWithErrors};
 use serde::Deserialize;
 use serde_bytes::{ByteBuf, Bytes};
 
@@ -207,11 +216,27 @@ fn char_unicode() {
     assert_eq!(decoded, '語');
 }
 
+#[test]
+fn char_surrogate() {
+    // 0xD800
+    let deserializer = Deserializer::from_slice(&[6, 4, 0, 253, 1, 176, 0]);
+    let err = char::deserialize(deserializer).unwrap_err();
+    assert_eq!(err.to_string(), "deserialize error: invalid char code 55296");
+}
+
+#[test]
+fn char_out_of_range() {
+    // 0x1_0000_0061, which used to be truncated to 'a'
+    let deserializer = Deserializer::from_slice(&[8, 4, 0, 251, 2, 0, 0, 0, 194]);
+    let err = char::deserialize(deserializer).unwrap_err();
+    assert_eq!(err.to_string(), "deserialize error: invalid char code 4294967393");
+}
+
 #[test]
 fn bytes_empty() {
     let deserializer = Deserializer::from_slice(&[3, 10, 0, 0]);
     let decoded = Bytes::deserialize(deserializer).unwrap();
-    assert_eq!(&*decoded, &[]);
+    assert!(decoded.is_empty());
 }
 
 #[test]
@@ -225,7 +250,7 @@ fn bytes_non_empty() {
 fn bytebuf_empty() {
     let deserializer = Deserializer::from_slice(&[3, 10, 0, 0]);
     let decoded = ByteBuf::deserialize(deserializer).unwrap();
-    assert_eq!(&*decoded, &[]);
+    assert!(decoded.is_empty());
 }
 
 #[test]
@@ -268,7 +293,7 @@ fn vec_of_bool_from_empty_slice() {
     let deserializer =
         Deserializer::from_slice(include_bytes!("reference/output/slice_of_bool_empty.gob"));
     let decoded = <Vec<bool>>::deserialize(deserializer).unwrap();
-    assert_eq!(decoded, &[]);
+    assert!(decoded.is_empty());
 }
 
 #[test]
@@ -276,7 +301,7 @@ fn vec_of_bool_from_empty_array() {
     let deserializer =
         Deserializer::from_slice(include_bytes!("reference/output/array_of_bool_empty.gob"));
     let decoded = <Vec<bool>>::deserialize(deserializer).unwrap();
-    assert_eq!(decoded, &[]);
+    assert!(decoded.is_empty());
 }
 
 #[test]
@@ -305,10 +330,10 @@ fn vec_of_bool_from_empty_slice_twice() {
     let mut stream = StreamDeserializer::new(cursor);
 
     let decoded1 = stream.deserialize::<Vec<bool>>().unwrap().unwrap();
-    assert_eq!(decoded1, &[]);
+    assert!(decoded1.is_empty());
 
     let decoded2 = stream.deserialize::<Vec<bool>>().unwrap().unwrap();
-    assert_eq!(decoded2, &[]);
+    assert!(decoded2.is_empty());
 }
 
 #[test]
@@ -325,6 +350,85 @@ fn vec_of_bool_from_non_empty_slice_twice() {
     assert_eq!(decoded2, &[false, true]);
 }
 
+#[test]
+fn vec_of_vec_from_slice_of_slices() {
+    let buffer = include_bytes!("reference/output/slice_of_slices.gob");
+    let expected = vec![vec![1i64, 2], vec![], vec![3]];
+
+    let decoded = Vec::<Vec<i64>>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(decoded, expected);
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&expected)
+        .unwrap();
+    let decoded = Vec::<Vec<i64>>::deserialize(Deserializer::from_slice(&buffer)).unwrap();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn vec_from_slice_of_pointers() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[allow(non_snake_case)]
+    struct User {
+        #[serde(default)]
+        Name: String,
+        #[serde(default)]
+        Age: i64,
+    }
+
+    // a `[]*User`, which is sent like a `[]User`; Go refuses to encode nil
+    // elements, so there are none to decode into `None`
+    let buffer = include_bytes!("reference/output/slice_of_pointers.gob");
+    let expected = vec![
+        User {
+            Name: "alice".into(),
+            Age: 30,
+        },
+        User {
+            Name: "bob".into(),
+            Age: 0,
+        },
+        User {
+            Name: "".into(),
+            Age: 0,
+        },
+    ];
+
+    let decoded = Vec::<User>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(decoded, expected);
+    let decoded = Vec::<Option<User>>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(decoded, expected.into_iter().map(Some).collect::<Vec<_>>());
+}
+
+#[test]
+fn struct_field_missing_from_definition() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Session {
+        #[serde(rename = "User")]
+        user: String,
+        // unexported in Go, so never part of the definition
+        #[serde(default)]
+        token: String,
+        #[serde(rename = "Expiry")]
+        expiry: i64,
+        #[serde(default)]
+        refreshed: Option<i64>,
+    }
+
+    let buffer = include_bytes!("reference/output/struct_with_unexported_field.gob");
+    let decoded = Session::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(
+        decoded,
+        Session {
+            user: "gopher".into(),
+            token: String::new(),
+            expiry: 1700000000,
+            refreshed: None,
+        }
+    );
+}
+
 #[test]
 fn map_empty() {
     let deserializer = Deserializer::from_slice(include_bytes!("reference/output/map_empty.gob"));
@@ -342,6 +446,383 @@ fn map_non_empty() {
     assert_eq!(decoded["bar"], false);
 }
 
+#[test]
+fn map_string_string() {
+    let mut stream = StreamDeserializer::new(Cursor::new(
+        include_bytes!("reference/output/map_string_string.gob").as_ref(),
+    ));
+    let config = stream.deserialize::<HashMap<String, String>>().unwrap().unwrap();
+    assert_eq!(
+        config,
+        HashMap::from([
+            ("APP_NAME".to_string(), "Gitea".to_string()),
+            ("RUN_USER".to_string(), "git".to_string()),
+        ])
+    );
+    let empty_strings = stream.deserialize::<HashMap<String, String>>().unwrap().unwrap();
+    assert_eq!(
+        empty_strings,
+        HashMap::from([
+            ("".to_string(), "root".to_string()),
+            ("ROOT_URL".to_string(), "".to_string()),
+        ])
+    );
+    let empty = stream.deserialize::<HashMap<String, String>>().unwrap().unwrap();
+    assert!(empty.is_empty());
+    assert!(stream.deserialize::<HashMap<String, String>>().unwrap().is_none());
+}
+
+#[test]
+fn struct_with_nested_collections() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Nested {
+        #[serde(rename = "Groups")]
+        groups: Vec<HashMap<String, Vec<i64>>>,
+        #[serde(rename = "Pair")]
+        pair: [i64; 2],
+    }
+
+    // Go sends the definitions outermost first, so the struct and the
+    // outer slice refer to types that are only defined after them
+    let buffer = include_bytes!("reference/output/struct_with_nested_collections.gob");
+    let types = gob::read_schema(&mut Cursor::new(buffer.as_ref())).unwrap();
+    let names = types.ids().map(|id| types.name(id)).collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        &[
+            Some("Nested"),
+            Some("[]int"),
+            Some("map[string][]int"),
+            Some("[]map[string][]int"),
+            Some("[2]int"),
+        ]
+    );
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    assert_eq!(
+        stream.deserialize::<Nested>().unwrap().unwrap(),
+        Nested {
+            groups: vec![
+                HashMap::from([("a".to_string(), vec![1, 2]), ("b".to_string(), vec![])]),
+                HashMap::new(),
+            ],
+            pair: [3, -4],
+        }
+    );
+    assert!(stream.deserialize::<Nested>().unwrap().is_none());
+}
+
+#[test]
+fn struct_with_float_widths() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Widths<S, D> {
+        #[serde(rename = "Single")]
+        single: S,
+        #[serde(rename = "Double")]
+        double: D,
+    }
+
+    // Go sends its float32 as the float64 of the same value, and a Rust
+    // field of either width takes either
+    let buffer = include_bytes!("reference/output/struct_with_float_widths.gob");
+    let decoded = <Widths<f32, f64>>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(decoded, Widths { single: 0.1, double: 0.1 });
+    let decoded = <Widths<f64, f32>>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(decoded, Widths { single: 0.1f32 as f64, double: 0.1 });
+    let decoded = <Widths<f32, f32>>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(decoded, Widths { single: 0.1, double: 0.1 });
+    let decoded = <Widths<f64, f64>>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(decoded, Widths { single: 0.1f32 as f64, double: 0.1 });
+}
+
+#[test]
+fn struct_with_complex_field() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Signal {
+        #[serde(rename = "C", default)]
+        c: (f64, f64),
+        #[serde(rename = "F", default)]
+        f: f64,
+    }
+
+    // Go omits a complex field that is zero, and sends the real and the
+    // imaginary part otherwise, even if one of them is zero
+    let mut stream = StreamDeserializer::new(Cursor::new(
+        include_bytes!("reference/output/struct_with_complex_field.gob").as_ref(),
+    ));
+    assert_eq!(
+        stream.deserialize::<Signal>().unwrap().unwrap(),
+        Signal { c: (0.0, 0.0), f: 1.0 }
+    );
+    assert_eq!(
+        stream.deserialize::<Signal>().unwrap().unwrap(),
+        Signal { c: (1.0, -2.0), f: 0.0 }
+    );
+    assert_eq!(
+        stream.deserialize::<Signal>().unwrap().unwrap(),
+        Signal { c: (0.0, 0.5), f: 0.0 }
+    );
+}
+
+#[test]
+fn struct_fields_matched_by_wire_name() {
+    // declared in another order than in Go, which does not matter
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Response {
+        #[serde(rename = "URL")]
+        url: String,
+        #[serde(rename = "UserID")]
+        user_id: i64,
+        #[serde(rename = "HTTPStatus")]
+        http_status: i64,
+    }
+
+    // the names from the Go struct tags, or a guess at the Go field names,
+    // do not match any field that was sent
+    #[derive(Deserialize, Debug, Default, PartialEq)]
+    #[serde(default)]
+    struct Guessed {
+        user_id: i64,
+        #[serde(rename = "UserId")]
+        user_id_camel: i64,
+        #[serde(rename = "HttpStatus")]
+        http_status: i64,
+        #[serde(rename = "Url")]
+        url: String,
+    }
+
+    let buffer = include_bytes!("reference/output/struct_with_tagged_fields.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    assert_eq!(
+        stream.deserialize::<Response>().unwrap().unwrap(),
+        Response {
+            url: "https://example.com/u/42".to_string(),
+            user_id: 42,
+            http_status: 404,
+        }
+    );
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    assert_eq!(
+        stream.deserialize::<Guessed>().unwrap().unwrap(),
+        Guessed::default()
+    );
+}
+
+#[test]
+fn nested_config_map() {
+    #[derive(Deserialize, Debug, Default, PartialEq)]
+    #[serde(default)]
+    struct Config {
+        #[serde(rename = "Host")]
+        host: String,
+        #[serde(rename = "Port")]
+        port: i64,
+        #[serde(rename = "Tags")]
+        tags: Vec<String>,
+        #[serde(rename = "Limits")]
+        limits: HashMap<String, i64>,
+    }
+
+    // a map of slices of maps of structs, whose fields are a slice and a
+    // map again, with empty and zero values at every level
+    let mut stream = StreamDeserializer::new(Cursor::new(
+        include_bytes!("reference/output/nested_config_map.gob").as_ref(),
+    ));
+    let decoded = stream
+        .deserialize::<HashMap<String, Vec<HashMap<String, Config>>>>()
+        .unwrap()
+        .unwrap();
+
+    let web = Config {
+        host: "web1".to_string(),
+        port: 443,
+        tags: vec!["a".to_string(), "b".to_string()],
+        limits: HashMap::from([("rps".to_string(), 100)]),
+    };
+    let db = Config {
+        host: "db1".to_string(),
+        port: 5432,
+        ..Config::default()
+    };
+    assert_eq!(
+        decoded,
+        HashMap::from([
+            (
+                "prod".to_string(),
+                vec![
+                    HashMap::from([("web".to_string(), web), ("db".to_string(), db)]),
+                    HashMap::new(),
+                ]
+            ),
+            ("dev".to_string(), vec![]),
+            (
+                "staging".to_string(),
+                vec![HashMap::from([("zero".to_string(), Config::default())])]
+            ),
+        ])
+    );
+    assert!(stream.deserialize::<Value>().unwrap().is_none());
+
+    // and dynamically
+    let mut stream = StreamDeserializer::new(Cursor::new(
+        include_bytes!("reference/output/nested_config_map.gob").as_ref(),
+    ));
+    let value = stream.deserialize::<Value>().unwrap().unwrap();
+    let prod = match value.get("prod") {
+        Some(Value::Seq(prod)) => prod,
+        other => panic!("unexpected {:?}", other),
+    };
+    let web = prod[0].get("web").unwrap();
+    assert_eq!(web.get("Host").and_then(Value::as_str), Some("web1"));
+    assert_eq!(
+        web.get("Limits").and_then(|limits| limits.get("rps")),
+        Some(&Value::Int(100))
+    );
+    assert_eq!(prod[1], Value::Map(vec![]));
+}
+
+#[test]
+fn map_with_bool_value_out_of_range() {
+    // map[string]bool{"a": true, "b": 2}, with a bool that is neither 0 nor 1
+    let bytes = [
+        14, 255, 129, 4, 1, 2, 255, 130, 0, 1, 12, 1, 2, 0, 0, 10, 255, 130, 0, 2, 1, 97, 1, 1, 98,
+        2,
+    ];
+    let deserializer = Deserializer::from_slice(&bytes);
+    assert!(<HashMap<String, bool>>::deserialize(deserializer).is_err());
+
+    let mut valid = bytes;
+    valid[25] = 0;
+    let deserializer = Deserializer::from_slice(&valid);
+    let decoded = <HashMap<String, bool>>::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, HashMap::from([("a".to_string(), true), ("b".to_string(), false)]));
+}
+
+#[test]
+fn map_into_struct() {
+    #[derive(Deserialize, Debug)]
+    struct Flags {
+        #[serde(rename = "foo")]
+        _foo: bool,
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(
+        include_bytes!("reference/output/map_non_empty.gob").as_ref(),
+    ));
+    let err = stream.deserialize::<Flags>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "deserialize error: cannot decode map type 65 into struct Flags"
+    );
+}
+
+#[test]
+fn lenient_decode_skips_corrupt_field() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[allow(non_snake_case)]
+    struct User {
+        Name: String,
+        Email: String,
+        Age: i64,
+    }
+
+    // User{Name: "gopher", Email: "\xff\xfe", Age: 42}, with an email that
+    // is not valid UTF-8
+    let bytes = [
+        45, 255, 129, 3, 1, 1, 4, 85, 115, 101, 114, 1, 255, 130, 0, 1, 3, 1, 4, 78, 97, 109, 101,
+        1, 12, 0, 1, 5, 69, 109, 97, 105, 108, 1, 12, 0, 1, 3, 65, 103, 101, 1, 4, 0, 0, 0, 17,
+        255, 130, 1, 6, 103, 111, 112, 104, 101, 114, 1, 2, 255, 254, 1, 84, 0,
+    ];
+    let mut stream = StreamDeserializer::new(Cursor::new(&bytes[..]));
+    assert!(stream.deserialize::<User>().is_err());
+
+    let mut stream = StreamDeserializer::new(Cursor::new(&bytes[..]));
+    let (user, skipped) = stream.deserialize_lenient::<User>().unwrap().unwrap();
+    assert_eq!(
+        user,
+        User {
+            Name: "gopher".to_string(),
+            Email: String::new(),
+            Age: 42,
+        }
+    );
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].field, "Email");
+    assert_eq!(skipped[0].error.kind(), ErrorKind::Deserialize);
+    assert!(stream.deserialize_lenient::<User>().unwrap().is_none());
+}
+
+#[test]
+fn value_of_undefined_type() {
+    // a value of type 72, which was never defined
+    let mut stream = StreamDeserializer::new(Cursor::new(&[4, 255, 144, 0, 0][..]));
+    let err = stream.deserialize::<i64>().unwrap_err();
+    assert_eq!(err.to_string(), "deserialize error: value of undefined type 72");
+}
+
+#[test]
+fn map_with_fewer_entries_than_declared() {
+    // a map[int64]string declaring five entries, with the message cut off
+    // after the second
+    let deserializer = Deserializer::from_slice(&[
+        14, 255, 129, 4, 1, 2, 255, 130, 0, 1, 4, 1, 12, 0, 0, 12, 255, 130, 0, 5, 2, 2, 118, 49,
+        4, 2, 118, 50,
+    ]);
+    let err = <BTreeMap<i64, String>>::deserialize(deserializer).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Truncated);
+    assert!(err.to_string().contains("entry 2 of 5"), "{}", err);
+}
+
+#[test]
+fn truncated_and_malformed_values() {
+    // the definition of map[int64]string, as in the test above
+    let def = [14, 255, 129, 4, 1, 2, 255, 130, 0, 1, 4, 1, 12, 0, 0];
+
+    // a single entry whose key, 256, is cut off after its first byte
+    let mut input = def.to_vec();
+    input.extend_from_slice(&[6, 255, 130, 0, 1, 254, 2]);
+    let err = <BTreeMap<i64, String>>::deserialize(Deserializer::from_slice(&input)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Truncated);
+
+    // a single entry whose value claims 9 bytes, with only "v1" following
+    let mut input = def.to_vec();
+    input.extend_from_slice(&[8, 255, 130, 0, 1, 4, 9, 118, 49]);
+    let err = <BTreeMap<i64, String>>::deserialize(Deserializer::from_slice(&input)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Deserialize);
+    assert!(err.to_string().contains("byte length 9"), "{}", err);
+}
+
+#[test]
+fn btree_map_with_int_keys() {
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/map_int64_string.gob"));
+    let decoded = <BTreeMap<i64, String>>::deserialize(deserializer).unwrap();
+    // keys arrive in Go's (unspecified) map order but come out sorted
+    assert_eq!(
+        decoded.into_iter().collect::<Vec<_>>(),
+        vec![
+            (-1, "minus one".to_owned()),
+            (2, "two".to_owned()),
+            (3, "three".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn btree_map_with_duplicate_keys() {
+    let deserializer = Deserializer::from_slice(&[
+        // map[int64]string definition
+        14, 255, 129, 4, 1, 2, 255, 130, 0, 1, 4, 1, 12, 0, 0,
+        // {1: "a", 1: "b"}
+        10, 255, 130, 0, 2, 2, 1, 97, 2, 1, 98,
+    ]);
+    let decoded = <BTreeMap<i64, String>>::deserialize(deserializer).unwrap();
+    // like Go's decoder, the last occurrence of a key wins
+    assert_eq!(decoded.len(), 1);
+    assert_eq!(decoded[&1], "b");
+}
+
 #[test]
 fn complex_64() {
     let deserializer = Deserializer::from_slice(&[6, 14, 0, 254, 240, 63, 64]);
@@ -358,6 +839,69 @@ fn complex_128() {
     assert_eq!(decoded.1, 2f64);
 }
 
+#[test]
+fn definition_and_value_in_one_read() {
+    #[derive(Deserialize)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    /// Hands out all of its bytes in the first read, counting the reads.
+    struct CountingRead<'a> {
+        bytes: &'a [u8],
+        reads: usize,
+    }
+
+    impl<'a> Read for CountingRead<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            self.bytes.read(buf)
+        }
+    }
+
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+    // the definition message, followed by the value message
+    assert_eq!(buffer[1], 0xff);
+    assert!((buffer[0] as usize) + 1 < buffer.len());
+
+    let mut stream = StreamDeserializer::new(CountingRead {
+        bytes: buffer,
+        reads: 0,
+    });
+    let decoded = stream.deserialize::<Point>().unwrap().unwrap();
+    assert_eq!((decoded.x, decoded.y), (22, 33));
+    assert_eq!(stream.get_ref().reads, 1);
+    assert!(stream.deserialize::<Point>().unwrap().is_none());
+}
+
+#[test]
+fn struct_fields_defined_in_other_order() {
+    // Go defines the fields as Age, Email, Name
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Person {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Email")]
+        email: String,
+        #[serde(rename = "Age")]
+        age: i64,
+    }
+
+    let buffer = include_bytes!("reference/output/struct_fields_reversed.gob");
+    let decoded = Person::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(
+        decoded,
+        Person {
+            name: "Ann".to_owned(),
+            email: "ann@example.com".to_owned(),
+            age: 30,
+        }
+    );
+}
+
 #[test]
 fn point_struct() {
     #[derive(Deserialize)]
@@ -376,120 +920,1592 @@ fn point_struct() {
     assert_eq!(decoded.y, 33);
 }
 
+#[derive(Deserialize, Debug, PartialEq)]
+struct ArrayPoint {
+    #[serde(rename = "X", default)]
+    x: i64,
+    #[serde(rename = "Y", default)]
+    y: i64,
+}
+
 #[test]
-fn unit_struct() {
+fn sha256_array() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[allow(non_snake_case)]
+    struct Blob {
+        Name: String,
+        Sum: [u8; 32],
+    }
+
+    let sum = [
+        0x2c, 0xf2, 0x4d, 0xba, 0x5f, 0xb0, 0xa3, 0x0e, 0x26, 0xe8, 0x3b, 0x2a, 0xc5, 0xb9, 0xe2,
+        0x9e, 0x1b, 0x16, 0x1e, 0x5c, 0x1f, 0xa7, 0x42, 0x5e, 0x73, 0x04, 0x33, 0x62, 0x93, 0x8b,
+        0x98, 0x24,
+    ];
+    let mut stream = StreamDeserializer::new(Cursor::new(
+        include_bytes!("reference/output/sha256_array.gob").as_ref(),
+    ));
+    assert_eq!(stream.deserialize::<[u8; 32]>().unwrap(), Some(sum));
+    let blob = stream.deserialize::<Blob>().unwrap().unwrap();
+    assert_eq!(
+        blob,
+        Blob {
+            Name: "hello.txt".to_string(),
+            Sum: sum,
+        }
+    );
+
+    // through `deserialize_bytes`, and with a length that does not match
+    let buffer = include_bytes!("reference/output/sha256_array.gob");
+    let deserializer = Deserializer::from_slice(buffer);
+    assert_eq!(ByteBuf::deserialize(deserializer).unwrap(), ByteBuf::from(sum.to_vec()));
+    let deserializer = Deserializer::from_slice(buffer);
+    assert!(<[u8; 16]>::deserialize(deserializer).is_err());
+}
+
+#[test]
+fn array_of_structs() {
     #[derive(Deserialize)]
-    struct EmptyStruct {};
+    struct Path {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Points")]
+        points: [ArrayPoint; 3],
+    }
 
     let deserializer =
-        Deserializer::from_slice(include_bytes!("reference/output/empty_struct.gob"));
-
-    EmptyStruct::deserialize(deserializer).unwrap();
+        Deserializer::from_slice(include_bytes!("reference/output/array_of_structs.gob"));
+
+    let decoded = Path::deserialize(deserializer).unwrap();
+    assert_eq!(decoded.name, "zigzag");
+    assert_eq!(
+        decoded.points,
+        [
+            ArrayPoint { x: 1, y: 2 },
+            ArrayPoint { x: 0, y: 0 },
+            ArrayPoint { x: -3, y: 4 },
+        ]
+    );
 }
 
 #[test]
-fn enum_with_newtype_variants_and_external_tags() {
-    #[derive(Deserialize, Debug, PartialEq, Eq)]
-    enum Enum {
-        #[serde(rename = "Var1")]
-        V1(bool),
-        #[serde(rename = "Var2")]
-        V2(i64),
-        #[serde(rename = "Var3")]
-        V3(String),
+fn array_of_structs_to_vec() {
+    #[derive(Deserialize)]
+    struct Path {
+        #[serde(rename = "Points")]
+        points: Vec<ArrayPoint>,
     }
 
-    let deserializer = Deserializer::from_slice(include_bytes!(
-        "reference/output/enum_with_newtype_variants.gob"
-    ));
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/array_of_structs.gob"));
 
-    let decoded = Enum::deserialize(deserializer).unwrap();
-    assert_eq!(decoded, Enum::V2(42));
+    let decoded = Path::deserialize(deserializer).unwrap();
+    assert_eq!(decoded.points.len(), 3);
+    assert_eq!(decoded.points[2], ArrayPoint { x: -3, y: 4 });
 }
 
 #[test]
-fn enum_with_struct_variants_and_external_tags() {
-    #[derive(Deserialize, Debug, PartialEq, Eq)]
-    enum Enum {
-        V1 {
-            #[serde(rename = "Foo")]
-            foo: bool,
-        },
-        V2 {
-            #[serde(rename = "Bar")]
-            bar: i64,
-            #[serde(rename = "Baz")]
-            baz: u64,
-        },
-        V3 {
-            #[serde(rename = "Quux")]
-            quux: String,
-        },
+fn array_of_structs_len_mismatch() {
+    #[derive(Deserialize, Debug)]
+    struct Path {
+        #[serde(rename = "Points")]
+        _points: [ArrayPoint; 2],
     }
 
-    let deserializer = Deserializer::from_slice(include_bytes!(
-        "reference/output/enum_with_struct_variants.gob"
-    ));
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/array_of_structs.gob"));
 
-    let decoded = Enum::deserialize(deserializer).unwrap();
-    assert_eq!(decoded, Enum::V2 { bar: 42, baz: 1234 });
+    let err = Path::deserialize(deserializer).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "deserialize error: sequence len mismatch (expected 2, got 3)"
+    );
+}
+
+#[derive(Deserialize)]
+struct ConfigWithMap {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Counts", default)]
+    counts: HashMap<String, i64>,
 }
 
 #[test]
-fn unit_from_any() {
-    let buffer = include_bytes!("reference/output/non_empty_values.gob");
+fn struct_with_map_field() {
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/struct_with_map_field.gob"));
+
+    let decoded = ConfigWithMap::deserialize(deserializer).unwrap();
+    assert_eq!(decoded.name, "foo");
+    assert_eq!(decoded.counts.len(), 2);
+    assert_eq!(decoded.counts["a"], 1);
+    assert_eq!(decoded.counts["b"], 2);
+}
+
+#[test]
+fn struct_with_empty_map_field() {
+    let buffer = include_bytes!("reference/output/struct_with_empty_map_field.gob");
 
     let cursor = Cursor::new(buffer.as_ref());
     let mut stream = StreamDeserializer::new(cursor);
 
-    for _ in 0..7 {
-        let () = stream.deserialize::<()>().unwrap().unwrap();
-    }
-    assert!(stream.deserialize::<()>().unwrap().is_none());
+    // an empty (but non-nil) map is transmitted with a zero count
+    let decoded = stream.deserialize::<ConfigWithMap>().unwrap().unwrap();
+    assert_eq!(decoded.name, "foo");
+    assert!(decoded.counts.is_empty());
+
+    // a nil map is omitted from the transmission entirely
+    let decoded = stream.deserialize::<ConfigWithMap>().unwrap().unwrap();
+    assert_eq!(decoded.name, "bar");
+    assert!(decoded.counts.is_empty());
 }
 
-quickcheck! {
-    fn non_blocking_io(seq: PartialWithErrors<GenWouldBlock>) -> bool {
-        macro_rules! block {
-            ($e:expr) => {
-                loop {
-                    #[allow(unreachable_patterns)]
-                    match $e {
-                        Err(e) => {
-                            if e.kind() == ErrorKind::Io(::std::io::ErrorKind::WouldBlock) {
-                                continue;
-                            } else {
-                                break Err(e);
-                            }
-                        }
-                        Ok(x) => break Ok(x),
-                    }
-                }
-            };
+#[derive(Deserialize, Debug, PartialEq)]
+struct BagWithOptionalSlice {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Items")]
+    items: Option<Vec<i64>>,
+}
+
+#[test]
+fn struct_with_optional_slice_field() {
+    let buffer = include_bytes!("reference/output/struct_with_optional_slice.gob");
+
+    let cursor = Cursor::new(buffer.as_ref());
+    let mut stream = StreamDeserializer::new(cursor);
+
+    // Go omits both nil and empty slices, so neither is transmitted
+    let decoded = stream.deserialize::<BagWithOptionalSlice>().unwrap().unwrap();
+    assert_eq!(decoded.name, "nil");
+    assert_eq!(decoded.items, None);
+
+    let decoded = stream.deserialize::<BagWithOptionalSlice>().unwrap().unwrap();
+    assert_eq!(decoded.name, "empty");
+    assert_eq!(decoded.items, None);
+
+    let decoded = stream.deserialize::<BagWithOptionalSlice>().unwrap().unwrap();
+    assert_eq!(decoded.name, "full");
+    assert_eq!(decoded.items, Some(vec![1, 2]));
+}
+
+#[test]
+fn struct_with_optional_slice_field_present_but_empty() {
+    let deserializer = Deserializer::from_slice(&[
+        // Bag and []int definitions, as sent by Go
+        37, 255, 129, 3, 1, 1, 3, 66, 97, 103, 1, 255, 130, 0, 1, 2, 1, 4, 78, 97, 109, 101, 1,
+        12, 0, 1, 5, 73, 116, 101, 109, 115, 1, 255, 132, 0, 0, 0, 12, 255, 131, 2, 1, 2, 255,
+        132, 0, 1, 4, 0, 0,
+        // {Name: "x", Items: []int{}} with the empty slice sent explicitly
+        8, 255, 130, 1, 1, 120, 1, 0, 0,
+    ]);
+    let decoded = BagWithOptionalSlice::deserialize(deserializer).unwrap();
+    assert_eq!(decoded.name, "x");
+    assert_eq!(decoded.items, Some(vec![]));
+}
+
+#[test]
+fn struct_with_double_pointers() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Pointers {
+        #[serde(rename = "A")]
+        a: Option<Option<String>>,
+        #[serde(rename = "B")]
+        b: Option<Option<String>>,
+        #[serde(rename = "C")]
+        c: Option<Option<String>>,
+        #[serde(rename = "D")]
+        d: Option<Option<i64>>,
+    }
+
+    let buffer = include_bytes!("reference/output/struct_with_double_pointers.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+
+    // nil pointers at either level and pointers to zero values are omitted
+    assert_eq!(
+        stream.deserialize::<Pointers>().unwrap().unwrap(),
+        Pointers {
+            a: None,
+            b: None,
+            c: None,
+            d: Some(Some(7)),
         }
+    );
+    assert_eq!(
+        stream.deserialize::<Pointers>().unwrap().unwrap(),
+        Pointers {
+            a: Some(Some("x".to_owned())),
+            b: None,
+            c: None,
+            d: None,
+        }
+    );
+}
 
-        let buffer = include_bytes!("reference/output/non_empty_values.gob");
-        let reader = Cursor::new(buffer.as_ref().to_vec());
-        let partial_reader = PartialRead::new(reader, seq);
-        let mut stream = StreamDeserializer::new(partial_reader);
+#[test]
+fn struct_with_duration_fields() {
+    #[derive(Deserialize)]
+    struct Job {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Timeout", with = "gob::go::duration")]
+        timeout: ::std::time::Duration,
+        #[serde(rename = "Backoff")]
+        backoff: gob::go::Duration,
+    }
 
-        assert_eq!(true, block!(stream.deserialize::<bool>()).unwrap().unwrap());
-        assert_eq!(42u64, block!(stream.deserialize::<u64>()).unwrap().unwrap());
-        assert_eq!(42i64, block!(stream.deserialize::<i64>()).unwrap().unwrap());
-        assert_eq!(42f64, block!(stream.deserialize::<f64>()).unwrap().unwrap());
-        assert_eq!(
-            "foo",
-            block!(stream.deserialize::<String>()).unwrap().unwrap()
-        );
-        assert_eq!(
-            ByteBuf::from(vec![0x1, 0x2]),
-            block!(stream.deserialize::<ByteBuf>()).unwrap().unwrap()
-        );
-        assert_eq!(
-            vec![true, false],
-            block!(stream.deserialize::<Vec<bool>>()).unwrap().unwrap()
-        );
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/struct_with_durations.gob"));
+
+    let decoded = Job::deserialize(deserializer).unwrap();
+    assert_eq!(decoded.name, "build");
+    assert_eq!(decoded.timeout, ::std::time::Duration::from_secs(5));
+    assert_eq!(decoded.backoff.as_nanos(), -1_500_000_000);
+    assert!(decoded.backoff.is_negative());
+    assert_eq!(decoded.backoff.to_std(), None);
+    assert_eq!(decoded.backoff.abs(), ::std::time::Duration::from_millis(1500));
+}
 
-        block!(stream.deserialize::<()>()).unwrap().is_none()
+#[test]
+fn negative_duration_into_std_duration() {
+    #[derive(Deserialize, Debug)]
+    struct Job {
+        #[serde(rename = "Backoff", with = "gob::go::duration")]
+        #[allow(unused)]
+        backoff: ::std::time::Duration,
+    }
+
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/struct_with_durations.gob"));
+    assert!(Job::deserialize(deserializer).is_err());
+}
+
+#[test]
+fn error_interface_field() {
+    #[derive(Deserialize)]
+    struct Result {
+        #[serde(rename = "Err", default)]
+        err: Option<GobError>,
+    }
+
+    let buffer = include_bytes!("reference/output/error_interface.gob");
+
+    let cursor = Cursor::new(buffer.as_ref());
+    let mut stream = StreamDeserializer::new(cursor);
+
+    let decoded = stream.deserialize::<Result>().unwrap().unwrap();
+    let err = decoded.err.unwrap();
+    assert_eq!(err.message(), "file not found");
+    assert_eq!(String::from(err), "file not found");
+
+    // a nil error is omitted from the transmission entirely
+    let decoded = stream.deserialize::<Result>().unwrap().unwrap();
+    assert!(decoded.err.is_none());
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Circle {
+    #[serde(rename = "Radius")]
+    radius: f64,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Square {
+    #[serde(rename = "Side")]
+    side: i64,
+}
+
+#[test]
+fn interface_slice_with_registered_types() {
+    let buffer = include_bytes!("reference/output/interface_slice.gob");
+
+    let cursor = Cursor::new(buffer.as_ref());
+    let mut stream = StreamDeserializer::new(cursor);
+    stream.register_interface_type::<Circle>("main.Circle");
+    stream.register_interface_type::<Square>("main.Square");
+
+    let decoded = stream.deserialize::<Vec<Interface>>().unwrap().unwrap();
+    assert_eq!(decoded.len(), 4);
+    assert_eq!(decoded[0].name(), "main.Circle");
+    assert_eq!(decoded[0].downcast_ref(), Some(&Circle { radius: 1.5 }));
+    assert_eq!(decoded[1].name(), "main.Square");
+    assert!(!decoded[1].is::<Circle>());
+    assert_eq!(decoded[1].downcast_ref(), Some(&Square { side: 3 }));
+    assert!(decoded[2].is_nil());
+    let circle = decoded.into_iter().nth(3).unwrap().downcast::<Circle>();
+    assert_eq!(circle.unwrap(), Circle { radius: 0.25 });
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Boxed<T> {
+    #[serde(rename = "Value")]
+    value: T,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Pair {
+    #[serde(rename = "Key")]
+    key: String,
+    #[serde(rename = "Value")]
+    value: f64,
+}
+
+#[test]
+fn interface_generic_types() {
+    let buffer = include_bytes!("reference/output/interface_generic_types.gob");
+
+    assert_eq!(
+        normalize_type_name(" main.Pair[string, float64] "),
+        "main.Pair[string,float64]"
+    );
+    assert_eq!(
+        normalize_type_name("main.Box[interface {}]"),
+        normalize_type_name("main.Box[interface{}]")
+    );
+    assert_eq!(normalize_type_name("map[string]chan  int"), "map[string]chan int");
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    stream.register_interface_type::<Boxed<i64>>("main.Box[int]");
+    stream.register_interface_type::<Pair>("main.Pair[string, float64]");
+    stream.register_interface_type::<Boxed<Value>>("main.Box[interface{}]");
+    assert!(stream.is_interface_type_registered("main.Pair[string,float64]"));
+
+    let decoded = stream.deserialize::<Vec<Interface>>().unwrap().unwrap();
+    assert_eq!(decoded.len(), 3);
+    assert_eq!(decoded[0].name(), "main.Box[int]");
+    assert_eq!(decoded[0].downcast_ref(), Some(&Boxed { value: 7i64 }));
+    assert_eq!(decoded[1].name(), "main.Pair[string,float64]");
+    let pair = Pair {
+        key: "pi".into(),
+        value: 3.14,
+    };
+    assert_eq!(decoded[1].downcast_ref(), Some(&pair));
+    assert_eq!(decoded[2].name(), "main.Box[interface {}]");
+    let boxed = Boxed {
+        value: Value::Int(5),
+    };
+    assert_eq!(decoded[2].downcast_ref(), Some(&boxed));
+}
+
+#[test]
+fn interface_slice_lengths() {
+    let buffer = include_bytes!("reference/output/interface_slice_lengths.gob");
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let empty = stream.deserialize::<Vec<Value>>().unwrap().unwrap();
+    assert!(empty.is_empty());
+    let one = stream.deserialize::<Vec<Value>>().unwrap().unwrap();
+    assert_eq!(one, &[Value::String("one".into())]);
+    let many = stream.deserialize::<Vec<Value>>().unwrap().unwrap();
+    assert_eq!(
+        many,
+        &[
+            Value::Int(-1),
+            Value::String("two".into()),
+            Value::Float(3.5),
+            Value::Bool(true),
+            Value::Nil,
+        ]
+    );
+    assert!(stream.deserialize::<Vec<Value>>().unwrap().is_none());
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let empty = stream.deserialize::<Vec<Interface>>().unwrap().unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn internally_tagged_enum() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(tag = "Kind")]
+    enum Shape {
+        #[serde(rename = "circle")]
+        Circle {
+            #[serde(rename = "Radius")]
+            radius: f64,
+        },
+        #[serde(rename = "square")]
+        Square {
+            #[serde(rename = "Side")]
+            side: i64,
+        },
+    }
+
+    let buffer = include_bytes!("reference/output/tagged_shapes.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let circle = stream.deserialize::<Shape>().unwrap().unwrap();
+    assert_eq!(circle, Shape::Circle { radius: 1.5 });
+    let square = stream.deserialize::<Shape>().unwrap().unwrap();
+    assert_eq!(square, Shape::Square { side: 3 });
+    assert!(stream.deserialize::<Shape>().unwrap().is_none());
+}
+
+#[test]
+fn interface_map_negative_ints() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Point {
+        x: i64,
+        y: i64,
+        z: i64,
+    }
+
+    let buffer = include_bytes!("reference/output/interface_map_negative_ints.gob");
+    let expected = Point {
+        x: -42,
+        y: -1,
+        z: i64::MIN,
+    };
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    // a map[interface{}]interface{}
+    assert_eq!(stream.deserialize::<Point>().unwrap(), Some(expected));
+    // a map[string]interface{}
+    let decoded = stream.deserialize::<HashMap<String, Value>>().unwrap().unwrap();
+    assert_eq!(decoded["x"], Value::Int(-42));
+    assert_eq!(decoded["y"], Value::Int(-1));
+    assert_eq!(decoded["z"], Value::Int(i64::MIN));
+}
+
+#[test]
+fn interface_map_floats() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Gauge {
+        #[serde(rename = "Ratio")]
+        ratio: f64,
+        #[serde(rename = "Scale")]
+        scale: f32,
+        #[serde(rename = "Count")]
+        count: i64,
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Origin")]
+        origin: Point,
+        #[serde(rename = "Unit")]
+        unit: Option<String>,
+    }
+
+    // the interface values carry a byte count, and all but the struct a
+    // singleton field delta
+    let buffer = include_bytes!("reference/output/interface_map_floats.gob");
+    let decoded = Gauge::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(
+        decoded,
+        Gauge {
+            ratio: 0.5,
+            scale: 0.25,
+            count: 3,
+            name: "gauge".into(),
+            origin: Point { x: 1, y: -2 },
+            unit: None,
+        }
+    );
+
+    let decoded = HashMap::<String, Value>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(decoded["Scale"], Value::Float(0.25));
+    assert_eq!(decoded["Origin"].get("Y"), Some(&Value::Int(-2)));
+    assert!(decoded["Unit"].is_nil());
+}
+
+#[test]
+fn interface_map_without_byte_counts() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Gauge {
+        #[serde(rename = "Scale")]
+        scale: f32,
+        #[serde(rename = "Count")]
+        count: i64,
+        #[serde(rename = "Origin")]
+        origin: Point,
+        #[serde(rename = "Unit")]
+        unit: Option<String>,
+    }
+
+    // the type definitions Go sent for interface_map_floats, followed by
+    // a value whose interface values leave out their byte counts
+    let go_buffer = include_bytes!("reference/output/interface_map_floats.gob");
+    let mut buffer = go_buffer[..0x2f].to_vec();
+    let key = |name: &str| {
+        let mut key = b"\x06string\x0c\x00".to_vec();
+        key.push(name.len() as u8);
+        key.extend_from_slice(name.as_bytes());
+        key
+    };
+    let mut value = b"\xff\x82\x00\x04".to_vec();
+    value.extend(key("Scale"));
+    value.extend_from_slice(b"\x07float32\x08\x00\xfe\xd0\x3f");
+    value.extend(key("Count"));
+    value.extend_from_slice(b"\x03int\x04\x00\x06");
+    value.extend(key("Origin"));
+    value.extend_from_slice(b"\x0amain.Point\xff\x84\x01\x02\x01\x03\x00");
+    value.extend(key("Unit"));
+    value.push(0);
+    buffer.push(value.len() as u8);
+    buffer.extend(value);
+
+    // Go always sends the byte counts, and values without them are not
+    // guessed at
+    assert!(Gauge::deserialize(Deserializer::from_slice(&buffer)).is_err());
+}
+
+#[test]
+fn string_interface_map_config() {
+    let buffer = include_bytes!("reference/output/string_interface_map_config.gob");
+    let config = HashMap::<String, Value>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(config.len(), 6);
+    assert_eq!(config["APP_NAME"], Value::String("Gitea".into()));
+    assert_eq!(config["HTTP_PORT"], Value::Int(3000));
+    assert_eq!(config["OFFLINE_MODE"], Value::Bool(true));
+    assert_eq!(config["RATE"], Value::Float(0.5));
+    assert_eq!(
+        config["ADMINS"],
+        Value::Seq(vec![Value::String("root".into()), Value::String("ops".into())])
+    );
+    assert!(config["SECRET"].is_nil());
+}
+
+#[test]
+fn registered_interface_types() {
+    let mut stream = StreamDeserializer::new(Cursor::new(Vec::<u8>::new()));
+    assert_eq!(stream.registered_interface_types().count(), 0);
+    stream.register_interface_type::<Circle>("main.Circle");
+    stream.register_interface_type::<Square>("main.Square");
+
+    let mut names = stream.registered_interface_types().collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, &["main.Circle", "main.Square"]);
+    assert!(stream.is_interface_type_registered("main.Square"));
+    assert!(!stream.is_interface_type_registered("main.Triangle"));
+}
+
+#[test]
+fn interface_with_unregistered_type() {
+    let buffer = include_bytes!("reference/output/interface_slice.gob");
+
+    let cursor = Cursor::new(buffer.as_ref());
+    let mut stream = StreamDeserializer::new(cursor);
+    stream.register_interface_type::<Circle>("main.Circle");
+
+    let err = stream.deserialize::<Vec<Interface>>().unwrap_err();
+    assert!(err.to_string().contains("main.Square"), "{}", err);
+}
+
+#[test]
+fn interface_holding_nested_maps() {
+    let buffer = include_bytes!("reference/output/interface_nested_map.gob");
+    let deserializer = Deserializer::from_slice(buffer);
+
+    let config = HashMap::<String, Value>::deserialize(deserializer).unwrap();
+    assert_eq!(config.len(), 3);
+    assert_eq!(config["APP_NAME"], Value::String("Gitea".into()));
+    let server = &config["server"];
+    assert_eq!(server.get("DOMAIN").and_then(Value::as_str), Some("git.example.com"));
+    assert_eq!(server.get("HTTP_PORT").and_then(Value::as_i64), Some(3000));
+    let database = &config["database"];
+    assert_eq!(database.get("DB_TYPE").and_then(Value::as_str), Some("sqlite3"));
+    assert_eq!(database.get("LOG_SQL"), Some(&Value::Bool(false)));
+    assert!(database.get("PATH").unwrap().is_nil());
+    assert_eq!(database.get("USER"), None);
+}
+
+#[test]
+fn struct_with_interface_field() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Event {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Payload", default)]
+        payload: Value,
+    }
+
+    let buffer = include_bytes!("reference/output/struct_with_interface_field.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let greeting = stream.deserialize::<Event>().unwrap().unwrap();
+    assert_eq!(greeting.name, "greeting");
+    assert_eq!(greeting.payload, Value::String("hello".into()));
+    let answer = stream.deserialize::<Event>().unwrap().unwrap();
+    assert_eq!(answer.name, "answer");
+    assert_eq!(answer.payload, Value::Int(42));
+    // Go omits nil interface fields
+    let empty = stream.deserialize::<Event>().unwrap().unwrap();
+    assert_eq!(empty.name, "empty");
+    assert!(empty.payload.is_nil());
+    assert!(stream.deserialize::<Event>().unwrap().is_none());
+
+    // or with the concrete type known up front
+    #[derive(Deserialize)]
+    struct Greeting {
+        #[serde(rename = "Payload")]
+        payload: String,
+    }
+    let greeting = Greeting::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(greeting.payload, "hello");
+}
+
+#[test]
+fn time_at_top_level() {
+    /// A `time.Time` in the format of its `MarshalBinary`.
+    #[derive(Debug, PartialEq)]
+    struct Time {
+        secs: i64,
+        nanos: i32,
+    }
+
+    impl GobDecoder for Time {
+        type Error = String;
+
+        fn gob_decode(bytes: &[u8]) -> Result<Time, String> {
+            if bytes.len() != 15 || bytes[0] != 1 {
+                return Err(format!("unsupported time encoding {:?}", bytes));
+            }
+            Ok(Time {
+                secs: i64::from_be_bytes(bytes[1..9].try_into().unwrap()),
+                nanos: i32::from_be_bytes(bytes[9..13].try_into().unwrap()),
+            })
+        }
+    }
+
+    let buffer = include_bytes!("reference/output/time_top_level.gob");
+    let time = GobEncoded::<Time>::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    // 2009-11-10 23:00:00 in seconds since January 1 of year 1
+    assert_eq!(
+        time.0,
+        Time {
+            secs: 63_393_490_800,
+            nanos: 5
+        }
+    );
+
+    let bytes = ByteBuf::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(bytes.len(), 15);
+    let value = Value::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(value, Value::Bytes(bytes.to_vec()));
+}
+
+#[test]
+fn struct_with_ip_net() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Route {
+        #[serde(rename = "Dst")]
+        dst: IPNet,
+        #[serde(rename = "Gateway")]
+        gateway: IP,
+    }
+
+    // Go sends the `net.IP`s as text, and the masks as bytes
+    let buffer = include_bytes!("reference/output/struct_with_ip_net.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let route = stream.deserialize::<Route>().unwrap().unwrap();
+    assert_eq!(route.dst.ip(), IpAddr::V4(Ipv4Addr::new(10, 20, 0, 0)));
+    assert_eq!(route.dst.prefix_len(), 14);
+    assert_eq!(route.gateway, IP::from(Ipv4Addr::new(10, 20, 0, 1)));
+    let route = stream.deserialize::<Route>().unwrap().unwrap();
+    assert_eq!(route.dst.ip(), "fd00::".parse::<IpAddr>().unwrap());
+    assert_eq!(route.dst.prefix_len(), 64);
+    assert_eq!(route.gateway, IP::from("fd00::1".parse::<Ipv6Addr>().unwrap()));
+    let ip = stream.deserialize::<IpAddr>().unwrap().unwrap();
+    assert_eq!(ip, "2001:db8::1".parse::<IpAddr>().unwrap());
+    assert!(stream.deserialize::<IpAddr>().unwrap().is_none());
+
+    let types = gob::read_schema(&mut Cursor::new(buffer.as_ref())).unwrap();
+    assert_eq!(gob::check_compatibility::<Route>(&types), Ok(()));
+}
+
+#[test]
+fn struct_with_bytes_and_string_fields() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Blob {
+        #[serde(rename = "Data")]
+        data: Vec<u8>,
+        #[serde(rename = "Text")]
+        text: String,
+    }
+
+    let buffer = include_bytes!("reference/output/struct_bytes_and_string.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let blob = stream.deserialize::<Blob>().unwrap().unwrap();
+    assert_eq!(blob.data, &[0xff, 0x00, b'o', b'k']);
+    assert_eq!(blob.text, "ok");
+    // bytes that are valid UTF-8 stay bytes, and a string may hold any
+    // valid UTF-8, including NUL
+    let blob = stream.deserialize::<Blob>().unwrap().unwrap();
+    assert_eq!(blob.data, "ünï\0".as_bytes());
+    assert_eq!(blob.text, "ünï\0 \u{2713}");
+
+    // each field is decoded as the type it was defined with
+    #[derive(Deserialize, Debug)]
+    struct Swapped {
+        #[serde(rename = "Data")]
+        _data: String,
+    }
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    assert!(stream.deserialize::<Swapped>().is_err());
+}
+
+#[test]
+fn struct_with_borrowed_bytes_field() {
+    #[derive(Deserialize)]
+    struct Blob<'a> {
+        #[serde(rename = "Name")]
+        name: &'a str,
+        #[serde(rename = "Data", borrow)]
+        data: Bytes<'a>,
+    }
+
+    let buffer = include_bytes!("reference/output/struct_with_bytes_field.gob");
+    let deserializer = Deserializer::from_slice(buffer);
+
+    let decoded = Blob::deserialize(deserializer).unwrap();
+    assert_eq!(decoded.name, "blob");
+    assert_eq!(&*decoded.data, &[0xde, 0xad, 0xbe, 0xef]);
+
+    // both fields point into the input rather than into a copy
+    let input = buffer.as_ptr_range();
+    assert!(input.contains(&decoded.name.as_ptr()));
+    assert!(input.contains(&decoded.data.as_ptr()));
+}
+
+#[test]
+fn struct_with_borrowed_bytes_field_from_stream() {
+    #[derive(Deserialize)]
+    struct Blob<'a> {
+        #[serde(rename = "Data", borrow)]
+        data: &'a [u8],
+    }
+
+    let buffer = include_bytes!("reference/output/struct_with_bytes_field.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+
+    let decoded = stream.deserialize::<Blob>().unwrap().unwrap();
+    assert_eq!(decoded.data, &[0xde, 0xad, 0xbe, 0xef]);
+}
+
+#[test]
+fn struct_with_cow_str_field() {
+    use std::borrow::Cow;
+
+    #[derive(Deserialize)]
+    struct Blob<'a> {
+        #[serde(rename = "Name", borrow)]
+        name: Cow<'a, str>,
+    }
+
+    // borrowed from the input
+    let buffer = include_bytes!("reference/output/struct_with_bytes_field.gob");
+    let decoded = Blob::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert!(matches!(decoded.name, Cow::Borrowed("blob")));
+    assert!(buffer.as_ptr_range().contains(&decoded.name.as_ptr()));
+
+    // borrowed from the buffer of the stream, until the next value is read
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let decoded = stream.deserialize::<Blob>().unwrap().unwrap();
+    assert!(matches!(decoded.name, Cow::Borrowed("blob")));
+
+    // owned where the type cannot borrow
+    #[derive(Deserialize)]
+    struct OwnedBlob {
+        #[serde(rename = "Name")]
+        name: Cow<'static, str>,
+    }
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let decoded = stream.deserialize::<OwnedBlob>().unwrap().unwrap();
+    assert!(matches!(decoded.name, Cow::Owned(ref name) if name == "blob"));
+}
+
+#[test]
+fn struct_with_single_nested_struct_field() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Inner {
+        #[serde(rename = "V")]
+        v: i64,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Outer {
+        #[serde(rename = "Inner")]
+        inner: Inner,
+    }
+
+    let buffer = include_bytes!("reference/output/single_field_nesting.gob");
+    // the value ends with the terminators of both structs
+    assert!(buffer.ends_with(&[0x01, 0x01, 0x0a, 0x00, 0x00]));
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let decoded = stream.deserialize::<Outer>().unwrap().unwrap();
+    assert_eq!(decoded, Outer { inner: Inner { v: 5 } });
+    assert!(stream.deserialize::<Outer>().unwrap().is_none());
+}
+
+#[test]
+fn struct_with_single_nested_struct_field_three_levels() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Leaf {
+        #[serde(rename = "V")]
+        v: i64,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Middle {
+        #[serde(rename = "Leaf")]
+        leaf: Leaf,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Top {
+        #[serde(rename = "Middle")]
+        middle: Middle,
+    }
+
+    let buffer = include_bytes!("reference/output/single_field_nesting_three_levels.gob");
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let decoded = stream.deserialize::<Top>().unwrap().unwrap();
+    assert_eq!(
+        decoded,
+        Top {
+            middle: Middle {
+                leaf: Leaf { v: -3 }
+            }
+        }
+    );
+    assert!(stream.deserialize::<Top>().unwrap().is_none());
+}
+
+#[test]
+fn unit_struct() {
+    #[derive(Deserialize)]
+    struct EmptyStruct {};
+
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/empty_struct.gob"));
+
+    EmptyStruct::deserialize(deserializer).unwrap();
+}
+
+#[test]
+fn enum_with_newtype_variants_and_external_tags() {
+    #[derive(Deserialize, Debug, PartialEq, Eq)]
+    enum Enum {
+        #[serde(rename = "Var1")]
+        V1(bool),
+        #[serde(rename = "Var2")]
+        V2(i64),
+        #[serde(rename = "Var3")]
+        V3(String),
+    }
+
+    let deserializer = Deserializer::from_slice(include_bytes!(
+        "reference/output/enum_with_newtype_variants.gob"
+    ));
+
+    let decoded = Enum::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, Enum::V2(42));
+}
+
+#[test]
+fn enum_with_struct_variants_and_external_tags() {
+    #[derive(Deserialize, Debug, PartialEq, Eq)]
+    enum Enum {
+        V1 {
+            #[serde(rename = "Foo")]
+            foo: bool,
+        },
+        V2 {
+            #[serde(rename = "Bar")]
+            bar: i64,
+            #[serde(rename = "Baz")]
+            baz: u64,
+        },
+        V3 {
+            #[serde(rename = "Quux")]
+            quux: String,
+        },
+    }
+
+    let deserializer = Deserializer::from_slice(include_bytes!(
+        "reference/output/enum_with_struct_variants.gob"
+    ));
+
+    let decoded = Enum::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, Enum::V2 { bar: 42, baz: 1234 });
+}
+
+/// Returns the `n`th value of `top_level_scalars.gob`, which Go sends
+/// each behind a zero singleton delta.
+fn top_level_scalar<T: serde::de::DeserializeOwned>(n: usize) -> T {
+    let buffer = include_bytes!("reference/output/top_level_scalars.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    for _ in 0..n {
+        let () = stream.deserialize::<()>().unwrap().unwrap();
+    }
+    stream.deserialize::<T>().unwrap().unwrap()
+}
+
+#[test]
+fn top_level_int64_from_go() {
+    assert_eq!(top_level_scalar::<i64>(0), 42);
+    assert_eq!(top_level_scalar::<i64>(1), -1000);
+}
+
+#[test]
+fn top_level_uint64_from_go() {
+    assert_eq!(top_level_scalar::<u64>(2), 300);
+}
+
+#[test]
+fn top_level_bool_from_go() {
+    assert_eq!(top_level_scalar::<bool>(3), true);
+}
+
+#[test]
+fn top_level_float64_from_go() {
+    assert_eq!(top_level_scalar::<f64>(4), -2.5);
+}
+
+#[test]
+fn top_level_string_from_go() {
+    assert_eq!(top_level_scalar::<String>(5), "hi");
+}
+
+#[test]
+fn top_level_bytes_from_go() {
+    assert_eq!(top_level_scalar::<ByteBuf>(6), ByteBuf::from(b"raw".to_vec()));
+}
+
+#[test]
+fn top_level_interface_values_from_go() {
+    let buffer = include_bytes!("reference/output/interface_top_level.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    assert_eq!(stream.deserialize::<bool>().unwrap().unwrap(), true);
+    assert_eq!(stream.deserialize::<f64>().unwrap().unwrap(), 2.5);
+    assert_eq!(stream.deserialize::<i64>().unwrap().unwrap(), -3);
+    assert!(stream.deserialize::<()>().unwrap().is_none());
+}
+
+#[test]
+fn top_level_scalar_without_singleton_delta() {
+    let deserializer = Deserializer::from_slice(&[3, 4, 1, 84]);
+    assert!(i64::deserialize(deserializer).is_err());
+}
+
+#[test]
+fn unit_from_any() {
+    let buffer = include_bytes!("reference/output/non_empty_values.gob");
+
+    let cursor = Cursor::new(buffer.as_ref());
+    let mut stream = StreamDeserializer::new(cursor);
+
+    for _ in 0..7 {
+        let () = stream.deserialize::<()>().unwrap().unwrap();
+    }
+    assert!(stream.deserialize::<()>().unwrap().is_none());
+}
+
+#[test]
+fn redefined_type_id() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Counter {
+        #[serde(rename = "A")]
+        a: i64,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Label {
+        #[serde(rename = "B")]
+        b: String,
+    }
+
+    // the output of two encoders, which both define their type as id 65
+    let mut buffer = vec![
+        27, 255, 129, 3, 1, 1, 7, 67, 111, 117, 110, 116, 101, 114, 1, 255, 130, 0, 1, 1, 1, 1,
+        65, 1, 4, 0, 0, 0, 5, 255, 130, 1, 14, 0,
+    ];
+    buffer.extend_from_slice(&[
+        25, 255, 129, 3, 1, 1, 5, 76, 97, 98, 101, 108, 1, 255, 130, 0, 1, 1, 1, 1, 66, 1, 12, 0,
+        0, 0, 6, 255, 130, 1, 1, 120, 0,
+    ]);
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(
+        stream.deserialize::<Counter>().unwrap().unwrap(),
+        Counter { a: 7 }
+    );
+    assert_eq!(
+        stream.deserialize::<Label>().unwrap().unwrap(),
+        Label { b: "x".to_owned() }
+    );
+    assert!(stream.deserialize::<()>().unwrap().is_none());
+}
+
+#[test]
+fn redefined_predefined_type_id() {
+    // a struct definition claiming the id of `int`
+    let buffer = [
+        25, 3, 3, 1, 1, 7, 67, 111, 117, 110, 116, 101, 114, 1, 4, 0, 1, 1, 1, 1, 65, 1, 4, 0, 0,
+        0,
+    ];
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer[..]));
+    let err = stream.deserialize::<()>().unwrap_err();
+    assert!(err.to_string().contains("reserved"), "{}", err);
+}
+
+#[test]
+fn read_schema_stops_at_value() {
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+    let mut cursor = Cursor::new(buffer.as_ref());
+
+    let types = gob::read_schema(&mut cursor).unwrap();
+    let ids = types.ids().collect::<Vec<_>>();
+    assert_eq!(ids.len(), 1);
+    assert_eq!(types.name(ids[0]), Some("Point"));
+    let fields = types.fields(ids[0]).unwrap().collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        &[("X", gob::ser::TypeId::INT), ("Y", gob::ser::TypeId::INT)]
+    );
+
+    // the value message is left unread
+    let pos = cursor.position() as usize;
+    assert_eq!(buffer[pos] as usize, buffer.len() - pos - 1);
+
+    // the schema alone is consumed again on a second call
+    assert_eq!(gob::read_schema(&mut cursor).unwrap().ids().count(), 0);
+    assert_eq!(cursor.position() as usize, pos);
+}
+
+#[test]
+fn read_schema_from_gitea_blob() {
+    let buffer = include_bytes!("normal-session-2.bin");
+    let mut cursor = Cursor::new(buffer.as_ref());
+
+    // the session values are a map[interface{}]interface{}, which is
+    // defined without a name or fields
+    let types = gob::read_schema(&mut cursor).unwrap();
+    let ids = types.ids().collect::<Vec<_>>();
+    assert_eq!(ids, &[gob::ser::TypeId(64)]);
+    assert_eq!(types.name(ids[0]), None);
+    assert!(types.fields(ids[0]).is_none());
+
+    // positioned right behind the single definition message
+    assert_eq!(cursor.position(), buffer[0] as u64 + 1);
+}
+
+#[test]
+fn read_schema_without_values() {
+    let mut cursor = Cursor::new(&[][..]);
+    assert_eq!(gob::read_schema(&mut cursor).unwrap().ids().count(), 0);
+}
+
+#[test]
+fn check_compatibility_with_matching_struct() {
+    #[derive(Serialize, GobSerialize)]
+    #[allow(non_snake_case)]
+    struct Point {
+        X: i64,
+        Y: i64,
+    }
+
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+    let types = gob::read_schema(&mut Cursor::new(buffer.as_ref())).unwrap();
+    assert_eq!(gob::check_compatibility::<Point>(&types), Ok(()));
+}
+
+#[test]
+fn check_compatibility_reports_mismatches() {
+    #[derive(Serialize, GobSerialize)]
+    #[allow(non_snake_case)]
+    struct Point {
+        X: String,
+        Z: i64,
+    }
+
+    #[derive(Serialize, GobSerialize)]
+    #[allow(non_snake_case)]
+    struct Line {
+        A: i64,
+    }
+
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+    let types = gob::read_schema(&mut Cursor::new(buffer.as_ref())).unwrap();
+    assert_eq!(
+        gob::check_compatibility::<Point>(&types),
+        Err(vec![
+            gob::Mismatch::TypeMismatch {
+                path: "Point.X".into(),
+                expected: "string".into(),
+                found: "int".into(),
+            },
+            gob::Mismatch::MissingField {
+                path: "Point.Z".into(),
+            },
+        ])
+    );
+    assert_eq!(
+        gob::check_compatibility::<Line>(&types),
+        Err(vec![gob::Mismatch::MissingType {
+            name: "Line".into(),
+        }])
+    );
+}
+
+#[test]
+fn schema_diff_of_renamed_field() {
+    #[derive(Serialize, GobSerialize)]
+    #[allow(non_snake_case)]
+    struct User {
+        Name: String,
+        Mail: String,
+        Age: String,
+    }
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&User {
+            Name: "gopher".into(),
+            Mail: "gopher@example.com".into(),
+            Age: "13".into(),
+        })
+        .unwrap();
+    let types = gob::read_schema(&mut Cursor::new(&buffer)).unwrap();
+
+    mod expected {
+        #[derive(Serialize, GobSerialize)]
+        #[allow(non_snake_case)]
+        pub struct User {
+            Name: String,
+            Email: String,
+            Age: i64,
+        }
+    }
+
+    let diff = gob::schema_diff::<expected::User>(&types).unwrap();
+    assert_eq!(
+        diff,
+        gob::SchemaDiff {
+            added: vec!["User.Mail".into()],
+            removed: vec!["User.Email".into()],
+            changed: vec![gob::FieldChange {
+                path: "User.Age".into(),
+                expected: "int".into(),
+                found: "string".into(),
+            }],
+        }
+    );
+    assert_eq!(
+        diff.to_string(),
+        "+ User.Mail\n- User.Email\n~ User.Age: expected int, found string\n"
+    );
+    assert!(gob::schema_diff::<User>(&types).unwrap().is_empty());
+}
+
+#[test]
+fn deserialize_seed_extends_vec() {
+    use serde::de::{DeserializeSeed, SeqAccess, Visitor};
+    use std::fmt;
+
+    /// Appends the elements of a sequence to a `Vec`.
+    struct Extend<'a>(&'a mut Vec<i64>);
+
+    impl<'de, 'a> DeserializeSeed<'de> for Extend<'a> {
+        type Value = usize;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<usize, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_seq(self)
+        }
+    }
+
+    impl<'de, 'a> Visitor<'de> for Extend<'a> {
+        type Value = usize;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a sequence of integers")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<usize, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let len = self.0.len();
+            while let Some(elem) = seq.next_element()? {
+                self.0.push(elem);
+            }
+            Ok(self.0.len() - len)
+        }
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = gob::StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&vec![1i64, 2, 3]).unwrap();
+        stream.serialize(&vec![-4i64]).unwrap();
+    }
+
+    let mut values = Vec::with_capacity(8);
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(stream.deserialize_seed(Extend(&mut values)).unwrap(), Some(3));
+    assert_eq!(stream.deserialize_seed(Extend(&mut values)).unwrap(), Some(1));
+    assert_eq!(stream.deserialize_seed(Extend(&mut values)).unwrap(), None);
+    assert_eq!(values, [1, 2, 3, -4]);
+    assert_eq!(values.capacity(), 8);
+}
+
+#[test]
+fn reader_reads_primitives_by_hand() {
+    // Point{X: 22, Y: 33}, after its type definition
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+    let mut reader = Reader::new(buffer);
+    let def_len = reader.read_uint().unwrap() as usize;
+    assert_eq!(reader.read_int().unwrap(), -65);
+    let def = &reader.remaining()[..def_len - 2];
+    assert!(def.windows(5).any(|w| w == b"Point"));
+
+    let mut reader = Reader::new(&buffer[def_len + 1..]);
+    let len = reader.read_uint().unwrap() as usize;
+    assert_eq!(reader.remaining().len(), len);
+    assert_eq!(reader.read_int().unwrap(), 65);
+    assert_eq!(reader.read_uint().unwrap(), 1);
+    assert_eq!(reader.read_int().unwrap(), 22);
+    assert_eq!(reader.read_uint().unwrap(), 1);
+    assert_eq!(reader.read_int().unwrap(), 33);
+    assert_eq!(reader.read_uint().unwrap(), 0);
+    assert!(reader.remaining().is_empty());
+    assert_eq!(
+        reader.read_uint().unwrap_err().kind(),
+        ErrorKind::Truncated
+    );
+}
+
+#[test]
+fn looks_like_gob_on_streams() {
+    let mut streams = vec![
+        include_bytes!("normal-session.bin").to_vec(),
+        include_bytes!("normal-session-2.bin").to_vec(),
+    ];
+    for entry in std::fs::read_dir("tests/reference/output").unwrap() {
+        streams.push(std::fs::read(entry.unwrap().path()).unwrap());
+    }
+    for stream in &streams {
+        assert!(gob::looks_like_gob(stream), "{:?}", &stream[..stream.len().min(16)]);
+        // the first few bytes are enough
+        assert!(gob::looks_like_gob(&stream[..stream.len().min(8)]));
+    }
+
+    let not_gob: &[&[u8]] = &[
+        b"",
+        b"{\"uid\": 1, \"uname\": \"test\"}",
+        b"[1, 2, 3]",
+        b"\"string\"",
+        b"uid=1&uname=test",
+        b"\x1f\x8b\x08\x00",
+        &[0x80],
+        &[0x00, 0x00],
+    ];
+    for bytes in not_gob {
+        assert!(!gob::looks_like_gob(bytes), "{:?}", bytes);
+    }
+}
+
+quickcheck! {
+    fn looks_like_gob_without_false_negatives(bytes: Vec<u8>) -> bool {
+        let decodes = StreamDeserializer::new(Cursor::new(&bytes))
+            .deserialize::<serde::de::IgnoredAny>()
+            .map_or(false, |value| value.is_some());
+        !decodes || gob::looks_like_gob(&bytes)
+    }
+}
+
+quickcheck! {
+    fn non_blocking_io(seq: PartialWithErrors<GenWouldBlock>) -> bool {
+        macro_rules! block {
+            ($e:expr) => {
+                loop {
+                    #[allow(unreachable_patterns)]
+                    match $e {
+                        Err(e) => {
+                            if e.kind() == ErrorKind::Io(::std::io::ErrorKind::WouldBlock) {
+                                continue;
+                            } else {
+                                break Err(e);
+                            }
+                        }
+                        Ok(x) => break Ok(x),
+                    }
+                }
+            };
+        }
+
+        let buffer = include_bytes!("reference/output/non_empty_values.gob");
+        let reader = Cursor::new(buffer.as_ref().to_vec());
+        let partial_reader = PartialRead::new(reader, seq);
+        let mut stream = StreamDeserializer::new(partial_reader);
+
+        assert_eq!(true, block!(stream.deserialize::<bool>()).unwrap().unwrap());
+        assert_eq!(42u64, block!(stream.deserialize::<u64>()).unwrap().unwrap());
+        assert_eq!(42i64, block!(stream.deserialize::<i64>()).unwrap().unwrap());
+        assert_eq!(42f64, block!(stream.deserialize::<f64>()).unwrap().unwrap());
+        assert_eq!(
+            "foo",
+            block!(stream.deserialize::<String>()).unwrap().unwrap()
+        );
+        assert_eq!(
+            ByteBuf::from(vec![0x1, 0x2]),
+            block!(stream.deserialize::<ByteBuf>()).unwrap().unwrap()
+        );
+        assert_eq!(
+            vec![true, false],
+            block!(stream.deserialize::<Vec<bool>>()).unwrap().unwrap()
+        );
+
+        block!(stream.deserialize::<()>()).unwrap().is_none()
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct OnlyY {
+    #[serde(rename = "Y")]
+    y: i64,
+}
+
+#[test]
+fn extra_fields_deny() {
+    let mut stream = StreamDeserializer::new(Cursor::new(
+        include_bytes!("reference/output/point_struct.gob").as_ref(),
+    ))
+    .with_extra_fields(ExtraFields::Deny);
+    let err = stream.deserialize::<OnlyY>().unwrap_err();
+    assert_eq!(err.to_string(), "deserialize error: unknown field `X` of Go type Point");
+}
+
+#[test]
+fn extra_fields_skip() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct StrictY {
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let bytes = include_bytes!("reference/output/point_struct.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(bytes.as_ref()));
+    assert!(stream.deserialize::<StrictY>().is_err());
+
+    let mut stream =
+        StreamDeserializer::new(Cursor::new(bytes.as_ref())).with_extra_fields(ExtraFields::Skip);
+    assert_eq!(stream.deserialize::<StrictY>().unwrap().unwrap(), StrictY { y: 33 });
+}
+
+#[test]
+fn extra_fields_collect_into_map() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct YAndRest {
+        #[serde(rename = "Y")]
+        y: i64,
+        #[serde(flatten)]
+        rest: HashMap<String, Value>,
+    }
+
+    let bytes = include_bytes!("reference/output/point_struct.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(bytes.as_ref()))
+        .with_extra_fields(ExtraFields::CollectIntoMap);
+    assert_eq!(
+        stream.deserialize::<YAndRest>().unwrap().unwrap(),
+        YAndRest {
+            y: 33,
+            rest: HashMap::from([("X".to_string(), Value::Int(22))]),
+        }
+    );
+
+    // the default, where types without such a field ignore the rest
+    let mut stream = StreamDeserializer::new(Cursor::new(bytes.as_ref()));
+    assert_eq!(stream.deserialize::<OnlyY>().unwrap().unwrap(), OnlyY { y: 33 });
+}
+
+#[test]
+fn peek_message_len() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let text = "x".repeat(300);
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Point { x: 1, y: 2 }).unwrap();
+        stream.serialize(&Point { x: 1000, y: -1000 }).unwrap();
+        stream.serialize(&text).unwrap();
+    }
+
+    let mut reader = Reader::new(&buffer);
+    let mut lens = Vec::new();
+    while !reader.remaining().is_empty() {
+        lens.push(reader.read_bytes().unwrap().len());
+    }
+    // a definition followed by two values, and the string of more than
+    // 127 bytes, whose length takes more than one byte
+    assert_eq!(lens.len(), 4);
+    assert!(lens[3] > 300);
+
+    // the stream is read a byte at a time, so that peeking can only have
+    // read the length
+    let mut stream = StreamDeserializer::new(PartialRead::new(
+        Cursor::new(&buffer),
+        vec![PartialOp::Limited(1); buffer.len()],
+    ));
+    assert_eq!(stream.peek_message_len().unwrap(), Some(lens[0]));
+    assert_eq!(stream.peek_message_len().unwrap(), Some(lens[0]));
+    assert_eq!(stream.get_ref().get_ref().position(), 1);
+    assert_eq!(
+        stream.deserialize::<Point>().unwrap().unwrap(),
+        Point { x: 1, y: 2 }
+    );
+    assert_eq!(stream.peek_message_len().unwrap(), Some(lens[2]));
+    stream.deserialize::<Point>().unwrap().unwrap();
+    assert_eq!(stream.peek_message_len().unwrap(), Some(lens[3]));
+    assert_eq!(stream.deserialize::<String>().unwrap().unwrap(), text);
+    assert_eq!(stream.peek_message_len().unwrap(), None);
+}
+
+#[test]
+fn deserialize_with_len() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    // the definition of Point and the value both count
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let (point, len) = stream.deserialize_with_len::<Point>().unwrap().unwrap();
+    assert_eq!(point, Point { x: 22, y: 33 });
+    assert_eq!(len, buffer.len());
+    assert!(stream.deserialize_with_len::<Point>().unwrap().is_none());
+
+    // later values take up their message alone
+    let buffer = include_bytes!("reference/output/non_empty_values.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    assert_eq!(stream.deserialize_with_len::<bool>().unwrap().unwrap(), (true, 4));
+    assert_eq!(stream.deserialize_with_len::<u64>().unwrap().unwrap(), (42, 4));
+}
+
+/// A value along with the kind of type it was sent as.
+#[derive(Debug, PartialEq)]
+struct Sent {
+    kind: WireKind,
+    value: Option<Value>,
+}
+
+impl<'de> Deserialize<'de> for Sent {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Sent, D::Error> {
+        struct SentVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for SentVisitor {
+            type Value = Sent;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a gob value")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Sent, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let kind = seq.next_element::<WireKind>()?.unwrap();
+                // branch on the kind, leaving the rest to be skipped
+                let value = match kind {
+                    WireKind::Struct | WireKind::Map | WireKind::Array => seq.next_element()?,
+                    _ => None,
+                };
+                Ok(Sent { kind, value })
+            }
+        }
+
+        gob::de::deserialize_with_kind(deserializer, SentVisitor)
+    }
+}
+
+#[test]
+fn deserialize_with_kind() {
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+    let sent = Sent::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(sent.kind, WireKind::Struct);
+    assert_eq!(sent.value.unwrap().get("Y"), Some(&Value::Int(33)));
+
+    let buffer = include_bytes!("reference/output/map_non_empty.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let sent = stream.deserialize::<Sent>().unwrap().unwrap();
+    assert_eq!(sent.kind, WireKind::Map);
+    assert_eq!(sent.value.unwrap().get("foo"), Some(&Value::Bool(true)));
+
+    // as the fields of a struct, where the slice is left unread
+    #[derive(Deserialize, Debug)]
+    struct Nested {
+        #[serde(rename = "Groups")]
+        groups: Sent,
+        #[serde(rename = "Pair")]
+        pair: Sent,
+    }
+    let buffer = include_bytes!("reference/output/struct_with_nested_collections.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let nested = stream.deserialize::<Nested>().unwrap().unwrap();
+    assert_eq!(
+        nested.groups,
+        Sent {
+            kind: WireKind::Slice,
+            value: None,
+        }
+    );
+    assert_eq!(
+        nested.pair,
+        Sent {
+            kind: WireKind::Array,
+            value: Some(Value::Seq(vec![Value::Int(3), Value::Int(-4)])),
+        }
+    );
+
+    // and for every other type
+    let buffer = include_bytes!("reference/output/non_empty_values.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let mut kinds = Vec::new();
+    while let Some(sent) = stream.deserialize::<Sent>().unwrap() {
+        kinds.push(sent.kind);
     }
+    assert_eq!(
+        kinds,
+        &[
+            WireKind::Bool,
+            WireKind::Uint,
+            WireKind::Int,
+            WireKind::Float,
+            WireKind::String,
+            WireKind::Bytes,
+            WireKind::Slice,
+        ]
+    );
 }
@@ -0,0 +1,10 @@
+//! Attributes and field types `#[derive(GobSerialize)]` rejects at compile
+//! time.
+
+extern crate trybuild;
+
+#[test]
+fn rejected_attributes() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
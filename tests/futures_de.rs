@@ -0,0 +1,72 @@
+#![cfg(feature = "async")]
+
+extern crate futures;
+extern crate gob;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_gob;
+#[macro_use]
+extern crate serde_gob_derive;
+
+use std::io;
+
+use futures::executor::block_on;
+use futures::stream::{self, TryStreamExt};
+use gob::de::FuturesRead;
+use gob::{AsyncStreamDeserializer, StreamSerializer};
+
+#[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+struct Point {
+    #[serde(rename = "X")]
+    x: i64,
+    #[serde(rename = "Y")]
+    y: i64,
+}
+
+#[test]
+fn values_from_cursor() {
+    let points = (1..100).map(|i| Point { x: i, y: -i }).collect::<Vec<_>>();
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for point in &points {
+            stream.serialize(point).unwrap();
+        }
+    }
+
+    block_on(async {
+        let reader = FuturesRead(futures::io::Cursor::new(&buffer));
+        let mut stream = AsyncStreamDeserializer::new(reader);
+        for point in &points {
+            assert_eq!(&stream.next::<Point>().await.unwrap().unwrap(), point);
+        }
+        assert!(stream.next::<Point>().await.is_none());
+    });
+
+    // messages arriving in several pieces
+    block_on(async {
+        let chunks = buffer
+            .chunks(7)
+            .map(|chunk| Ok::<_, io::Error>(chunk.to_vec()))
+            .collect::<Vec<_>>();
+        let reader = FuturesRead(stream::iter(chunks).into_async_read());
+        let mut stream = AsyncStreamDeserializer::new(reader);
+        for point in &points {
+            assert_eq!(&stream.next::<Point>().await.unwrap().unwrap(), point);
+        }
+        assert!(stream.next::<Point>().await.is_none());
+    });
+}
+
+#[test]
+fn truncated_stream() {
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+
+    block_on(async {
+        let reader = FuturesRead(&buffer[..buffer.len() - 1]);
+        let mut stream = AsyncStreamDeserializer::new(reader);
+        assert!(stream.next::<Point>().await.unwrap().is_err());
+    });
+}
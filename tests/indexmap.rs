@@ -0,0 +1,45 @@
+#![cfg(feature = "indexmap")]
+
+extern crate gob;
+extern crate indexmap;
+
+use std::io::Cursor;
+
+use gob::{StreamDeserializer, StreamSerializer, Value};
+use indexmap::IndexMap;
+
+#[test]
+fn interface_map_keeps_wire_order() {
+    let buffer = include_bytes!("normal-session-2.bin");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let session = stream
+        .deserialize::<IndexMap<String, Value>>()
+        .unwrap()
+        .unwrap();
+    let keys = session.keys().map(String::as_str).collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        &["uid", "uname", "email", "userHasTwoFactorAuth", "_old_uid"]
+    );
+    assert_eq!(session["uname"], Value::String("dsotsen".into()));
+}
+
+#[test]
+fn round_trip_keeps_insertion_order() {
+    let map = ["zulu", "alpha", "mike"]
+        .iter()
+        .enumerate()
+        .map(|(i, key)| (key.to_string(), i as i64))
+        .collect::<IndexMap<_, _>>();
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&map)
+        .unwrap();
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    let decoded = stream
+        .deserialize::<IndexMap<String, i64>>()
+        .unwrap()
+        .unwrap();
+    assert!(decoded.keys().eq(map.keys()));
+}
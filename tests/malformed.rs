@@ -0,0 +1,144 @@
+//! Decoding malformed input must fail with an error, never panic.
+
+extern crate gob;
+#[macro_use]
+extern crate quickcheck;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+
+use gob::{Deserializer, StreamDeserializer};
+use serde::de::{Deserialize, DeserializeOwned, IgnoredAny};
+
+#[derive(Deserialize)]
+#[allow(unused)]
+struct Point {
+    #[serde(rename = "X")]
+    x: i64,
+    #[serde(rename = "Y")]
+    y: i64,
+}
+
+#[derive(Deserialize)]
+#[allow(unused)]
+struct Session {
+    uid: i64,
+    uname: String,
+}
+
+#[derive(Deserialize)]
+#[allow(unused)]
+enum Enum {
+    V1 { #[serde(rename = "Foo")] foo: bool },
+    V2(i64),
+}
+
+fn decode_slice<T: DeserializeOwned>(bytes: &[u8]) {
+    let _ = T::deserialize(Deserializer::from_slice(bytes));
+}
+
+fn decode_stream<T: DeserializeOwned>(bytes: &[u8]) {
+    let mut stream = StreamDeserializer::new(Cursor::new(bytes));
+    while let Ok(Some(_)) = stream.deserialize::<T>() {}
+}
+
+fn decode_all(bytes: &[u8]) {
+    decode_slice::<IgnoredAny>(bytes);
+    decode_slice::<i64>(bytes);
+    decode_slice::<String>(bytes);
+    decode_slice::<Vec<u64>>(bytes);
+    decode_slice::<Point>(bytes);
+    decode_slice::<Enum>(bytes);
+    decode_stream::<IgnoredAny>(bytes);
+    decode_stream::<Option<gob::go::GobError>>(bytes);
+    decode_stream::<HashMap<String, bool>>(bytes);
+    decode_stream::<Session>(bytes);
+    let _ = gob::read_schema(&mut Cursor::new(bytes));
+}
+
+fn fixtures() -> Vec<Vec<u8>> {
+    let mut fixtures = vec![include_bytes!("normal-session-2.bin").to_vec()];
+    for entry in fs::read_dir("tests/reference/output").unwrap() {
+        let bytes = fs::read(entry.unwrap().path()).unwrap();
+        // keep the exhaustive mutations below cheap
+        if bytes.len() <= 512 {
+            fixtures.push(bytes);
+        }
+    }
+    fixtures
+}
+
+#[test]
+fn truncated_fixtures() {
+    for fixture in fixtures() {
+        for len in 0..fixture.len() {
+            decode_all(&fixture[..len]);
+        }
+    }
+}
+
+#[test]
+fn mutated_fixtures() {
+    for fixture in fixtures() {
+        for pos in 0..fixture.len() {
+            for &byte in &[0x00, 0x01, 0x7f, 0x80, 0xf8, 0xff] {
+                let mut bytes = fixture.clone();
+                bytes[pos] = byte;
+                decode_all(&bytes);
+            }
+        }
+    }
+}
+
+#[test]
+fn self_referential_type_nested_deeply() {
+    // type T []T
+    let mut bytes = vec![13, 255, 129, 2, 1, 2, 255, 130, 0, 1, 255, 130, 0, 0];
+    // a T nested 100000 levels deep
+    bytes.extend_from_slice(&[253, 1, 134, 164, 255, 130, 0]);
+    bytes.extend(::std::iter::repeat(1).take(100_000));
+    bytes.push(0);
+
+    let err = IgnoredAny::deserialize(Deserializer::from_slice(&bytes)).unwrap_err();
+    assert!(err.to_string().contains("nested"));
+}
+
+#[test]
+fn empty_elements_over_byte_budget() {
+    // type A []int64; type B []A
+    let mut bytes = vec![12, 255, 129, 2, 1, 2, 255, 130, 0, 1, 4, 0, 0];
+    bytes.extend_from_slice(&[13, 255, 131, 2, 1, 2, 255, 132, 0, 1, 255, 130, 0, 0]);
+    // a B of 100000 empty As, one byte each but 24 bytes as a Vec
+    bytes.extend_from_slice(&[253, 1, 134, 167, 255, 132, 0, 253, 1, 134, 160]);
+    bytes.extend(::std::iter::repeat(0).take(100_000));
+
+    let mut stream = StreamDeserializer::new(Cursor::new(&bytes)).with_byte_budget(1 << 20);
+    let err = stream.deserialize::<Vec<Vec<i64>>>().unwrap_err();
+    assert!(err.to_string().contains("byte budget"), "{}", err);
+
+    let mut stream = StreamDeserializer::new(Cursor::new(&bytes)).with_byte_budget(4 << 20);
+    let value = stream.deserialize::<Vec<Vec<i64>>>().unwrap().unwrap();
+    assert_eq!(value.len(), 100_000);
+}
+
+quickcheck! {
+    fn random_bytes(bytes: Vec<u8>) -> bool {
+        decode_all(&bytes);
+        true
+    }
+}
+
+quickcheck! {
+    fn random_bytes_after_valid_definitions(bytes: Vec<u8>) -> bool {
+        let mut input = include_bytes!("reference/output/point_struct.gob").to_vec();
+        let def_len = input[0] as usize + 1;
+        input.truncate(def_len);
+        input.extend_from_slice(&bytes);
+        decode_all(&input);
+        true
+    }
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "json")]
+
+extern crate gob;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+use gob::Deserializer;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Event {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Payload", with = "gob::raw_json")]
+    payload: serde_json::Value,
+}
+
+#[test]
+fn struct_with_raw_json() {
+    let buffer = include_bytes!("reference/output/struct_with_raw_json.gob");
+    let event = Event::deserialize(Deserializer::from_slice(buffer)).unwrap();
+    assert_eq!(event.name, "push");
+    assert_eq!(
+        event.payload,
+        json!({
+            "ref": "refs/heads/main",
+            "commits": [{"id": "a1b2c3"}],
+            "forced": false,
+        })
+    );
+}
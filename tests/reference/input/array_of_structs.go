@@ -0,0 +1,21 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Point struct {
+	X int64
+	Y int64
+}
+
+type Path struct {
+	Name   string
+	Points [3]Point
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Path{Name: "zigzag", Points: [3]Point{{1, 2}, {0, 0}, {-3, 4}}})
+}
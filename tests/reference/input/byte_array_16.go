@@ -0,0 +1,20 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Record struct {
+	ID [16]byte
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	id := [16]byte{
+		0x6b, 0xa7, 0xb8, 0x10, 0x9d, 0xad, 0x11, 0xd1,
+		0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8,
+	}
+	enc.Encode(id)
+	enc.Encode(Record{ID: id})
+}
@@ -0,0 +1,16 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+func main() {
+	var blob = make([]byte, 0x10000)
+	for i := range blob {
+		blob[i] = byte(i % 251)
+	}
+
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(blob)
+}
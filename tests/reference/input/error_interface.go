@@ -0,0 +1,26 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type wrappedError struct {
+	Msg string
+}
+
+func (e wrappedError) Error() string {
+	return e.Msg
+}
+
+type Result struct {
+	Err error
+}
+
+func main() {
+	gob.Register(wrappedError{})
+
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Result{Err: wrappedError{"file not found"}})
+	enc.Encode(Result{})
+}
@@ -0,0 +1,30 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Box[T any] struct {
+	Value T
+}
+
+type Pair[K comparable, V any] struct {
+	Key   K
+	Value V
+}
+
+func main() {
+	// registered as main.Box[int], main.Pair[string,float64] and
+	// main.Box[interface {}], after their reflect names
+	gob.Register(Box[int]{})
+	gob.Register(Pair[string, float64]{})
+	gob.Register(Box[any]{})
+
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode([]interface{}{
+		Box[int]{7},
+		Pair[string, float64]{"pi", 3.14},
+		Box[any]{5},
+	})
+}
@@ -0,0 +1,24 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Point struct {
+	X, Y int
+}
+
+func main() {
+	gob.Register(Point{})
+
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(map[interface{}]interface{}{
+		"Ratio":  0.5,
+		"Scale":  float32(0.25),
+		"Count":  3,
+		"Name":   "gauge",
+		"Origin": Point{1, -2},
+		"Unit":   nil,
+	})
+}
@@ -0,0 +1,21 @@
+package main
+
+import (
+	"encoding/gob"
+	"math"
+	"os"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(map[interface{}]interface{}{
+		"x": int64(-42),
+		"y": int64(-1),
+		"z": int64(math.MinInt64),
+	})
+	enc.Encode(map[string]interface{}{
+		"x": int64(-42),
+		"y": int64(-1),
+		"z": int64(math.MinInt64),
+	})
+}
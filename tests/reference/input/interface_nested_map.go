@@ -0,0 +1,24 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+func main() {
+	gob.Register(map[string]interface{}{})
+
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(map[string]interface{}{
+		"APP_NAME": "Gitea",
+		"server": map[string]interface{}{
+			"DOMAIN":    "git.example.com",
+			"HTTP_PORT": 3000,
+		},
+		"database": map[string]interface{}{
+			"DB_TYPE": "sqlite3",
+			"LOG_SQL": false,
+			"PATH":    nil,
+		},
+	})
+}
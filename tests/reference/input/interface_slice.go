@@ -0,0 +1,22 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Circle struct {
+	Radius float64
+}
+
+type Square struct {
+	Side int64
+}
+
+func main() {
+	gob.Register(Circle{})
+	gob.Register(Square{})
+
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode([]interface{}{Circle{1.5}, Square{3}, nil, Circle{0.25}})
+}
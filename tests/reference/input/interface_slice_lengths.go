@@ -0,0 +1,13 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode([]interface{}{})
+	enc.Encode([]interface{}{"one"})
+	enc.Encode([]interface{}{int64(-1), "two", 3.5, true, nil})
+}
@@ -0,0 +1,17 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	// a pointer to an interface sends the interface itself
+	var v interface{} = true
+	enc.Encode(&v)
+	v = 2.5
+	enc.Encode(&v)
+	v = int64(-3)
+	enc.Encode(&v)
+}
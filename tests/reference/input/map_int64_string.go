@@ -0,0 +1,11 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(map[int64]string{3: "three", -1: "minus one", 2: "two"})
+}
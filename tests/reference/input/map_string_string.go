@@ -0,0 +1,13 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(map[string]string{"APP_NAME": "Gitea", "RUN_USER": "git"})
+	enc.Encode(map[string]string{"": "root", "ROOT_URL": ""})
+	enc.Encode(map[string]string{})
+}
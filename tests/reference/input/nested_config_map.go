@@ -0,0 +1,28 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Config struct {
+	Host   string
+	Port   int
+	Tags   []string
+	Limits map[string]int
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(map[string][]map[string]Config{
+		"prod": {
+			{
+				"web": {Host: "web1", Port: 443, Tags: []string{"a", "b"}, Limits: map[string]int{"rps": 100}},
+				"db":  {Host: "db1", Port: 5432},
+			},
+			{},
+		},
+		"dev":     {},
+		"staging": {{"zero": {}}},
+	})
+}
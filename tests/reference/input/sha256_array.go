@@ -0,0 +1,19 @@
+package main
+
+import (
+	"crypto/sha256"
+	"encoding/gob"
+	"os"
+)
+
+type Blob struct {
+	Name string
+	Sum  [32]byte
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	sum := sha256.Sum256([]byte("hello"))
+	enc.Encode(sum)
+	enc.Encode(Blob{Name: "hello.txt", Sum: sum})
+}
@@ -0,0 +1,19 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Inner struct {
+	V int64
+}
+
+type Outer struct {
+	Inner Inner
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Outer{Inner: Inner{V: 5}})
+}
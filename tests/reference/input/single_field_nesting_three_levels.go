@@ -0,0 +1,23 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Leaf struct {
+	V int64
+}
+
+type Middle struct {
+	Leaf Leaf
+}
+
+type Top struct {
+	Middle Middle
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Top{Middle: Middle{Leaf: Leaf{V: -3}}})
+}
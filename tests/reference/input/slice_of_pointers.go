@@ -0,0 +1,18 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type User struct {
+	Name string
+	Age  int
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	// gob refuses nil elements with "encodeArray: nil element", so every
+	// pointer is set
+	enc.Encode([]*User{{Name: "alice", Age: 30}, {Name: "bob"}, {}})
+}
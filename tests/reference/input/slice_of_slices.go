@@ -0,0 +1,11 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode([][]int{{1, 2}, {}, {3}})
+}
@@ -0,0 +1,21 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type User struct {
+	Name  string
+	Age   int64
+	Admin bool
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode([]User{
+		{Name: "alice", Age: 31, Admin: true},
+		{Name: "bob", Age: 0, Admin: false},
+		{Name: "", Age: 47, Admin: false},
+	})
+}
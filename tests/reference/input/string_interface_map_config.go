@@ -0,0 +1,18 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(map[string]interface{}{
+		"APP_NAME":     "Gitea",
+		"HTTP_PORT":    3000,
+		"OFFLINE_MODE": true,
+		"RATE":         0.5,
+		"ADMINS":       []string{"root", "ops"},
+		"SECRET":       nil,
+	})
+}
@@ -0,0 +1,17 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Blob struct {
+	Data []byte
+	Text string
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Blob{Data: []byte{0xff, 0x00, 'o', 'k'}, Text: "ok"})
+	enc.Encode(Blob{Data: []byte("ünï\x00"), Text: "\xc3\xbcn\xc3\xaf\x00 ✓"})
+}
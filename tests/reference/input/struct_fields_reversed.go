@@ -0,0 +1,17 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Person struct {
+	Age   int64
+	Email string
+	Name  string
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Person{Age: 30, Email: "ann@example.com", Name: "Ann"})
+}
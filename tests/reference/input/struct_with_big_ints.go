@@ -0,0 +1,19 @@
+package main
+
+import (
+	"encoding/gob"
+	"math/big"
+	"os"
+)
+
+type Account struct {
+	Balance *big.Int
+	Debt    *big.Int
+	Fee     *big.Int
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	balance, _ := new(big.Int).SetString("12345678901234567890123", 10)
+	enc.Encode(Account{Balance: balance, Debt: big.NewInt(-42)})
+}
@@ -0,0 +1,16 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Blob struct {
+	Name string
+	Data []byte
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Blob{Name: "blob", Data: []byte{0xde, 0xad, 0xbe, 0xef}})
+}
@@ -0,0 +1,18 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Signal struct {
+	C complex128
+	F float64
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Signal{C: 0, F: 1})
+	enc.Encode(Signal{C: 1 - 2i})
+	enc.Encode(Signal{C: 0.5i})
+}
@@ -0,0 +1,29 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Pointers struct {
+	A **string
+	B **string
+	C **string
+	D **int64
+}
+
+func main() {
+	var x = "x"
+	var px = &x
+	var empty = ""
+	var pempty = &empty
+	var nilp *string
+	var n int64 = 7
+	var pn = &n
+
+	var enc = gob.NewEncoder(os.Stdout)
+	// A is nil, B points to a nil pointer, C to a pointer to an empty
+	// string: all three are omitted
+	enc.Encode(Pointers{A: nil, B: &nilp, C: &pempty, D: &pn})
+	enc.Encode(Pointers{A: &px})
+}
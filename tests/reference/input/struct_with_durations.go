@@ -0,0 +1,18 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+	"time"
+)
+
+type Job struct {
+	Name    string
+	Timeout time.Duration
+	Backoff time.Duration
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Job{Name: "build", Timeout: 5 * time.Second, Backoff: -1500 * time.Millisecond})
+}
@@ -0,0 +1,21 @@
+package main
+
+import (
+	"encoding/gob"
+	"math"
+	"os"
+)
+
+type Floats struct {
+	F float64
+	G float32
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	negZero := math.Copysign(0, -1)
+	enc.Encode(Floats{F: 0, G: 0})
+	enc.Encode(Floats{F: negZero, G: float32(negZero)})
+	enc.Encode(Floats{F: 1.5})
+	enc.Encode(Floats{G: -2.25})
+}
@@ -0,0 +1,16 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Widths struct {
+	Single float32
+	Double float64
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Widths{Single: 0.1, Double: 0.1})
+}
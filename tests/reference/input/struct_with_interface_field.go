@@ -0,0 +1,18 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Event struct {
+	Name    string
+	Payload interface{}
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Event{"greeting", "hello"})
+	enc.Encode(Event{"answer", 42})
+	enc.Encode(Event{"empty", nil})
+}
@@ -0,0 +1,21 @@
+package main
+
+import (
+	"encoding/gob"
+	"net"
+	"os"
+)
+
+type Route struct {
+	Dst     *net.IPNet
+	Gateway net.IP
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	_, dst, _ := net.ParseCIDR("10.20.0.0/14")
+	enc.Encode(Route{Dst: dst, Gateway: net.ParseIP("10.20.0.1")})
+	_, dst, _ = net.ParseCIDR("fd00::/64")
+	enc.Encode(Route{Dst: dst, Gateway: net.ParseIP("fd00::1")})
+	enc.Encode(net.ParseIP("2001:db8::1"))
+}
@@ -0,0 +1,16 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Config struct {
+	Name   string
+	Counts map[string]int
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Config{Name: "foo", Counts: map[string]int{"a": 1, "b": 2}})
+}
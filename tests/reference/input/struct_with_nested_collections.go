@@ -0,0 +1,19 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Nested struct {
+	Groups []map[string][]int
+	Pair   [2]int
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Nested{
+		Groups: []map[string][]int{{"a": {1, 2}, "b": {}}, {}},
+		Pair:   [2]int{3, -4},
+	})
+}
@@ -0,0 +1,21 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Record struct {
+	A int64
+	B *int64
+	C string
+	D int64
+	E *string
+	F *int64
+}
+
+func main() {
+	var e = "x"
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Record{D: 5, E: &e})
+}
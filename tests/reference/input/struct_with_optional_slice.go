@@ -0,0 +1,18 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Bag struct {
+	Name  string
+	Items []int
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Bag{Name: "nil"})
+	enc.Encode(Bag{Name: "empty", Items: []int{}})
+	enc.Encode(Bag{Name: "full", Items: []int{1, 2}})
+}
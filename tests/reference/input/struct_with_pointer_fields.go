@@ -0,0 +1,20 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Pointers struct {
+	A *int
+	B *string
+	C int
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	x, seven, zero := "x", 7, 0
+	enc.Encode(Pointers{A: nil, B: &x, C: 3})
+	enc.Encode(Pointers{A: &seven, B: nil, C: 0})
+	enc.Encode(Pointers{A: &zero})
+}
@@ -0,0 +1,20 @@
+package main
+
+import (
+	"encoding/gob"
+	"encoding/json"
+	"os"
+)
+
+type Event struct {
+	Name    string
+	Payload json.RawMessage
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Event{
+		Name:    "push",
+		Payload: json.RawMessage(`{"ref":"refs/heads/main","commits":[{"id":"a1b2c3"}],"forced":false}`),
+	})
+}
@@ -0,0 +1,18 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+// gob ignores the tags and sends the Go field names
+type Response struct {
+	UserID     int64  `json:"user_id"`
+	HTTPStatus int64  `json:"http_status"`
+	URL        string `json:"url"`
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Response{UserID: 42, HTTPStatus: 404, URL: "https://example.com/u/42"})
+}
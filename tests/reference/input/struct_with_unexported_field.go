@@ -0,0 +1,18 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Session struct {
+	User   string
+	token  string
+	Expiry int64
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	// unexported fields are left out of the type definition
+	enc.Encode(Session{User: "gopher", token: "secret", Expiry: 1700000000})
+}
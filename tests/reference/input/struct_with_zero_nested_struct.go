@@ -0,0 +1,23 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Inner struct {
+	V int64
+	S string
+}
+
+type Outer struct {
+	Name  string
+	Inner Inner
+	N     int64
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	// struct fields are sent even if all their fields are zero
+	enc.Encode(Outer{Name: "outer", N: 3})
+}
@@ -0,0 +1,18 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Shape struct {
+	Kind   string
+	Radius float64
+	Side   int64
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Shape{Kind: "circle", Radius: 1.5})
+	enc.Encode(Shape{Kind: "square", Side: 3})
+}
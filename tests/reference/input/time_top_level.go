@@ -0,0 +1,12 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+	"time"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(time.Date(2009, time.November, 10, 23, 0, 0, 5, time.UTC))
+}
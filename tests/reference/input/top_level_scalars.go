@@ -0,0 +1,17 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(int64(42))
+	enc.Encode(int64(-1000))
+	enc.Encode(uint64(300))
+	enc.Encode(true)
+	enc.Encode(float64(-2.5))
+	enc.Encode("hi")
+	enc.Encode([]byte("raw"))
+}
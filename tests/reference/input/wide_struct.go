@@ -0,0 +1,214 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Wide struct {
+	F0 int64
+	F1 int64
+	F2 int64
+	F3 int64
+	F4 int64
+	F5 int64
+	F6 int64
+	F7 int64
+	F8 int64
+	F9 int64
+	F10 int64
+	F11 int64
+	F12 int64
+	F13 int64
+	F14 int64
+	F15 int64
+	F16 int64
+	F17 int64
+	F18 int64
+	F19 int64
+	F20 int64
+	F21 int64
+	F22 int64
+	F23 int64
+	F24 int64
+	F25 int64
+	F26 int64
+	F27 int64
+	F28 int64
+	F29 int64
+	F30 int64
+	F31 int64
+	F32 int64
+	F33 int64
+	F34 int64
+	F35 int64
+	F36 int64
+	F37 int64
+	F38 int64
+	F39 int64
+	F40 int64
+	F41 int64
+	F42 int64
+	F43 int64
+	F44 int64
+	F45 int64
+	F46 int64
+	F47 int64
+	F48 int64
+	F49 int64
+	F50 int64
+	F51 int64
+	F52 int64
+	F53 int64
+	F54 int64
+	F55 int64
+	F56 int64
+	F57 int64
+	F58 int64
+	F59 int64
+	F60 int64
+	F61 int64
+	F62 int64
+	F63 int64
+	F64 int64
+	F65 int64
+	F66 int64
+	F67 int64
+	F68 int64
+	F69 int64
+	F70 int64
+	F71 int64
+	F72 int64
+	F73 int64
+	F74 int64
+	F75 int64
+	F76 int64
+	F77 int64
+	F78 int64
+	F79 int64
+	F80 int64
+	F81 int64
+	F82 int64
+	F83 int64
+	F84 int64
+	F85 int64
+	F86 int64
+	F87 int64
+	F88 int64
+	F89 int64
+	F90 int64
+	F91 int64
+	F92 int64
+	F93 int64
+	F94 int64
+	F95 int64
+	F96 int64
+	F97 int64
+	F98 int64
+	F99 int64
+	F100 int64
+	F101 int64
+	F102 int64
+	F103 int64
+	F104 int64
+	F105 int64
+	F106 int64
+	F107 int64
+	F108 int64
+	F109 int64
+	F110 int64
+	F111 int64
+	F112 int64
+	F113 int64
+	F114 int64
+	F115 int64
+	F116 int64
+	F117 int64
+	F118 int64
+	F119 int64
+	F120 int64
+	F121 int64
+	F122 int64
+	F123 int64
+	F124 int64
+	F125 int64
+	F126 int64
+	F127 int64
+	F128 int64
+	F129 int64
+	F130 int64
+	F131 int64
+	F132 int64
+	F133 int64
+	F134 int64
+	F135 int64
+	F136 int64
+	F137 int64
+	F138 int64
+	F139 int64
+	F140 int64
+	F141 int64
+	F142 int64
+	F143 int64
+	F144 int64
+	F145 int64
+	F146 int64
+	F147 int64
+	F148 int64
+	F149 int64
+	F150 int64
+	F151 int64
+	F152 int64
+	F153 int64
+	F154 int64
+	F155 int64
+	F156 int64
+	F157 int64
+	F158 int64
+	F159 int64
+	F160 int64
+	F161 int64
+	F162 int64
+	F163 int64
+	F164 int64
+	F165 int64
+	F166 int64
+	F167 int64
+	F168 int64
+	F169 int64
+	F170 int64
+	F171 int64
+	F172 int64
+	F173 int64
+	F174 int64
+	F175 int64
+	F176 int64
+	F177 int64
+	F178 int64
+	F179 int64
+	F180 int64
+	F181 int64
+	F182 int64
+	F183 int64
+	F184 int64
+	F185 int64
+	F186 int64
+	F187 int64
+	F188 int64
+	F189 int64
+	F190 int64
+	F191 int64
+	F192 int64
+	F193 int64
+	F194 int64
+	F195 int64
+	F196 int64
+	F197 int64
+	F198 int64
+	F199 int64
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Wide{F0: 1, F190: 7})
+}
@@ -0,0 +1,119 @@
+//! Randomized round trips: whatever is serialized must decode to an equal
+//! value.
+
+extern crate gob;
+#[macro_use]
+extern crate quickcheck;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_gob;
+#[macro_use]
+extern crate serde_gob_derive;
+
+use std::collections::{BTreeMap, HashMap};
+
+use gob::{Deserializer, StreamSerializer};
+use quickcheck::{Arbitrary, Gen};
+use serde::de::DeserializeOwned;
+use serde_gob::GobSerialize;
+
+/// Serializes `value` on its own stream and decodes it again, reporting
+/// the value that did not survive.
+///
+/// Values need to be representable in gob for this to hold: Go sends
+/// `Some` of a zero value like `None`, and omitted struct fields come back
+/// as their defaults, so fields need `#[serde(default)]`.
+fn round_trips<T>(value: &T) -> bool
+where
+    T: GobSerialize + DeserializeOwned + PartialEq,
+{
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(value)
+        .unwrap();
+    let decoded = T::deserialize(Deserializer::from_slice(&buffer)).unwrap();
+    &decoded == value
+}
+
+#[derive(Serialize, Deserialize, GobSerialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+struct User {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Age")]
+    age: i64,
+    #[serde(rename = "Admin")]
+    admin: bool,
+    #[serde(rename = "Tags")]
+    tags: Vec<String>,
+    #[serde(rename = "Scores")]
+    scores: HashMap<String, u32>,
+}
+
+impl Arbitrary for User {
+    fn arbitrary<G: Gen>(g: &mut G) -> User {
+        User {
+            name: String::arbitrary(g),
+            age: i64::arbitrary(g),
+            admin: bool::arbitrary(g),
+            tags: Vec::arbitrary(g),
+            scores: HashMap::arbitrary(g),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, GobSerialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+struct Team {
+    #[serde(rename = "Lead")]
+    lead: User,
+    #[serde(rename = "Members")]
+    members: Vec<User>,
+    #[serde(rename = "Budget")]
+    budget: f64,
+    #[serde(rename = "Parent")]
+    parent: Option<String>,
+}
+
+impl Arbitrary for Team {
+    fn arbitrary<G: Gen>(g: &mut G) -> Team {
+        Team {
+            lead: User::arbitrary(g),
+            members: Vec::arbitrary(g),
+            budget: f64::arbitrary(g),
+            // gob cannot tell `Some("")` from `None`
+            parent: Option::<String>::arbitrary(g).filter(|parent| !parent.is_empty()),
+        }
+    }
+}
+
+quickcheck! {
+    fn user(value: User) -> bool {
+        round_trips(&value)
+    }
+
+    fn team(value: Team) -> bool {
+        round_trips(&value)
+    }
+
+    fn users_by_id(value: BTreeMap<u64, User>) -> bool {
+        round_trips(&value)
+    }
+
+    fn scalars(int: i64, uint: u64, float: f64, string: String, flag: bool) -> bool {
+        round_trips(&int)
+            && round_trips(&uint)
+            && round_trips(&float)
+            && round_trips(&string)
+            && round_trips(&flag)
+    }
+
+    fn nested_collections(value: HashMap<String, Vec<i32>>) -> bool {
+        round_trips(&value)
+    }
+
+    fn small_ints(value: BTreeMap<i8, Vec<u16>>) -> bool {
+        round_trips(&value)
+    }
+}
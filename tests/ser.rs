@@ -7,9 +7,23 @@ extern crate serde_gob;
 #[macro_use]
 extern crate serde_gob_derive;
 
-use std::collections::BTreeMap;
+mod common;
 
-use gob::StreamSerializer;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasherDefault;
+use std::io::Cursor;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::num::{Saturating, Wrapping};
+
+use gob::error::SchemaMismatch;
+use gob::go::{Array, IPNet, IP};
+use gob::ser::{Framing, TypeId};
+use gob::{StreamDeserializer, StreamSerializer};
+use serde_gob::GobSerialize;
+use serde_bytes::{ByteBuf, Bytes};
+
+use common::assert_gob_eq;
 
 #[test]
 fn bool_true() {
@@ -220,6 +234,58 @@ fn f32_zero() {
     assert_eq!(buffer, &[3, 8, 0, 0]);
 }
 
+#[test]
+fn f32_pos() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&1.5f32).unwrap();
+    }
+    // what Go sends for float32(1.5): there is no narrower encoding, a
+    // float32 goes out as the float64 it widens to
+    assert_eq!(buffer, &[5, 8, 0, 254, 248, 63]);
+
+    for &value in &[1.5f32, 0.1, -3.4e38, f32::MIN_POSITIVE] {
+        let mut buffer = Vec::new();
+        StreamSerializer::new_with_write(&mut buffer)
+            .serialize(&value)
+            .unwrap();
+        let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+        assert_eq!(stream.deserialize::<f32>().unwrap(), Some(value));
+    }
+}
+
+#[test]
+fn wrapping_u32() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Counter {
+        #[serde(rename = "Hits")]
+        hits: Wrapping<u32>,
+        #[serde(rename = "Budget")]
+        budget: Saturating<i16>,
+    }
+
+    // sent like the integer it wraps
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&Wrapping(200u32))
+        .unwrap();
+    assert_eq!(buffer, &[4, 6, 0, 255, 200]);
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize::<Wrapping<u32>>().unwrap(), Some(Wrapping(200)));
+
+    let counter = Counter {
+        hits: Wrapping(u32::MAX) + Wrapping(2),
+        budget: Saturating(i16::MIN) - Saturating(1),
+    };
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&counter)
+        .unwrap();
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize::<Counter>().unwrap(), Some(counter));
+}
+
 #[test]
 fn f64_zero() {
     let mut buffer = Vec::new();
@@ -270,25 +336,62 @@ fn char_unicode() {
     assert_eq!(buffer, &[6, 4, 0, 253, 1, 21, 60]);
 }
 
-// #[test]
-// fn bytes_empty() {
-//     let mut buffer = Vec::new();
-//     {
-//         let mut stream = StreamSerializer::new_with_write(&mut buffer);
-//         stream.serialize(&Bytes::new(&[])).unwrap();
-//     }
-//     assert_eq!(buffer, &[3, 10, 0, 0]);
-// }
+#[test]
+fn char_round_trip() {
+    let values = ['a', '🦀'];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for value in &values {
+            stream.serialize(value).unwrap();
+        }
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    for value in &values {
+        assert_eq!(&stream.deserialize::<char>().unwrap().unwrap(), value);
+    }
+    assert!(stream.deserialize::<char>().unwrap().is_none());
+}
+
+#[test]
+fn bytes_empty() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Bytes::new(&[])).unwrap();
+    }
+    assert_eq!(buffer, &[3, 10, 0, 0]);
+}
+
+#[test]
+fn bytes_non_empty() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Bytes::new(&[1, 2, 3, 4])).unwrap();
+    }
+    assert_eq!(buffer, &[7, 10, 0, 4, 1, 2, 3, 4]);
+}
+
+#[test]
+fn bytes_large() {
+    let blob = (0..0x10000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&ByteBuf::from(blob.clone())).unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/bytes_large.gob").as_ref()
+    );
 
-// #[test]
-// fn bytes_non_empty() {
-//     let mut buffer = Vec::new();
-//     {
-//         let mut stream = StreamSerializer::new_with_write(&mut buffer);
-//         stream.serialize(&Bytes::new(&[1, 2, 3, 4])).unwrap();
-//     }
-//     assert_eq!(buffer, &[7, 10, 0, 4, 1, 2, 3, 4]);
-// }
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    let decoded = stream.deserialize::<ByteBuf>().unwrap().unwrap();
+    assert_eq!(&*decoded, &blob[..]);
+}
 
 #[test]
 fn str_empty() {
@@ -434,9 +537,9 @@ fn point_struct() {
         let mut stream = StreamSerializer::new_with_write(&mut buffer);
         stream.serialize(&Point { x: 22, y: 33 }).unwrap();
     }
-    assert_eq!(
-        buffer,
-        include_bytes!("reference/output/point_struct.gob").as_ref()
+    assert_gob_eq(
+        &buffer,
+        include_bytes!("reference/output/point_struct.gob"),
     );
 }
 
@@ -447,9 +550,74 @@ fn point_struct_skip_x() {
         let mut stream = StreamSerializer::new_with_write(&mut buffer);
         stream.serialize(&Point { x: 0, y: 42 }).unwrap();
     }
-    assert_eq!(
-        buffer,
-        include_bytes!("reference/output/point_struct_skip_x.gob").as_ref()
+    assert_gob_eq(
+        &buffer,
+        include_bytes!("reference/output/point_struct_skip_x.gob"),
+    );
+}
+
+#[test]
+fn float_fields_omitted_when_zero() {
+    #[derive(Serialize, GobSerialize)]
+    struct Floats {
+        #[serde(rename = "F")]
+        f: f64,
+        #[serde(rename = "G")]
+        g: f32,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Floats { f: 0.0, g: 0.0 }).unwrap();
+        // Go compares with zero, which negative zero equals
+        stream.serialize(&Floats { f: -0.0, g: -0.0 }).unwrap();
+        stream.serialize(&Floats { f: 1.5, g: 0.0 }).unwrap();
+        stream.serialize(&Floats { f: 0.0, g: -2.25 }).unwrap();
+    }
+    assert_gob_eq(
+        &buffer,
+        include_bytes!("reference/output/struct_with_float_fields.gob"),
+    );
+}
+
+#[test]
+fn byte_array_16() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Record {
+        #[serde(rename = "ID")]
+        id: [u8; 16],
+    }
+
+    let id = [
+        0x6b, 0xa7, 0xb8, 0x10, 0x9d, 0xad, 0x11, 0xd1, 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30,
+        0xc8,
+    ];
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&id).unwrap();
+        stream.serialize(&Record { id }).unwrap();
+    }
+    // an array of uints like Go's `[16]byte`, not a byte string
+    assert_gob_eq(&buffer, include_bytes!("reference/output/byte_array_16.gob"));
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(stream.deserialize::<[u8; 16]>().unwrap(), Some(id));
+    assert_eq!(stream.deserialize::<Record>().unwrap(), Some(Record { id }));
+}
+
+#[test]
+#[should_panic(expected = "value.Y: Rust sent Int(34), Go sent Int(33)")]
+fn point_struct_mismatch_names_field() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Point { x: 22, y: 34 }).unwrap();
+    }
+    assert_gob_eq(
+        &buffer,
+        include_bytes!("reference/output/point_struct.gob"),
     );
 }
 
@@ -473,97 +641,1495 @@ fn bool_struct() {
 }
 
 #[test]
-fn enum_with_newtype_variants_and_external_tags() {
-    #[derive(Serialize, GobSerialize)]
-    enum Enum {
-        #[serde(rename = "Var1")]
-        #[allow(unused)]
-        V1(bool),
-        #[serde(rename = "Var2")]
-        V2(i64),
-        #[serde(rename = "Var3")]
-        #[allow(unused)]
-        V3(String),
+fn slice_of_structs() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct User {
+        #[serde(rename = "Name", default)]
+        name: String,
+        #[serde(rename = "Age", default)]
+        age: i64,
+        #[serde(rename = "Admin", default)]
+        admin: bool,
     }
 
+    let users = vec![
+        User {
+            name: "alice".to_owned(),
+            age: 31,
+            admin: true,
+        },
+        User {
+            name: "bob".to_owned(),
+            age: 0,
+            admin: false,
+        },
+        User {
+            name: "".to_owned(),
+            age: 47,
+            admin: false,
+        },
+    ];
+
     let mut buffer = Vec::new();
     {
         let mut stream = StreamSerializer::new_with_write(&mut buffer);
-        stream.serialize(&Enum::V2(42)).unwrap();
+        stream.serialize(&users).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize::<Vec<User>>().unwrap().unwrap(), users);
+
+    let expected = include_bytes!("reference/output/slice_of_structs.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(&expected[..]));
+    assert_eq!(stream.deserialize::<Vec<User>>().unwrap().unwrap(), users);
+
+    // type ids are assigned in a different order than Go does, but the value
+    // message past its length and two-byte type id is the same
+    let value_body = |buffer: &[u8]| {
+        let ids = message_type_ids(buffer);
+        let mut rest = buffer;
+        for _ in 1..ids.len() {
+            rest = &rest[1 + rest[0] as usize..];
+        }
+        rest[3..].to_vec()
+    };
+    assert_eq!(value_body(&buffer), value_body(&expected[..]));
+}
+
+#[test]
+fn zero_nested_struct_is_sent() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, Default, PartialEq)]
+    #[serde(default)]
+    struct Inner {
+        #[serde(rename = "V")]
+        v: i64,
+        #[serde(rename = "S")]
+        s: String,
+    }
+
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Outer {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Inner")]
+        inner: Inner,
+        #[serde(rename = "N")]
+        n: i64,
+    }
+
+    let outer = Outer {
+        name: "outer".to_owned(),
+        inner: Inner::default(),
+        n: 3,
+    };
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&outer)
+        .unwrap();
+
+    // unlike fields of other types, Go sends a struct field even if all of
+    // its fields are zero, as an empty struct
+    let expected = include_bytes!("reference/output/struct_with_zero_nested_struct.gob");
+    // type ids are assigned in a different order than Go does, so only the
+    // value message past its two-byte type id is the same
+    let body = &expected[expected.len() - 12..];
+    assert!(buffer.ends_with(body));
+    assert!(body.ends_with(&[0x01, 0x00, 0x01, 0x06, 0x00]));
+
+    let mut stream = StreamDeserializer::new(Cursor::new(&expected[..]));
+    assert_eq!(stream.deserialize::<Outer>().unwrap().unwrap(), outer);
+}
+
+#[test]
+fn serialize_append_to_log() {
+    // a log that already holds a message
+    let mut log = Vec::new();
+    gob::to_writer(&mut log, &Point { x: 1, y: 2 }).unwrap();
+    let first = log.clone();
+    gob::to_writer(&mut log, &Point { x: 3, y: 4 }).unwrap();
+    assert!(log.starts_with(&first));
+    assert!(log.len() > first.len());
+
+    let mut log = Vec::new();
+    let mut stream = StreamSerializer::new_with_buffer();
+    for i in 0..3 {
+        stream.serialize_append(&mut log, &Point { x: i, y: -i }).unwrap();
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Decoded {
+        #[serde(rename = "X", default)]
+        x: i64,
+        #[serde(rename = "Y", default)]
+        y: i64,
+    }
+    let mut stream = StreamDeserializer::new(Cursor::new(&log));
+    for i in 0..3 {
+        let point = stream.deserialize::<Decoded>().unwrap().unwrap();
+        assert_eq!(point, Decoded { x: i, y: -i });
+    }
+    assert!(stream.deserialize::<Decoded>().unwrap().is_none());
+}
+
+#[test]
+fn serialized_size_matches_output() {
+    fn check<T: GobSerialize>(value: &T) {
+        let mut buffer = Vec::new();
+        gob::to_writer(&mut buffer, value).unwrap();
+        assert_eq!(gob::serialized_size(value).unwrap(), buffer.len());
+    }
+
+    check(&0u64);
+    check(&u64::MAX);
+    check(&"gopher".to_owned());
+    check(&Point { x: 22, y: 33 });
+    check(&(0..100).map(|i| Point { x: i, y: -i }).collect::<Vec<_>>());
+    check(&[("a".to_owned(), 1i64)].iter().cloned().collect::<BTreeMap<_, _>>());
+}
+
+#[test]
+fn serialize_slice_matches_serialize() {
+    #[derive(Serialize, GobSerialize, Clone)]
+    struct Entry {
+        #[serde(rename = "Key")]
+        key: String,
+        #[serde(rename = "Count")]
+        count: u64,
+    }
+
+    // enough entries to be written in several chunks
+    let entries = (0..5000)
+        .map(|n| Entry {
+            key: format!("entry{}", n),
+            count: n * n,
+        })
+        .collect::<Vec<_>>();
+
+    for framing in [Framing::Messages, Framing::LengthDelimited] {
+        let mut expected = Vec::new();
+        {
+            let mut stream = StreamSerializer::builder()
+                .framing(framing)
+                .build(&mut expected);
+            stream.serialize(&entries).unwrap();
+            stream.serialize(&entries[..3].to_vec()).unwrap();
+            stream.serialize(&Vec::<Entry>::new()).unwrap();
+        }
+
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamSerializer::builder().framing(framing).build(&mut buffer);
+            stream.serialize_slice(&entries).unwrap();
+            stream.serialize_slice(&entries[..3]).unwrap();
+            stream.serialize_slice::<Entry>(&[]).unwrap();
+        }
+        assert_eq!(buffer, expected);
+    }
+}
+
+#[test]
+fn serialize_slice_with_changing_elements() {
+    use std::cell::Cell;
+
+    // encodes to a longer string each time it is serialized
+    struct Counter(Cell<usize>);
+
+    impl serde::Serialize for Counter {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.set(self.0.get() + 1);
+            serializer.serialize_str(&"x".repeat(self.0.get()))
+        }
+    }
+
+    impl GobSerialize for Counter {
+        fn schema_register<S: serde_gob::Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+            String::schema_register(schema)
+        }
+    }
+
+    let mut buffer = Vec::new();
+    let mut stream = StreamSerializer::new_with_write(&mut buffer);
+    let err = stream.serialize_slice(&[Counter(Cell::new(0))]).unwrap_err();
+    assert!(err.to_string().contains("encoded differently"), "{}", err);
+}
+
+#[test]
+fn serde_skip_and_zero_omission_combined() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Record {
+        // omitted by gob as a zero value
+        #[serde(rename = "A", default)]
+        a: i64,
+        // skipped by serde
+        #[serde(rename = "B", skip_serializing_if = "Option::is_none")]
+        b: Option<i64>,
+        #[serde(rename = "C", default)]
+        c: String,
+        #[serde(rename = "D", default)]
+        d: i64,
+        #[serde(rename = "E", skip_serializing_if = "Option::is_none")]
+        e: Option<String>,
+        #[serde(rename = "F", skip_serializing_if = "Option::is_none")]
+        f: Option<i64>,
+    }
+
+    let record = Record {
+        a: 0,
+        b: None,
+        c: String::new(),
+        d: 5,
+        e: Some("x".to_owned()),
+        f: None,
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&record).unwrap();
     }
     assert_eq!(
         buffer,
-        include_bytes!("reference/output/enum_with_newtype_variants.gob").as_ref()
+        include_bytes!("reference/output/struct_with_omitted_fields.gob").as_ref()
+    );
+
+    // the same fields sent, whichever rule omits the others
+    let mixed = Record {
+        a: 7,
+        b: Some(0),
+        c: "c".to_owned(),
+        d: 0,
+        e: None,
+        f: Some(-1),
+    };
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&record).unwrap();
+        stream.serialize(&mixed).unwrap();
+    }
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(stream.deserialize::<Record>().unwrap().unwrap(), record);
+    let decoded = stream.deserialize::<Record>().unwrap().unwrap();
+    assert_eq!(
+        decoded,
+        Record {
+            // Some(0) is sent as an omitted zero
+            b: None,
+            ..mixed
+        }
     );
 }
 
+/// Returns the type ids of the messages in `buffer`, with negative ids
+/// denoting type definitions.
+fn message_type_ids(buffer: &[u8]) -> Vec<i64> {
+    common::split_messages(buffer)
+        .unwrap()
+        .iter()
+        .map(|msg| msg.type_id)
+        .collect()
+}
+
 #[test]
-fn enum_with_struct_variants_and_external_tags() {
+fn batch_round_trip() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Record {
+        #[serde(rename = "ID", default)]
+        id: u64,
+        #[serde(rename = "Name")]
+        name: String,
+    }
+
+    let records = (0..1000)
+        .map(|id| Record {
+            id,
+            name: format!("record {}", id),
+        })
+        .collect::<Vec<_>>();
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize_batch(&records).unwrap();
+        stream.serialize_batch(&records[..2]).unwrap();
+    }
+
+    // the count, the definition of Record, and then the records alone
+    let ids = message_type_ids(&buffer);
+    assert_eq!(ids.len(), 1 + 1 + 1000 + 1 + 2);
+    assert_eq!(ids.iter().filter(|&&id| id < 0).count(), 1);
+    assert_eq!(&ids[..3], &[TypeId::UINT.0, -65, 65]);
+
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize_batch::<Record>().unwrap().unwrap(), records);
+    assert_eq!(
+        stream.deserialize_batch::<Record>().unwrap().unwrap(),
+        &records[..2]
+    );
+    assert!(stream.deserialize_batch::<Record>().unwrap().is_none());
+
+    // a batch cut short
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&3u64).unwrap();
+        stream.serialize(&records[0]).unwrap();
+    }
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    let err = stream.deserialize_batch::<Record>().unwrap_err();
+    assert_eq!(err.kind(), gob::error::ErrorKind::Truncated);
+}
+
+#[test]
+fn struct_of_structs_definitions_precede_value() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Coord {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Line {
+        #[serde(rename = "Start")]
+        start: Coord,
+        #[serde(rename = "End")]
+        end: Coord,
+        #[serde(rename = "Tags")]
+        tags: Vec<String>,
+    }
+
+    let line = Line {
+        start: Coord { x: 1, y: 2 },
+        end: Coord { x: 3, y: 4 },
+        tags: vec!["a".to_owned()],
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&line).unwrap();
+        stream.serialize(&line).unwrap();
+    }
+
+    // each definition is sent once, ahead of the first value using it,
+    // and like in Go the struct's definition comes before its field types
+    let ids = message_type_ids(&buffer);
+    assert_eq!(ids, vec![-67, -65, -66, 67, 67]);
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(stream.deserialize::<Line>().unwrap().unwrap(), line);
+    assert_eq!(stream.deserialize::<Line>().unwrap().unwrap(), line);
+    assert!(stream.deserialize::<Line>().unwrap().is_none());
+}
+
+#[test]
+fn gob_encoded_top_level() {
+    use gob::go::{GobEncoded, GobEncoder};
+
+    // a time.Time, as the bytes its GobEncode returned
+    struct Time(Vec<u8>);
+
+    impl GobEncoder for Time {
+        const GO_TYPE_NAME: &'static str = "Time";
+
+        fn gob_encode(&self) -> Vec<u8> {
+            self.0.clone()
+        }
+    }
+
+    let go_buffer = include_bytes!("reference/output/time_top_level.gob");
+    let time = Time(go_buffer[go_buffer.len() - 15..].to_vec());
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&GobEncoded(time)).unwrap();
+    }
+    // the GobEncoderT definition Go requires for a type that decodes itself
+    assert_eq!(&buffer[..17], b"\x10\xff\x81\x05\x01\x01\x04Time\x01\xff\x82\x00\x00\x00");
+    assert_eq!(buffer, go_buffer.as_ref());
+}
+
+#[test]
+fn gob_encoded_zero_value_is_omitted() {
+    use gob::go::{GobEncoded, GobEncoder};
+
+    struct Stamp(u32);
+
+    impl GobEncoder for Stamp {
+        const GO_TYPE_NAME: &'static str = "Stamp";
+
+        fn gob_encode(&self) -> Vec<u8> {
+            self.0.to_be_bytes().to_vec()
+        }
+
+        fn is_zero(&self) -> bool {
+            self.0 == 0
+        }
+    }
+
     #[derive(Serialize, GobSerialize)]
-    enum Enum {
-        #[allow(unused)]
-        V1 {
-            #[serde(rename = "Foo")]
-            foo: bool,
-        },
-        V2 {
-            #[serde(rename = "Bar")]
-            bar: i64,
-            #[serde(rename = "Baz")]
-            baz: u64,
-        },
-        #[allow(unused)]
-        V3 {
-            #[serde(rename = "Quux")]
-            quux: String,
-        },
+    struct Record {
+        #[serde(rename = "A")]
+        a: i64,
+        #[serde(rename = "Stamp")]
+        stamp: GobEncoded<Stamp>,
+        #[serde(rename = "B")]
+        b: i64,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Decoded {
+        #[serde(rename = "A")]
+        a: i64,
+        #[serde(rename = "Stamp")]
+        stamp: Option<ByteBuf>,
+        #[serde(rename = "B")]
+        b: i64,
     }
 
     let mut buffer = Vec::new();
     {
         let mut stream = StreamSerializer::new_with_write(&mut buffer);
-        stream.serialize(&Enum::V2 { bar: 42, baz: 1234 }).unwrap();
+        for stamp in 0..2 {
+            stream
+                .serialize(&Record {
+                    a: 1,
+                    stamp: GobEncoded(Stamp(stamp)),
+                    b: 2,
+                })
+                .unwrap();
+        }
     }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    // the zero stamp encodes to four bytes, but is left out, so that the
+    // delta to B spans it
     assert_eq!(
-        buffer,
-        include_bytes!("reference/output/enum_with_struct_variants.gob").as_ref()
+        stream.deserialize::<Decoded>().unwrap().unwrap(),
+        Decoded {
+            a: 1,
+            stamp: None,
+            b: 2,
+        }
+    );
+    assert_eq!(
+        stream.deserialize::<Decoded>().unwrap().unwrap(),
+        Decoded {
+            a: 1,
+            stamp: Some(ByteBuf::from(vec![0, 0, 0, 1])),
+            b: 2,
+        }
     );
 }
 
-// #[test]
-// fn option_none_to_empty_values() {
-//     let mut buffer = Vec::new();
-//     {
-//         let mut stream = StreamSerializer::new_with_write(&mut buffer);
-//         stream.serialize::<Option<bool>>(&None).unwrap();
-//         stream.serialize::<Option<u64>>(&None).unwrap();
-//         stream.serialize::<Option<i64>>(&None).unwrap();
-//         stream.serialize::<Option<f64>>(&None).unwrap();
-//         stream.serialize::<Option<String>>(&None).unwrap();
-//         stream.serialize::<Option<Bytes>>(&None).unwrap();
-//         stream.serialize::<Option<Vec<bool>>>(&None).unwrap();
-//     }
-//     assert_eq!(
-//         buffer,
-//         include_bytes!("reference/output/empty_values.gob").as_ref()
-//     );
-// }
-
-// #[test]
-// fn option_some_to_non_empty_values() {
-//     let mut buffer = Vec::new();
-//     {
-//         let mut stream = StreamSerializer::new_with_write(&mut buffer);
-//         stream.serialize(&Some(true)).unwrap();
-//         stream.serialize(&Some(42u64)).unwrap();
-//         stream.serialize(&Some(42i64)).unwrap();
-//         stream.serialize(&Some(42f64)).unwrap();
-//         stream.serialize(&Some("foo")).unwrap();
-//         stream.serialize(&Some(Bytes::new(&[0x1, 0x2]))).unwrap();
-//         stream.serialize(&Some(vec![true, false])).unwrap();
-//     }
-//     assert_eq!(
-//         buffer,
-//         include_bytes!("reference/output/non_empty_values.gob").as_ref()
-//     );
-// }
+#[test]
+fn long_array_round_trip() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Digest {
+        #[serde(rename = "Sum")]
+        sum: Array<u8, 64>,
+        #[serde(rename = "Salt")]
+        salt: [u8; 32],
+    }
+
+    let mut sum = [0; 64];
+    for (i, b) in sum.iter_mut().enumerate() {
+        *b = (i * 4) as u8;
+    }
+    let digest = Digest {
+        sum: Array(sum),
+        salt: [7; 32],
+    };
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&digest)
+        .unwrap();
+
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    let decoded = stream.deserialize::<Digest>().unwrap().unwrap();
+    assert_eq!(decoded, digest);
+
+    // the length is part of the type
+    #[derive(Deserialize, Debug)]
+    #[allow(unused)]
+    struct ShortDigest {
+        #[serde(rename = "Sum")]
+        sum: Array<u8, 32>,
+    }
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert!(stream.deserialize::<ShortDigest>().is_err());
+}
+
+#[test]
+fn array_lengths() {
+    fn round_trip<T>(value: &T) -> T
+    where
+        T: GobSerialize + serde::de::DeserializeOwned,
+    {
+        let mut buffer = Vec::new();
+        StreamSerializer::new_with_write(&mut buffer)
+            .serialize(value)
+            .unwrap();
+        let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+        stream.deserialize::<T>().unwrap().unwrap()
+    }
+
+    assert!(round_trip(&[0i64; 0]).is_empty());
+    assert_eq!(round_trip(&[-5i64]), [-5]);
+    assert_eq!(round_trip(&Array([3u16; 33])), Array([3; 33]));
+    assert_eq!(round_trip(&Array([0xffu8; 64])), Array([0xff; 64]));
+
+    // a wrapped array is the same type as a plain one
+    let mut stream = StreamSerializer::new_with_buffer();
+    let plain = <[u8; 32]>::schema_register(stream.schema_mut()).unwrap();
+    let wrapped = <Array<u8, 32>>::schema_register(stream.schema_mut()).unwrap();
+    assert_eq!(plain, wrapped);
+    let empty = <[u8; 0]>::schema_register(stream.schema_mut()).unwrap();
+    assert_ne!(empty, plain);
+}
+
+#[test]
+fn ip_round_trip() {
+    let v4 = IP::from(Ipv4Addr::new(192, 0, 2, 1));
+    let v6 = IP::from("2001:db8::1".parse::<Ipv6Addr>().unwrap());
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&v4).unwrap();
+        stream.serialize(&v6).unwrap();
+    }
+    // a `TextMarshaler` named IP, and the addresses as text
+    assert_eq!(&buffer[..15], b"\x0e\xff\x81\x07\x01\x01\x02IP\x01\xff\x82\x00\x00\x00");
+    assert_eq!(&buffer[15..29], b"\x0d\xff\x82\x00\x09192.0.2.1");
+    assert!(buffer.ends_with(b"\x0b2001:db8::1"));
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(stream.deserialize::<IP>().unwrap().unwrap(), v4);
+    assert_eq!(stream.deserialize::<IP>().unwrap().unwrap(), v6);
+}
+
+#[test]
+fn std_ip_addr_round_trip() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Host {
+        #[serde(rename = "V4")]
+        v4: Ipv4Addr,
+        #[serde(rename = "V6")]
+        v6: Ipv6Addr,
+        #[serde(rename = "Any")]
+        any: IpAddr,
+    }
+
+    let host = Host {
+        v4: Ipv4Addr::new(192, 0, 2, 1),
+        v6: "2001:db8::1".parse().unwrap(),
+        any: IpAddr::V4(Ipv4Addr::LOCALHOST),
+    };
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&host).unwrap();
+    }
+
+    // all three fields share the one IP type, like Go's `net.IP` fields
+    let types = gob::read_schema(&mut Cursor::new(&buffer)).unwrap();
+    assert_eq!(types.ids().count(), 2);
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(stream.deserialize::<Host>().unwrap().unwrap(), host);
+
+    // and as plain byte strings, which an `IP` reads in the byte form
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Bytes::new(&[192, 0, 2, 1])).unwrap();
+    }
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    let ip = stream.deserialize::<IP>().unwrap().unwrap();
+    assert_eq!(IpAddr::from(ip), IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+}
+
+#[test]
+fn ip_from_ipv4_mapped_form() {
+    let mut bytes = vec![19, 10, 0, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff];
+    bytes.extend_from_slice(&[10, 1, 2, 3]);
+    let mut stream = StreamDeserializer::new(Cursor::new(bytes));
+    let ip = stream.deserialize::<IP>().unwrap().unwrap();
+    assert_eq!(IpAddr::from(ip), IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3)));
+}
+
+#[test]
+fn ip_net_round_trip() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Route {
+        #[serde(rename = "Dst")]
+        dst: IPNet,
+        #[serde(rename = "Gateway")]
+        gateway: IP,
+    }
+
+    let routes = vec![
+        Route {
+            dst: IPNet::new(IpAddr::V4(Ipv4Addr::new(10, 20, 0, 0)), 14).unwrap(),
+            gateway: IP::from(Ipv4Addr::new(10, 20, 0, 1)),
+        },
+        Route {
+            dst: IPNet::new("fd00::".parse().unwrap(), 64).unwrap(),
+            gateway: IP::from("fd00::1".parse::<Ipv6Addr>().unwrap()),
+        },
+    ];
+    assert_eq!(routes[0].dst.mask(), &[255, 252, 0, 0]);
+    assert!(IPNet::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 33).is_none());
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&routes).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(stream.deserialize::<Vec<Route>>().unwrap().unwrap(), routes);
+}
+
+#[test]
+fn option_struct_fields() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Pointers {
+        #[serde(rename = "A")]
+        a: Option<i64>,
+        #[serde(rename = "B")]
+        b: Option<String>,
+        #[serde(rename = "C", default)]
+        c: i64,
+    }
+
+    // `None` is omitted like a nil pointer, keeping the deltas of the
+    // fields after it right, and `Some` is sent as the value it holds,
+    // unless that is zero
+    let values = [
+        Pointers {
+            a: None,
+            b: Some("x".to_owned()),
+            c: 3,
+        },
+        Pointers {
+            a: Some(7),
+            b: None,
+            c: 0,
+        },
+        Pointers {
+            a: Some(0),
+            b: None,
+            c: 0,
+        },
+    ];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for value in &values {
+            stream.serialize(value).unwrap();
+        }
+    }
+    assert_gob_eq(
+        &buffer,
+        include_bytes!("reference/output/struct_with_pointer_fields.gob"),
+    );
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(stream.deserialize::<Pointers>().unwrap().unwrap(), values[0]);
+    assert_eq!(stream.deserialize::<Pointers>().unwrap().unwrap(), values[1]);
+    assert_eq!(
+        stream.deserialize::<Pointers>().unwrap().unwrap(),
+        Pointers {
+            a: None,
+            b: None,
+            c: 0,
+        }
+    );
+}
+
+#[test]
+fn nested_option_round_trip() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Nested {
+        #[serde(rename = "V")]
+        v: Option<Option<String>>,
+    }
+
+    // Options are sent like Go pointers, which gob flattens: only a
+    // non-zero value at the innermost level is transmitted, so `Some(None)`
+    // is omitted just like `None`, and so is `Some(Some(""))`.
+    let cases = [
+        (None, None),
+        (Some(None), None),
+        (Some(Some(String::new())), None),
+        (Some(Some("x".to_owned())), Some(Some("x".to_owned()))),
+    ];
+
+    for (v, expected) in cases {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamSerializer::new_with_write(&mut buffer);
+            stream.serialize(&Nested { v }).unwrap();
+        }
+        let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+        assert_eq!(
+            stream.deserialize::<Nested>().unwrap().unwrap(),
+            Nested { v: expected }
+        );
+    }
+}
+
+#[test]
+fn result_round_trip() {
+    let values: Vec<Result<i64, String>> =
+        vec![Ok(42), Err("failed".to_owned()), Ok(0), Err(String::new())];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for value in &values {
+            stream.serialize(value).unwrap();
+        }
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    for value in &values {
+        assert_eq!(
+            &stream.deserialize::<Result<i64, String>>().unwrap().unwrap(),
+            value
+        );
+    }
+    assert!(stream.deserialize::<Result<i64, String>>().unwrap().is_none());
+}
+
+#[test]
+fn result_struct_layout() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Ok::<i64, String>(42)).unwrap();
+        stream.serialize(&Err::<i64, String>("failed".to_owned())).unwrap();
+        stream.serialize(&Ok::<i64, String>(0)).unwrap();
+        stream.serialize(&Err::<i64, String>(String::new())).unwrap();
+    }
+    // type Result struct { Ok bool; Value int64; Error string }
+    assert_eq!(message_type_ids(&buffer[..48]), vec![-65]);
+    assert!(buffer[..48].windows(5).any(|w| w == b"Value"));
+    assert_eq!(
+        &buffer[48..],
+        &[
+            // Ok: true, Value: 42
+            7, 255, 130, 1, 1, 1, 84, 0,
+            // Error: "failed"
+            11, 255, 130, 3, 6, b'f', b'a', b'i', b'l', b'e', b'd', 0,
+            // Ok: true, with the zero Value left out
+            5, 255, 130, 1, 1, 0,
+            // an Err with the zero Error, all fields left out
+            3, 255, 130, 0,
+        ][..]
+    );
+}
+
+#[test]
+fn struct_with_result_field_round_trip() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Response {
+        #[serde(rename = "Id")]
+        id: u64,
+        #[serde(rename = "Outcome")]
+        outcome: Result<Vec<String>, String>,
+    }
+
+    let responses = vec![
+        Response {
+            id: 1,
+            outcome: Ok(vec!["a".to_owned(), "b".to_owned()]),
+        },
+        Response {
+            id: 2,
+            outcome: Err("not found".to_owned()),
+        },
+    ];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for response in &responses {
+            stream.serialize(response).unwrap();
+        }
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    for response in &responses {
+        assert_eq!(&stream.deserialize::<Response>().unwrap().unwrap(), response);
+    }
+}
+
+macro_rules! wide_struct {
+    ($name:ident { $($field:ident),* }) => {
+        // fields left at zero are omitted, so they need a default
+        #[derive(Serialize, Deserialize, GobSerialize, Debug, Default, PartialEq)]
+        #[serde(default)]
+        #[allow(non_snake_case)]
+        struct $name {
+            $($field: i64),*
+        }
+    };
+}
+
+#[test]
+fn wide_struct_with_multi_byte_field_delta() {
+    wide_struct!(Wide {
+        F0, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17,
+        F18, F19, F20, F21, F22, F23, F24, F25, F26, F27, F28, F29, F30, F31, F32, F33,
+        F34, F35, F36, F37, F38, F39, F40, F41, F42, F43, F44, F45, F46, F47, F48, F49,
+        F50, F51, F52, F53, F54, F55, F56, F57, F58, F59, F60, F61, F62, F63, F64, F65,
+        F66, F67, F68, F69, F70, F71, F72, F73, F74, F75, F76, F77, F78, F79, F80, F81,
+        F82, F83, F84, F85, F86, F87, F88, F89, F90, F91, F92, F93, F94, F95, F96, F97,
+        F98, F99, F100, F101, F102, F103, F104, F105, F106, F107, F108, F109, F110,
+        F111, F112, F113, F114, F115, F116, F117, F118, F119, F120, F121, F122, F123,
+        F124, F125, F126, F127, F128, F129, F130, F131, F132, F133, F134, F135, F136,
+        F137, F138, F139, F140, F141, F142, F143, F144, F145, F146, F147, F148, F149,
+        F150, F151, F152, F153, F154, F155, F156, F157, F158, F159, F160, F161, F162,
+        F163, F164, F165, F166, F167, F168, F169, F170, F171, F172, F173, F174, F175,
+        F176, F177, F178, F179, F180, F181, F182, F183, F184, F185, F186, F187, F188,
+        F189, F190, F191, F192, F193, F194, F195, F196, F197, F198, F199
+    });
+
+    let wide = Wide {
+        F0: 1,
+        F190: 7,
+        ..Wide::default()
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&wide).unwrap();
+    }
+    // the delta of 190 from F0 to F190 takes two bytes
+    assert!(buffer.ends_with(&[0x01, 0x02, 0xff, 0xbe, 0x0e, 0x00]));
+    assert_gob_eq(
+        &buffer,
+        include_bytes!("reference/output/wide_struct.gob"),
+    );
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    assert_eq!(stream.deserialize::<Wide>().unwrap().unwrap(), wide);
+}
+
+#[test]
+fn struct_with_more_than_255_fields() {
+    wide_struct!(Wider {
+        F0, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18,
+        F19, F20, F21, F22, F23, F24, F25, F26, F27, F28, F29, F30, F31, F32, F33, F34, F35,
+        F36, F37, F38, F39, F40, F41, F42, F43, F44, F45, F46, F47, F48, F49, F50, F51, F52,
+        F53, F54, F55, F56, F57, F58, F59, F60, F61, F62, F63, F64, F65, F66, F67, F68, F69,
+        F70, F71, F72, F73, F74, F75, F76, F77, F78, F79, F80, F81, F82, F83, F84, F85, F86,
+        F87, F88, F89, F90, F91, F92, F93, F94, F95, F96, F97, F98, F99, F100, F101, F102, F103,
+        F104, F105, F106, F107, F108, F109, F110, F111, F112, F113, F114, F115, F116, F117,
+        F118, F119, F120, F121, F122, F123, F124, F125, F126, F127, F128, F129, F130, F131,
+        F132, F133, F134, F135, F136, F137, F138, F139, F140, F141, F142, F143, F144, F145,
+        F146, F147, F148, F149, F150, F151, F152, F153, F154, F155, F156, F157, F158, F159,
+        F160, F161, F162, F163, F164, F165, F166, F167, F168, F169, F170, F171, F172, F173,
+        F174, F175, F176, F177, F178, F179, F180, F181, F182, F183, F184, F185, F186, F187,
+        F188, F189, F190, F191, F192, F193, F194, F195, F196, F197, F198, F199, F200, F201,
+        F202, F203, F204, F205, F206, F207, F208, F209, F210, F211, F212, F213, F214, F215,
+        F216, F217, F218, F219, F220, F221, F222, F223, F224, F225, F226, F227, F228, F229,
+        F230, F231, F232, F233, F234, F235, F236, F237, F238, F239, F240, F241, F242, F243,
+        F244, F245, F246, F247, F248, F249, F250, F251, F252, F253, F254, F255, F256, F257,
+        F258, F259, F260, F261, F262, F263, F264, F265, F266, F267, F268, F269, F270, F271,
+        F272, F273, F274, F275, F276, F277, F278, F279, F280, F281, F282, F283, F284, F285,
+        F286, F287, F288, F289, F290, F291, F292, F293, F294, F295, F296, F297, F298, F299
+    });
+
+    let wider = Wider {
+        F0: -1,
+        F255: 255,
+        F256: 256,
+        F299: 299,
+        ..Wider::default()
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&wider).unwrap();
+    }
+    // the delta of 255 from F0 to F255 takes two bytes
+    assert!(buffer.ends_with(&[
+        0x01, 0x01, 0xff, 0xff, 0xfe, 0x01, 0xfe, 0x01, 0xfe, 0x02, 0x00, 0x2b, 0xfe, 0x02, 0x56,
+        0x00
+    ]));
+
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize::<Wider>().unwrap().unwrap(), wider);
+
+    #[derive(Deserialize)]
+    struct Last {
+        #[serde(rename = "F299")]
+        last: i64,
+    }
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize::<Last>().unwrap().unwrap().last, 299);
+}
+
+#[test]
+fn enum_with_newtype_variants_and_external_tags() {
+    #[derive(Serialize, GobSerialize)]
+    enum Enum {
+        #[serde(rename = "Var1")]
+        #[allow(unused)]
+        V1(bool),
+        #[serde(rename = "Var2")]
+        V2(i64),
+        #[serde(rename = "Var3")]
+        #[allow(unused)]
+        V3(String),
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Enum::V2(42)).unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/enum_with_newtype_variants.gob").as_ref()
+    );
+}
+
+#[test]
+fn enum_with_struct_variants_and_external_tags() {
+    #[derive(Serialize, GobSerialize)]
+    enum Enum {
+        #[allow(unused)]
+        V1 {
+            #[serde(rename = "Foo")]
+            foo: bool,
+        },
+        V2 {
+            #[serde(rename = "Bar")]
+            bar: i64,
+            #[serde(rename = "Baz")]
+            baz: u64,
+        },
+        #[allow(unused)]
+        V3 {
+            #[serde(rename = "Quux")]
+            quux: String,
+        },
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Enum::V2 { bar: 42, baz: 1234 }).unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/enum_with_struct_variants.gob").as_ref()
+    );
+}
+
+#[test]
+fn enum_with_tuple_variants_round_trip() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    enum Enum {
+        Pair(i64, String),
+        Triple(bool, u64, f64),
+        Single(i64),
+    }
+
+    let values = vec![
+        Enum::Pair(-7, "seven".to_owned()),
+        Enum::Triple(false, 0, 2.5),
+        Enum::Single(42),
+    ];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for value in &values {
+            stream.serialize(value).unwrap();
+        }
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    for value in &values {
+        assert_eq!(&stream.deserialize::<Enum>().unwrap().unwrap(), value);
+    }
+    assert!(stream.deserialize::<Enum>().unwrap().is_none());
+}
+
+#[test]
+fn enum_with_struct_variants_round_trip() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Inner {
+        #[serde(rename = "Name")]
+        name: String,
+    }
+
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    enum Enum {
+        Point {
+            #[serde(rename = "X")]
+            x: i64,
+            #[serde(rename = "Y")]
+            y: i64,
+        },
+        Named {
+            #[serde(rename = "Inner")]
+            inner: Inner,
+            #[serde(rename = "Tags")]
+            tags: Vec<String>,
+        },
+        Pair(u64, Inner),
+    }
+
+    let values = vec![
+        Enum::Point { x: 3, y: -4 },
+        Enum::Named {
+            inner: Inner {
+                name: "a".to_owned(),
+            },
+            tags: vec!["b".to_owned(), "c".to_owned()],
+        },
+        Enum::Pair(
+            9,
+            Inner {
+                name: "d".to_owned(),
+            },
+        ),
+    ];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for value in &values {
+            stream.serialize(value).unwrap();
+        }
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    for value in &values {
+        assert_eq!(&stream.deserialize::<Enum>().unwrap().unwrap(), value);
+    }
+    assert!(stream.deserialize::<Enum>().unwrap().is_none());
+}
+
+#[test]
+fn option_none_to_empty_values() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize::<Option<bool>>(&None).unwrap();
+        stream.serialize::<Option<u64>>(&None).unwrap();
+        stream.serialize::<Option<i64>>(&None).unwrap();
+        stream.serialize::<Option<f64>>(&None).unwrap();
+        stream.serialize::<Option<String>>(&None).unwrap();
+        stream.serialize::<Option<Bytes>>(&None).unwrap();
+        stream.serialize::<Option<Vec<bool>>>(&None).unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/empty_values.gob").as_ref()
+    );
+}
+
+#[test]
+fn option_some_to_non_empty_values() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Some(true)).unwrap();
+        stream.serialize(&Some(42u64)).unwrap();
+        stream.serialize(&Some(42i64)).unwrap();
+        stream.serialize(&Some(42f64)).unwrap();
+        stream.serialize(&Some("foo")).unwrap();
+        stream.serialize(&Some(Bytes::new(&[0x1, 0x2]))).unwrap();
+        stream.serialize(&Some(vec![true, false])).unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/non_empty_values.gob").as_ref()
+    );
+}
+
+#[test]
+fn decode_into_existing_reuses_value() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, Default, PartialEq)]
+    struct Record {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Tags")]
+        tags: Vec<String>,
+    }
+
+    let records = vec![
+        Record {
+            name: "first".to_owned(),
+            tags: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+        },
+        Record {
+            name: "second".to_owned(),
+            tags: vec!["d".to_owned()],
+        },
+    ];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for record in &records {
+            stream.serialize(record).unwrap();
+        }
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    let mut record = Record::default();
+    assert!(stream.decode_into_existing(&mut record).unwrap());
+    assert_eq!(record, records[0]);
+    let tags_capacity = record.tags.capacity();
+    assert!(stream.decode_into_existing(&mut record).unwrap());
+    assert_eq!(record, records[1]);
+    assert_eq!(record.tags.capacity(), tags_capacity);
+    assert!(!stream.decode_into_existing(&mut record).unwrap());
+}
+
+#[test]
+fn sorted_maps_encode_deterministically() {
+    let encode = |map: &HashMap<String, i64>| {
+        let mut buffer = Vec::new();
+        StreamSerializer::builder()
+            .sorted_maps(true)
+            .build(&mut buffer)
+            .serialize(map)
+            .unwrap();
+        buffer
+    };
+
+    let expected = encode(&(0..50).map(|n| (format!("key{}", n), n)).collect());
+    for _ in 0..10 {
+        // every map gets its own random iteration order
+        let map = (0..50).rev().map(|n| (format!("key{}", n), n)).collect();
+        assert_eq!(encode(&map), expected);
+    }
+
+    let mut stream = StreamDeserializer::new(Cursor::new(expected));
+    let decoded = stream.deserialize::<HashMap<String, i64>>().unwrap().unwrap();
+    assert_eq!(decoded.len(), 50);
+    assert_eq!(decoded["key42"], 42);
+}
+
+#[test]
+fn sorted_maps_ignore_map_type_and_hasher() {
+    fn encode<M: GobSerialize>(map: &M) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        StreamSerializer::builder()
+            .sorted_maps(true)
+            .build(&mut buffer)
+            .serialize(map)
+            .unwrap();
+        buffer
+    }
+
+    let entries = (0..50).map(|n| (n * 7 - 100, format!("value{}", n)));
+    let expected = encode(&entries.clone().collect::<BTreeMap<i64, String>>());
+    assert_eq!(encode(&entries.clone().collect::<HashMap<i64, String>>()), expected);
+    let with_hasher: HashMap<i64, String, BuildHasherDefault<DefaultHasher>> =
+        entries.collect();
+    assert_eq!(encode(&with_hasher), expected);
+}
+
+#[test]
+fn schema_mismatch_errors() {
+    #[derive(Serialize)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+    }
+
+    let mut stream = StreamSerializer::new_with_buffer();
+    let vec_id = <Vec<i64>>::schema_register(stream.schema_mut()).unwrap();
+
+    let err = stream
+        .serialize_with_type_id(TypeId(1000), &Point { x: 1 })
+        .unwrap_err();
+    assert_eq!(
+        err.schema_mismatch(),
+        Some(&SchemaMismatch::TypeNotFound {
+            type_id: TypeId(1000)
+        })
+    );
+
+    let err = stream
+        .serialize_with_type_id(vec_id, &Point { x: 1 })
+        .unwrap_err();
+    assert_eq!(
+        err.schema_mismatch(),
+        Some(&SchemaMismatch::UnexpectedType {
+            type_id: vec_id,
+            expected: "a struct or map",
+        })
+    );
+
+    let err = stream
+        .serialize_with_type_id(vec_id, &BTreeMap::<i64, i64>::new())
+        .unwrap_err();
+    assert_eq!(
+        err.schema_mismatch(),
+        Some(&SchemaMismatch::UnexpectedType {
+            type_id: vec_id,
+            expected: "a map",
+        })
+    );
+
+    // errors that are not about the schema carry no details
+    let err = stream
+        .serialize_with_type_id(vec_id, &Bytes::new(&[1, 2]))
+        .unwrap_err();
+    assert_eq!(err.schema_mismatch(), None);
+}
+
+#[test]
+fn newtype_with_underlying_type() {
+    // Go's `type Timeout int64`, which it sends as a plain int64
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, Default, PartialEq)]
+    #[gob(underlying = "int64")]
+    struct Timeout(i64);
+
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Request {
+        #[serde(rename = "Timeout", default)]
+        timeout: Timeout,
+    }
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&Timeout(1234))
+        .unwrap();
+    // no definition, and the value of an int64
+    assert_eq!(buffer, &[5, 4, 0, 254, 9, 164]);
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize::<Timeout>().unwrap(), Some(Timeout(1234)));
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&Request {
+            timeout: Timeout(30),
+        })
+        .unwrap();
+    let types = gob::read_schema(&mut Cursor::new(&buffer)).unwrap();
+    let ids = types.ids().collect::<Vec<_>>();
+    assert_eq!(ids.len(), 1);
+    assert_eq!(
+        types.fields(ids[0]).unwrap().collect::<Vec<_>>(),
+        &[("Timeout", TypeId::INT)]
+    );
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    let request = stream.deserialize::<Request>().unwrap().unwrap();
+    assert_eq!(request.timeout, Timeout(30));
+}
+
+#[test]
+fn c_like_enum() {
+    // sent as the variant's name, like Go's `type Color string`
+    #[derive(Serialize, Deserialize, GobSerialize, Clone, Copy, Debug, PartialEq)]
+    enum Color {
+        Red,
+        Green,
+        Blue,
+    }
+
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Paint {
+        #[serde(rename = "Color")]
+        color: Color,
+    }
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&Color::Green)
+        .unwrap();
+    assert_eq!(buffer, &[8, 12, 0, 5, b'G', b'r', b'e', b'e', b'n']);
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize::<Color>().unwrap(), Some(Color::Green));
+
+    for color in [Color::Red, Color::Green, Color::Blue] {
+        let mut buffer = Vec::new();
+        StreamSerializer::new_with_write(&mut buffer)
+            .serialize(&Paint { color })
+            .unwrap();
+        let types = gob::read_schema(&mut Cursor::new(&buffer)).unwrap();
+        let ids = types.ids().collect::<Vec<_>>();
+        assert_eq!(
+            types.fields(ids[0]).unwrap().collect::<Vec<_>>(),
+            &[("Color", TypeId::STRING)]
+        );
+        let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+        assert_eq!(stream.deserialize::<Paint>().unwrap(), Some(Paint { color }));
+    }
+}
+
+#[test]
+fn c_like_enum_as_int() {
+    // sent as the variant's index, like Go's `type Level int` with iota
+    // constants
+    #[derive(Serialize, Deserialize, GobSerialize, Clone, Copy, Debug, Default, PartialEq)]
+    #[gob(underlying = "int")]
+    enum Level {
+        #[default]
+        Debug,
+        Info,
+        Warn,
+    }
+
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Logger {
+        #[serde(rename = "Level", default)]
+        level: Level,
+    }
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&Level::Warn)
+        .unwrap();
+    assert_eq!(buffer, &[3, 4, 0, 4]);
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize::<Level>().unwrap(), Some(Level::Warn));
+
+    for level in [Level::Debug, Level::Info, Level::Warn] {
+        let mut buffer = Vec::new();
+        StreamSerializer::new_with_write(&mut buffer)
+            .serialize(&Logger { level })
+            .unwrap();
+        let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+        assert_eq!(stream.deserialize::<Logger>().unwrap(), Some(Logger { level }));
+    }
+}
+
+#[test]
+fn type_id_base() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .with_type_id_base(70)
+        .unwrap()
+        .serialize(&Point { x: 1, y: 2 })
+        .unwrap();
+    let types = gob::read_schema(&mut Cursor::new(&buffer)).unwrap();
+    assert_eq!(types.ids().collect::<Vec<_>>(), &[TypeId(70)]);
+    let mut stream = StreamDeserializer::new(Cursor::new(&buffer));
+    assert_eq!(stream.deserialize::<Point>().unwrap(), Some(Point { x: 1, y: 2 }));
+
+    let err = StreamSerializer::new_with_buffer()
+        .with_type_id_base(64)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "serialize error: type id base 64 is below the first user type id 65"
+    );
+}
+
+#[test]
+fn reset_type_definitions() {
+    #[derive(Serialize, Deserialize, GobSerialize, Debug, PartialEq)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let mut stream = StreamSerializer::new_with_write(Vec::new());
+    stream.serialize(&Point { x: 1, y: 2 }).unwrap();
+    let first = std::mem::take(stream.get_mut().get_mut());
+
+    // without a reset, the definition is not written again
+    stream.serialize(&Point { x: 1, y: 2 }).unwrap();
+    let without_reset = std::mem::take(stream.get_mut().get_mut());
+    assert!(without_reset.len() < first.len());
+
+    stream.reset_type_definitions();
+    stream.serialize(&Point { x: 1, y: 2 }).unwrap();
+    let second = std::mem::take(stream.get_mut().get_mut());
+    assert_eq!(second, first);
+
+    for file in [first, second] {
+        let types = gob::read_schema(&mut Cursor::new(&file)).unwrap();
+        assert_eq!(types.ids().collect::<Vec<_>>(), &[TypeId(65)]);
+        let mut stream = StreamDeserializer::new(Cursor::new(&file));
+        assert_eq!(stream.deserialize::<Point>().unwrap(), Some(Point { x: 1, y: 2 }));
+    }
+}
+
+#[test]
+fn struct_with_go_name() {
+    #[derive(Serialize, GobSerialize)]
+    #[gob(go_name = "models.User")]
+    struct User {
+        #[serde(rename = "Name")]
+        name: String,
+    }
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&User {
+            name: "gopher".to_owned(),
+        })
+        .unwrap();
+
+    let types = gob::read_schema(&mut Cursor::new(buffer)).unwrap();
+    let ids = types.ids().collect::<Vec<_>>();
+    assert_eq!(ids.len(), 1);
+    assert_eq!(types.name(ids[0]), Some("models.User"));
+    assert_eq!(
+        types.fields(ids[0]).unwrap().collect::<Vec<_>>(),
+        &[("Name", TypeId::STRING)]
+    );
+}
+
+#[test]
+fn struct_with_own_instant_type() {
+    mod clock {
+        // not the std type the derive rejects
+        #[derive(Serialize, GobSerialize)]
+        pub struct Instant {
+            #[serde(rename = "Ticks")]
+            pub ticks: u64,
+        }
+    }
+
+    #[derive(Serialize, GobSerialize)]
+    struct Sample {
+        #[serde(rename = "At")]
+        at: clock::Instant,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .serialize(&Sample {
+                at: clock::Instant { ticks: 7 },
+            })
+            .unwrap();
+    }
+    assert_eq!(message_type_ids(&buffer), vec![-66, -65, 66]);
+}
@@ -0,0 +1,66 @@
+//! Memory use of `StreamSerializer::serialize_slice`, measured with an
+//! allocator that tracks the peak of allocated bytes. This is the only test
+//! in its binary, so that nothing else allocates while it runs.
+
+extern crate gob;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use gob::StreamSerializer;
+
+struct PeakAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAlloc = PeakAlloc;
+
+/// Counts the bytes written to it and drops them.
+struct CountingWriter(usize);
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn serialize_slice_in_bounded_memory() {
+    let elems = (0..10_000_000u32).collect::<Vec<_>>();
+    let mut writer = CountingWriter(0);
+
+    let baseline = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    StreamSerializer::new_with_write(&mut writer)
+        .serialize_slice(&elems)
+        .unwrap();
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+
+    // most elements take up four bytes
+    assert!(writer.0 > 39_000_000, "wrote {} bytes", writer.0);
+    assert!(peak < 1 << 20, "allocated up to {} bytes", peak);
+}
@@ -1,3 +1,4 @@
+use gob::ser::Framing;
 use gob::{StreamSerializer, StreamDeserializer};
 #[macro_use]
 extern crate serde_derive;
@@ -63,6 +64,161 @@ fn test_gitea_gob_serialize() {
     assert_eq!(user.has_2fa, false);
 }
 
+#[test]
+fn test_gitea_gob_serialize_with_builder() {
+    let user = User {
+        uid: 1,
+        uname: "test".to_string(),
+        email: "test@test.com".to_string(),
+        old_uid: "test".to_string(),
+        has_2fa: true,
+    };
+
+    let mut expected = Vec::new();
+    StreamSerializer::new_with_write(&mut expected)
+        .serialize(&user)
+        .unwrap();
+
+    // the defaults reproduce the plain constructor
+    let mut buffer = Vec::new();
+    StreamSerializer::builder()
+        .build(&mut buffer)
+        .serialize(&user)
+        .unwrap();
+    assert_eq!(buffer, expected);
+
+    let mut sorted = Vec::new();
+    StreamSerializer::builder()
+        .sorted_maps(true)
+        .capacity(256)
+        .build(&mut sorted)
+        .serialize(&user)
+        .unwrap();
+    assert_eq!(sorted.len(), expected.len());
+    let mut stream = StreamDeserializer::new(Cursor::new(sorted));
+    let decoded = stream.deserialize::<User>().unwrap().unwrap();
+    assert_eq!(decoded.uname, "test");
+    assert_eq!(decoded.has_2fa, true);
+
+    let mut framed = Vec::new();
+    StreamSerializer::builder()
+        .framing(Framing::LengthDelimited)
+        .build(&mut framed)
+        .serialize(&user)
+        .unwrap();
+    assert_eq!(&framed[..4], &(expected.len() as u32).to_be_bytes());
+    assert_eq!(&framed[4..], &expected[..]);
+}
+
+#[test]
+fn test_gitea_gob_encode_schema_and_value() {
+    let user = User {
+        uid: 1,
+        uname: "test".to_string(),
+        email: "test@test.com".to_string(),
+        old_uid: "test".to_string(),
+        has_2fa: true,
+    };
+
+    let mut expected = Vec::new();
+    StreamSerializer::new_with_write(&mut expected)
+        .serialize(&user)
+        .unwrap();
+
+    let mut stream = StreamSerializer::new_with_buffer();
+    let (schema, value) = stream.encode_schema_and_value(&user).unwrap();
+    assert!(!schema.is_empty());
+    assert_eq!([&schema[..], &value[..]].concat(), expected);
+
+    // the schema is only produced once
+    let (schema_again, value_again) = stream.encode_schema_and_value(&user).unwrap();
+    assert!(schema_again.is_empty());
+    assert_eq!(value_again, value);
+
+    let mut recombined = schema;
+    recombined.extend_from_slice(&value);
+    recombined.extend_from_slice(&value_again);
+    let mut stream = StreamDeserializer::new(Cursor::new(recombined));
+    for _ in 0..2 {
+        let decoded = stream.deserialize::<User>().unwrap().unwrap();
+        assert_eq!(decoded.uname, "test");
+        assert_eq!(decoded.email, "test@test.com");
+        assert_eq!(decoded.has_2fa, true);
+    }
+    assert!(stream.deserialize::<User>().unwrap().is_none());
+}
+
+#[test]
+fn test_gitea_gob_write_type_definition() {
+    let users = [("alice", 1), ("bob", 2)].map(|(name, uid)| User {
+        uid,
+        uname: name.to_string(),
+        email: format!("{}@test.com", name),
+        old_uid: uid.to_string(),
+        has_2fa: false,
+    });
+
+    let (schema, first) = StreamSerializer::new_with_buffer()
+        .encode_schema_and_value(&users[0])
+        .unwrap();
+    let (_, second) = StreamSerializer::new_with_buffer()
+        .encode_schema_and_value(&users[1])
+        .unwrap();
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.write_type_definition::<User>().unwrap();
+        assert_eq!(stream.get_ref().get_ref().as_slice(), &schema[..]);
+        // nothing left to define
+        stream.write_type_definition::<User>().unwrap();
+        for user in &users {
+            stream.serialize(user).unwrap();
+        }
+    }
+    assert_eq!(buffer, [&schema[..], &first[..], &second[..]].concat());
+
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+    for user in &users {
+        let decoded = stream.deserialize::<User>().unwrap().unwrap();
+        assert_eq!(decoded.uname, user.uname);
+        assert_eq!(decoded.uid, user.uid);
+    }
+    assert!(stream.deserialize::<User>().unwrap().is_none());
+}
+
+#[derive(Serialize, GobSerialize, Deserialize, Debug, PartialEq)]
+#[gob(interpret_as = "map[interface{}]interface{}", type_id=64)]
+struct Session {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uid: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uname: Option<String>,
+}
+
+#[test]
+fn test_gitea_gob_serialize_skipped_fields() {
+    let sessions = [
+        Session {
+            uid: Some(1),
+            uname: None,
+        },
+        Session {
+            uid: None,
+            uname: None,
+        },
+    ];
+
+    for session in &sessions {
+        let mut buffer = Vec::new();
+        StreamSerializer::new_with_write(&mut buffer)
+            .serialize(session)
+            .unwrap();
+        let mut stream = StreamDeserializer::new(Cursor::new(buffer));
+        assert_eq!(&stream.deserialize::<Session>().unwrap().unwrap(), session);
+    }
+}
+
 // fn test_decode_user_info() {
 //     let client = redis::Client::open("redis://cdn.mixstudio.tech:30002/0").unwrap();
 //     let mut con = client.get_connection().unwrap();
@@ -0,0 +1,73 @@
+#![cfg(feature = "trace")]
+
+extern crate gob;
+extern crate log;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+use std::io::Cursor;
+use std::sync::Mutex;
+
+use gob::StreamDeserializer;
+use log::{Log, Metadata, Record};
+
+struct CaptureLogger {
+    records: Mutex<Vec<String>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "gob"
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.records.lock().unwrap().push(format!("{}", record.args()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    records: Mutex::new(Vec::new()),
+};
+
+#[derive(Deserialize)]
+struct User {
+    uid: i64,
+    uname: String,
+}
+
+#[test]
+fn gitea_blob_decode_events() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let buffer = include_bytes!("normal-session-2.bin");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    let user = stream.deserialize::<User>().unwrap().unwrap();
+    assert_eq!(user.uid, 1);
+    assert_eq!(user.uname, "dsotsen");
+
+    let records = LOGGER.records.lock().unwrap();
+    assert_eq!(
+        *records,
+        &[
+            // the map[interface{}]interface{} definition itself
+            "decoding field \"MapT\" (type 23) at offset 1",
+            "decoding field \"common\" (type 18) at offset 2",
+            "decoding field \"Id\" (type 2) at offset 3",
+            "decoding field \"Key\" (type 2) at offset 7",
+            "decoding field \"Elem\" (type 2) at offset 9",
+            "registered type definition 64 \"\"",
+            // the entries of the session value
+            "decoding field \"uid\" at offset 16",
+            "decoding field \"uname\" at offset 42",
+            "decoding field \"email\" at offset 76",
+            "decoding field \"userHasTwoFactorAuth\" at offset 132",
+            "decoding field \"_old_uid\" at offset 160",
+        ]
+    );
+}
@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate serde_gob_derive;
+
+use std::time::Instant;
+
+#[derive(GobSerialize)]
+struct Job {
+    name: String,
+    started: Option<Instant>,
+}
+
+fn main() {}
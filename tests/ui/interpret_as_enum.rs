@@ -0,0 +1,13 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_gob_derive;
+
+#[derive(Serialize, GobSerialize)]
+#[gob(interpret_as = "map[interface{}]interface{}")]
+enum Session {
+    Anonymous,
+    User { uid: i64 },
+}
+
+fn main() {}
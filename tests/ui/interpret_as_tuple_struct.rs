@@ -0,0 +1,10 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_gob_derive;
+
+#[derive(Serialize, GobSerialize)]
+#[gob(interpret_as = "map[interface{}]interface{}")]
+struct Session(i64, String);
+
+fn main() {}
@@ -0,0 +1,22 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_gob_derive;
+extern crate serde;
+
+type Samples = (
+    u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8,
+);
+
+#[derive(Serialize, GobSerialize)]
+struct Reading {
+    sensor: String,
+    #[serde(serialize_with = "serialize_samples")]
+    samples: Samples,
+}
+
+fn serialize_samples<S: serde::Serializer>(_: &Samples, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_unit()
+}
+
+fn main() {}
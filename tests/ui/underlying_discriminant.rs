@@ -0,0 +1,20 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_gob_derive;
+
+#[derive(Serialize, GobSerialize)]
+#[gob(underlying = "int")]
+enum Level {
+    Debug = 1,
+    Info = 5,
+}
+
+#[derive(Serialize, GobSerialize)]
+#[gob(underlying = "string")]
+enum Color {
+    Red = 1,
+    Green = 2,
+}
+
+fn main() {}
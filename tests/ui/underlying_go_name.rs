@@ -0,0 +1,10 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_gob_derive;
+
+#[derive(Serialize, GobSerialize)]
+#[gob(underlying = "int64", go_name = "pkg.Timeout")]
+struct Timeout(i64);
+
+fn main() {}
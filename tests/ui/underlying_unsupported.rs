@@ -0,0 +1,23 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_gob_derive;
+
+#[derive(Serialize, GobSerialize)]
+#[gob(underlying = "map[string]int")]
+struct Counts(Vec<(String, i64)>);
+
+#[derive(Serialize, GobSerialize)]
+#[gob(underlying = "int64")]
+struct Timeouts {
+    read: i64,
+}
+
+#[derive(Serialize, GobSerialize)]
+#[gob(underlying = "float64")]
+enum Level {
+    Low,
+    High,
+}
+
+fn main() {}